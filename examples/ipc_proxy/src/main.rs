@@ -0,0 +1,75 @@
+// Serves a mock display from a child process, and renders a few frames through a proxy.
+// Exits with an error when the frames don't match the mock display.
+extern crate ipc_channel;
+extern crate rust_webvr;
+
+use ipc_channel::ipc::{self, IpcOneShotServer, IpcSender};
+use rust_webvr::api::{MockServiceCreator, VRProxyRequest, VRProxyServer};
+use rust_webvr::{MockVRInit, VRIdAllocator, VRLayer, VRMonotonicClock, VRServiceManager};
+use std::env;
+use std::process::{self, Command};
+use std::sync::Arc;
+
+const EYE_POSITION: [f32; 3] = [0.0, 1.6, 0.0];
+const FRAMES: usize = 10;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() == 3 && args[1] == "--serve" {
+        serve(args[2].clone());
+    } else {
+        render();
+    }
+}
+
+// Child process: serves the mock service until the parent goes away
+fn serve(server_name: String) {
+    let mut init = MockVRInit::default();
+    init.viewer_origin = Some((EYE_POSITION, [0.0, 0.0, 0.0, 1.0]));
+    let service = MockServiceCreator::new_service_with_init(init,
+                                                            Arc::new(VRMonotonicClock::new()),
+                                                            Arc::new(VRIdAllocator::new()));
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel");
+    let bootstrap = IpcSender::connect(server_name).expect("Failed to connect to the parent process");
+    bootstrap.send(sender).expect("Failed to send the proxy channel");
+    VRProxyServer::serve(service, receiver);
+}
+
+// Parent process: renders through the proxy
+#[allow(deprecated)]
+fn render() {
+    let (server, server_name) = IpcOneShotServer::<IpcSender<VRProxyRequest>>::new().expect("Failed to create IPC server");
+    let mut child = Command::new(env::current_exe().unwrap())
+        .arg("--serve")
+        .arg(server_name)
+        .spawn()
+        .expect("Failed to start the server process");
+    let (_, sender) = server.accept().expect("Failed to receive the proxy channel");
+
+    let mut vr = VRServiceManager::new();
+    vr.register_proxy(sender).expect("Failed to connect to the VR proxy server");
+    vr.initialize_services();
+    let display = vr.get_displays().pop().expect("No display served");
+    println!("Display: {}", display.borrow().data().display_name);
+
+    display.borrow_mut().start_present(None);
+    let mut last_timestamp = 0.0;
+    for frame in 0..FRAMES {
+        let data = display.borrow_mut().future_frame_data(0.1, 1000.0).block();
+        println!("Frame {}: {:?} at {}", frame, data.pose.position, data.timestamp);
+        if data.pose.position != Some(EYE_POSITION) || data.timestamp <= last_timestamp {
+            eprintln!("Unexpected frame data: {:?}", data);
+            process::exit(1);
+        }
+        last_timestamp = data.timestamp;
+        display.borrow_mut().render_layer(&VRLayer::default());
+        display.borrow_mut().submit_frame();
+    }
+    display.borrow_mut().stop_present();
+
+    // The server process exits once the proxy went away
+    drop(display);
+    drop(vr);
+    let status = child.wait().expect("Failed to wait for the server process");
+    process::exit(if status.success() { 0 } else { 1 });
+}
@@ -0,0 +1,27 @@
+// Sample VR plugin that exposes the mock service through the plugin ABI.
+// Build it with `cargo build` and load the resulting shared library using
+// `VRServiceManager::load_plugin`.
+extern crate rust_webvr as webvr;
+
+use std::os::raw::c_char;
+use std::sync::Arc;
+use webvr::VRIdAllocator;
+use webvr::api::MockServiceCreator;
+use webvr::vr_plugin::{VRPluginClock, VRPluginCreator, VRPluginService, VR_PLUGIN_ABI_VERSION};
+
+static CREATOR: VRPluginCreator = VRPluginCreator {
+    abi_version: VR_PLUGIN_ABI_VERSION,
+    name: b"mock-plugin\0" as *const u8 as *const c_char,
+    new_service: new_service,
+};
+
+#[no_mangle]
+pub extern "C" fn rust_webvr_plugin_entry() -> *const VRPluginCreator {
+    &CREATOR
+}
+
+unsafe extern "C" fn new_service(clock: VRPluginClock) -> VRPluginService {
+    // The host assigns its own display and gamepad ids, so the plugin can use a private allocator
+    let ids = Arc::new(VRIdAllocator::new());
+    VRPluginService::export(MockServiceCreator::new().new_service(clock.into_clock(), ids))
+}
@@ -14,7 +14,7 @@ use std::mem;
 use std::path::Path;
 use std::{thread, time};
 
-use webvr::{VRServiceManager, VREvent, VRDisplayEvent, VRLayer, VRFrameData, VRFramebufferAttributes};
+use webvr::{VRServiceManager, VRServicePolicy, VREvent, VRDisplayEvent, VRLayer, VRFrameData, VRFramebufferAttributes};
 
 type Vec3 = Vector3<f32>;
 type Mat4 = Matrix4<f32>;
@@ -409,8 +409,9 @@ pub fn main() {
     // Default VRServices are specified using cargo features.
     vr.register_defaults();
     // Add a mock service to allow running the demo when no VRDisplay is available.
-    // If no VR service is found the demo fallbacks to the Mock.
     vr.register_mock();
+    // Only use the Mock when no VR hardware is found.
+    vr.set_policy(VRServicePolicy::prefer_hardware());
     // Intialize all registered VR Services
     vr.initialize_services();
 
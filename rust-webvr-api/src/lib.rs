@@ -25,6 +25,8 @@ extern crate sparkle;
 
 
 pub mod mock;
+pub mod vr_clock;
+pub mod vr_device_identity;
 pub mod vr_display;
 pub mod vr_service;
 pub mod vr_display_data;
@@ -33,6 +35,7 @@ pub mod vr_eye;
 pub mod vr_eye_parameters;
 pub mod vr_framebuffer;
 pub mod vr_frame_data;
+pub mod vr_frame_layout;
 pub mod vr_future_frame_data;
 pub mod vr_layer;
 pub mod vr_pose;
@@ -40,9 +43,15 @@ pub mod vr_stage_parameters;
 pub mod vr_event;
 pub mod vr_field_view;
 pub mod vr_gamepad;
+pub mod vr_id_allocator;
 pub mod vr_main_thread_heartbeat;
+#[cfg(feature = "plugin")]
+pub mod vr_plugin;
 
-pub use mock::{MockVRControlMsg, MockVRInit, MockVRView};
+pub use mock::{MockVRCompositorInit, MockVRControlMsg, MockVRDeviceProfile, MockVRFault, MockVRFaultKind, MockVRFaultPlan, MockVRFaultTrigger, MockVRFrame, MockVRGamepadInit, MockVRInit, MockVRTimeline, MockVRTimelineEvent, MockVRTracking, MockVRView};
+pub use mock::{MockVREntityKind, MockVRHitTestResult, MockVRInputSource, MockVRRegion, MockVRTargetRayMode, MockVRVisibility, MockVRWorld};
+pub use vr_clock::{VRClock, VRClockPtr, VRManualClock, VRMonotonicClock};
+pub use vr_device_identity::VRDeviceIdentity;
 pub use vr_display::{VRDisplay,VRDisplayPtr};
 pub use vr_service::{VRService,VRServiceCreator};
 pub use vr_display_data::VRDisplayData;
@@ -51,6 +60,7 @@ pub use vr_eye::VREye;
 pub use vr_eye_parameters::VREyeParameters;
 pub use vr_framebuffer::{VRFramebuffer, VRFramebufferAttributes, VRViewport};
 pub use vr_frame_data::VRFrameData;
+pub use vr_future_frame_data::VRFrameDataCallback;
 pub use vr_future_frame_data::VRFutureFrameData;
 pub use vr_future_frame_data::VRResolveFrameData;
 pub use vr_layer::VRLayer;
@@ -60,4 +70,7 @@ pub use vr_event::{VREvent, VRDisplayEvent, VRDisplayEventReason, VRGamepadEvent
 pub use vr_field_view::VRFieldOfView;
 pub use vr_gamepad::{VRGamepad, VRGamepadPtr, VRGamepadHand,
                      VRGamepadData, VRGamepadState, VRGamepadButton};
+pub use vr_id_allocator::{VRIdAllocator, VRIdAllocatorPtr};
 pub use vr_main_thread_heartbeat::VRMainThreadHeartbeat;
+#[cfg(feature = "plugin")]
+pub use vr_plugin::{VRPluginClock, VRPluginCreator, VRPluginService, VR_PLUGIN_ABI_VERSION};
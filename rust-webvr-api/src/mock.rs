@@ -1,12 +1,142 @@
 
+use VRDisplayCapabilities;
+use VRFieldOfView;
+use VRGamepadButton;
+use VRGamepadHand;
+use VRLayer;
+
+/// Gamepad messages identify the mock gamepad by an index chosen by the sender.
 #[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
 #[derive(Debug)]
 pub enum MockVRControlMsg {
     SetViewerPose([f32; 3], [f32; 4]),
+    /// The field of view of the eyes is derived from the projection matrices.
     SetViews(MockVRView, MockVRView),
+    /// Left and right projection matrices returned regardless of the requested clip planes.
+    /// None goes back to the matrices derived from the field of view.
+    SetProjectionOverride(Option<([f32; 16], [f32; 16])>),
+    /// Height of the sitting space origin above the floor, in meters.
     SetEyeLevel(f32),
+    /// Width and depth of the play area, in meters.
+    SetStageSize(f32, f32),
+    /// Degrades the tracking of the viewer pose, Full recovers it.
+    SetTracking(MockVRTracking),
+    /// Position and orientation of the floor in the sitting space, None goes back to the eye level.
+    SetFloorOrigin(Option<([f32; 3], [f32; 4])>),
+    /// Points of the play area on the floor, as (x, z) pairs in meters.
+    SetBoundsGeometry(Vec<[f32; 2]>),
+    SetVisibility(MockVRVisibility),
+    /// Real world geometry used by hit tests.
+    SetWorld(Box<MockVRWorld>),
+    ClearWorld,
     Focus,
     Blur,
+    Connect,
+    Disconnect,
+    ConnectGamepad(u32, MockVRGamepadInit),
+    DisconnectGamepad(u32),
+    /// Connects a disconnected gamepad again, as it was.
+    ReconnectGamepad(u32),
+    /// Grip pose of the gamepad.
+    SetGamepadPose(u32, [f32; 3], [f32; 4]),
+    ClearGamepadPose(u32),
+    /// Pose the target ray starts from, the grip pose when None.
+    SetGamepadPointerOrigin(u32, Option<([f32; 3], [f32; 4])>),
+    SetGamepadHand(u32, VRGamepadHand),
+    SetGamepadTargetRayMode(u32, MockVRTargetRayMode),
+    SetGamepadProfiles(u32, Vec<String>),
+    SetGamepadButtons(u32, Vec<VRGamepadButton>),
+    SetGamepadAxes(u32, Vec<f64>),
+    /// The primary action is pressed through the first button of the gamepad.
+    StartSelection(u32),
+    EndSelection(u32),
+    /// Presses the first button during the next frame.
+    SimulateSelect(u32),
+}
+
+/// Visibility of the content presented on a mock display.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MockVRVisibility {
+    Visible,
+    /// Visible, but the input goes somewhere else (e.g. a system menu).
+    VisibleBlurred,
+    Hidden,
+}
+
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MockVRTargetRayMode {
+    Gaze,
+    TrackedPointer,
+    Screen,
+}
+
+impl Default for MockVRTargetRayMode {
+    fn default() -> MockVRTargetRayMode {
+        MockVRTargetRayMode::TrackedPointer
+    }
+}
+
+/// Real world geometry seen by a mock display, in its sitting space.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Default)]
+pub struct MockVRWorld {
+    pub regions: Vec<MockVRRegion>,
+}
+
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone)]
+pub struct MockVRRegion {
+    /// Triangles, as three points each.
+    pub faces: Vec<[[f32; 3]; 3]>,
+    pub kind: MockVREntityKind,
+}
+
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MockVREntityKind {
+    Point,
+    Plane,
+    Mesh,
+}
+
+/// Intersection of a ray with the world of a mock display.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy)]
+pub struct MockVRHitTestResult {
+    pub position: [f32; 3],
+    /// Normal of the face that was hit, facing the origin of the ray.
+    pub normal: [f32; 3],
+    /// Distance from the origin of the ray, in meters.
+    pub distance: f32,
+    pub kind: MockVREntityKind,
+}
+
+/// State of a mock gamepad that the gamepad API doesn't expose.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone)]
+pub struct MockVRInputSource {
+    pub gamepad_id: u32,
+    pub hand: VRGamepadHand,
+    pub target_ray_mode: MockVRTargetRayMode,
+    pub profiles: Vec<String>,
+    pub pointer_origin: Option<([f32; 3], [f32; 4])>,
+    pub grip_origin: Option<([f32; 3], [f32; 4])>,
+    pub selecting: bool,
+}
+
+/// Quality of the tracking of a mock display.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MockVRTracking {
+    Full,
+    /// The position is None, only the orientation is tracked.
+    OrientationOnly,
+    /// The position is estimated from the orientation and marked as emulated.
+    Emulated,
+    /// Neither the position nor the orientation are known.
+    Lost,
 }
 
 #[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
@@ -15,6 +145,159 @@ pub struct MockVRInit {
     pub views: Option<(MockVRView, MockVRView)>,
     pub eye_level: Option<f32>,
     pub viewer_origin: Option<([f32; 3], [f32; 4])>,
+    pub serial: Option<String>,
+    pub timeline: Option<MockVRTimeline>,
+    pub profile: Option<MockVRDeviceProfile>,
+    pub projection_override: Option<([f32; 16], [f32; 16])>,
+    /// Resolves the future frame data from a compositor thread instead of blocking.
+    pub compositor: Option<MockVRCompositorInit>,
+    /// Faults injected by the service created with this display.
+    pub faults: Option<MockVRFaultPlan>,
+}
+
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Default, Clone)]
+pub struct MockVRCompositorInit {
+    /// Frames per second, the refresh rate of the profile when None.
+    pub refresh_rate: Option<f64>,
+    /// Maximum deviation of each vsync, in milliseconds.
+    pub jitter: f64,
+    /// Seed of the jitter, the same seed always gives the same vsync times.
+    pub seed: u64,
+}
+
+/// Failures injected by a mock service, to exercise the error handling of its users.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Default, Clone)]
+pub struct MockVRFaultPlan {
+    /// Seed of the random triggers, the same seed always fails the same calls.
+    pub seed: u64,
+    pub faults: Vec<MockVRFault>,
+}
+
+impl MockVRFaultPlan {
+    /// Plan injecting a single fault, with the default seed.
+    pub fn single(kind: MockVRFaultKind, trigger: MockVRFaultTrigger) -> MockVRFaultPlan {
+        MockVRFaultPlan {
+            seed: 0,
+            faults: vec![MockVRFault { kind, trigger }],
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone)]
+pub struct MockVRFault {
+    pub kind: MockVRFaultKind,
+    pub trigger: MockVRFaultTrigger,
+}
+
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MockVRFaultKind {
+    /// `VRService::initialize` returns an error.
+    FailInitialize,
+    /// `VRService::fetch_displays` returns an error.
+    FailFetchDisplays,
+    /// `VRService::fetch_gamepads` returns an error.
+    FailFetchGamepads,
+    /// `VRService::is_available` returns false. Rolled once per frame, so that
+    /// querying the availability doesn't consume the trigger.
+    Unavailable,
+    /// The future frame data is never resolved, blocking on it hangs until
+    /// `stop_present` is called, after which it panics.
+    StallFrame,
+    /// The display is disconnected while presenting.
+    DisconnectWhilePresenting,
+    /// The display emits `VRDisplayEvent::Exit` while presenting.
+    Exit,
+}
+
+/// Frames are counted across the displays of the service, each `sync_poses`
+/// or `future_frame_data` call starts a new one. The first frame is frame 0.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy)]
+pub enum MockVRFaultTrigger {
+    /// Every time the fault is checked.
+    Always,
+    /// Each time the fault is checked, with the given probability between 0 and 1.
+    Random(f64),
+    /// Once, the first time the fault is checked at or after the given frame.
+    Frame(u64),
+    /// Every time the fault is checked at or after the given frame.
+    FromFrame(u64),
+}
+
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone)]
+pub struct MockVRGamepadInit {
+    pub name: String,
+    pub hand: VRGamepadHand,
+    pub buttons: usize,
+    pub axes: usize,
+    pub serial: Option<String>,
+    #[cfg_attr(feature = "serde-serialization", serde(default))]
+    pub target_ray_mode: MockVRTargetRayMode,
+    /// Input profile names, from the most to the least specific.
+    #[cfg_attr(feature = "serde-serialization", serde(default))]
+    pub profiles: Vec<String>,
+    pub pointer_origin: Option<([f32; 3], [f32; 4])>,
+}
+
+/// Describes the shape of the device simulated by a mock display.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone)]
+pub struct MockVRDeviceProfile {
+    pub name: String,
+    pub display_name: String,
+    /// Per eye resolution.
+    pub render_width: u32,
+    pub render_height: u32,
+    pub left_field_of_view: VRFieldOfView,
+    pub right_field_of_view: VRFieldOfView,
+    /// Interpupillary distance, in meters.
+    pub ipd: f32,
+    /// Frames per second.
+    pub refresh_rate: f64,
+    pub capabilities: VRDisplayCapabilities,
+    /// Size of the play area in meters, None for seated only devices.
+    pub stage_size: Option<(f32, f32)>,
+    /// Controllers connected when the display is created.
+    pub controllers: Vec<MockVRGamepadInit>,
+}
+
+/// A frame submitted to a mock display.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone)]
+pub struct MockVRFrame {
+    pub display_id: u32,
+    pub layer: VRLayer,
+    /// Timestamp of the frame data the frame was rendered with.
+    pub timestamp: f64,
+    /// False when the frame was submitted after its vsync and dropped by the compositor.
+    pub presented: bool,
+    /// Number of vsyncs without a new frame before this one.
+    pub missed_vsyncs: u32,
+    /// RGBA pixels of the layer texture, bottom row first.
+    /// Only read back when requested, for layers with a texture size submitted through submit_layer.
+    /// None as well if GL failed to read them back.
+    pub pixels: Option<Vec<u8>>,
+}
+
+/// Control messages scheduled at given times, replayed by the mock display
+/// as its clock advances.
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug, Default)]
+pub struct MockVRTimeline {
+    pub events: Vec<MockVRTimelineEvent>,
+}
+
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+#[derive(Debug)]
+pub struct MockVRTimelineEvent {
+    /// Milliseconds since the creation of the display.
+    pub time: f64,
+    pub msg: MockVRControlMsg,
 }
 
 #[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
@@ -9,12 +9,14 @@ static DEVICE_ID_COUNTER: AtomicUsize  = AtomicUsize::new(0);
 
 // Generates a unique identifier for any VRDisplay
 #[allow(dead_code)]
+#[deprecated(since="0.18.0", note="please use a `VRIdAllocator` instead")]
 pub fn new_id() -> u32 {
     DEVICE_ID_COUNTER.fetch_add(1, SeqCst) as u32
 }
 
 // Returns the current time in milliseconds
 #[allow(dead_code)]
+#[deprecated(since="0.18.0", note="please use a `VRClock` instead")]
 pub fn timestamp() -> f64 {
     let timespec = time::get_time();
     timespec.sec as f64 * 1000.0 + (timespec.nsec as f64 * 1e-6)
@@ -0,0 +1,93 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub type VRClockPtr = Arc<dyn VRClock>;
+
+/// Time source used by VR services and displays to stamp
+/// `VRFrameData` and `VRGamepadState`. Times are given in milliseconds.
+pub trait VRClock: Send + Sync {
+    /// Returns the current time in milliseconds.
+    fn now(&self) -> f64;
+
+    /// Blocks the calling thread until the clock reaches `deadline`.
+    fn wait_until(&self, deadline: f64);
+}
+
+/// Monotonic clock backed by `std::time::Instant`.
+/// Timestamps are relative to the creation of the clock.
+pub struct VRMonotonicClock {
+    origin: Instant,
+}
+
+impl VRMonotonicClock {
+    pub fn new() -> VRMonotonicClock {
+        VRMonotonicClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for VRMonotonicClock {
+    fn default() -> VRMonotonicClock {
+        VRMonotonicClock::new()
+    }
+}
+
+impl VRClock for VRMonotonicClock {
+    fn now(&self) -> f64 {
+        let elapsed = self.origin.elapsed();
+        elapsed.as_secs() as f64 * 1000.0 + elapsed.subsec_nanos() as f64 * 1e-6
+    }
+
+    fn wait_until(&self, deadline: f64) {
+        let remaining = deadline - self.now();
+        if remaining > 0.0 {
+            thread::sleep(Duration::from_micros((remaining * 1000.0) as u64));
+        }
+    }
+}
+
+/// Clock that only moves when it is told to.
+/// Used to get deterministic timestamps in tests.
+pub struct VRManualClock {
+    now: Mutex<f64>,
+}
+
+impl VRManualClock {
+    pub fn new(start: f64) -> VRManualClock {
+        VRManualClock {
+            now: Mutex::new(start),
+        }
+    }
+
+    /// Sets the current time in milliseconds.
+    pub fn set(&self, now: f64) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Moves the clock forward by `delta` milliseconds.
+    pub fn advance(&self, delta: f64) {
+        *self.now.lock().unwrap() += delta;
+    }
+}
+
+impl Default for VRManualClock {
+    fn default() -> VRManualClock {
+        VRManualClock::new(0.0)
+    }
+}
+
+impl VRClock for VRManualClock {
+    fn now(&self) -> f64 {
+        *self.now.lock().unwrap()
+    }
+
+    // Nobody else is going to move the clock, so waiting jumps straight to the deadline.
+    fn wait_until(&self, deadline: f64) {
+        let mut now = self.now.lock().unwrap();
+        if *now < deadline {
+            *now = deadline;
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+
+/// Stable identity of a physical device, made of the name of the backend
+/// that exposes it and the serial reported by the device.
+/// Unlike display and gamepad ids, it survives reconnections and restarts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+pub struct VRDeviceIdentity {
+    /// Name of the backend exposing the device (e.g. "openvr", "mock").
+    pub backend: String,
+
+    /// Serial number of the device as reported by the backend.
+    pub serial: String,
+}
+
+impl VRDeviceIdentity {
+    pub fn new<B: Into<String>, S: Into<String>>(backend: B, serial: S) -> VRDeviceIdentity {
+        VRDeviceIdentity {
+            backend: backend.into(),
+            serial: serial.into(),
+        }
+    }
+}
+
+impl fmt::Display for VRDeviceIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.backend, self.serial)
+    }
+}
@@ -1,3 +1,4 @@
+use VRDeviceIdentity;
 use VRDisplayCapabilities;
 use VREyeParameters;
 use VRStageParameters;
@@ -7,6 +8,7 @@ use VRStageParameters;
 pub struct VRDisplayData {
     pub display_id: u32,
     pub display_name: String,
+    pub identity: VRDeviceIdentity,
     pub connected: bool,
     pub capabilities: VRDisplayCapabilities,
     pub stage_parameters: Option<VRStageParameters>,
@@ -19,6 +21,7 @@ impl Default for VRDisplayData {
          VRDisplayData {
             display_id: 0,
             display_name: String::new(),
+            identity: VRDeviceIdentity::default(),
             connected: false,
             capabilities: VRDisplayCapabilities::default(),
             stage_parameters: None,
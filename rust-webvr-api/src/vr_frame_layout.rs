@@ -0,0 +1,111 @@
+//! `#[repr(C)]` layout of the frame data, shared by the plugin ABI and the shared memory
+//! pose rings. Changing it is an incompatible change of both.
+
+use {VRFrameData, VRPose};
+
+pub const VR_FRAME_POSE_POSITION: u32 = 1 << 0;
+pub const VR_FRAME_POSE_LINEAR_VELOCITY: u32 = 1 << 1;
+pub const VR_FRAME_POSE_LINEAR_ACCELERATION: u32 = 1 << 2;
+pub const VR_FRAME_POSE_ORIENTATION: u32 = 1 << 3;
+pub const VR_FRAME_POSE_ANGULAR_VELOCITY: u32 = 1 << 4;
+pub const VR_FRAME_POSE_ANGULAR_ACCELERATION: u32 = 1 << 5;
+pub const VR_FRAME_POSE_EMULATED_POSITION: u32 = 1 << 6;
+
+/// `VRPose` where `flags` tells which of the fields are set.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VRFrameLayoutPose {
+    pub flags: u32,
+    pub position: [f32; 3],
+    pub linear_velocity: [f32; 3],
+    pub linear_acceleration: [f32; 3],
+    pub orientation: [f32; 4],
+    pub angular_velocity: [f32; 3],
+    pub angular_acceleration: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VRFrameLayoutData {
+    pub timestamp: f64,
+    pub left_projection_matrix: [f32; 16],
+    pub left_view_matrix: [f32; 16],
+    pub right_projection_matrix: [f32; 16],
+    pub right_view_matrix: [f32; 16],
+    pub pose: VRFrameLayoutPose,
+}
+
+impl<'a> From<&'a VRPose> for VRFrameLayoutPose {
+    fn from(pose: &'a VRPose) -> VRFrameLayoutPose {
+        let mut out = VRFrameLayoutPose::default();
+        if let Some(position) = pose.position {
+            out.flags |= VR_FRAME_POSE_POSITION;
+            out.position = position;
+        }
+        if let Some(velocity) = pose.linear_velocity {
+            out.flags |= VR_FRAME_POSE_LINEAR_VELOCITY;
+            out.linear_velocity = velocity;
+        }
+        if let Some(acceleration) = pose.linear_acceleration {
+            out.flags |= VR_FRAME_POSE_LINEAR_ACCELERATION;
+            out.linear_acceleration = acceleration;
+        }
+        if let Some(orientation) = pose.orientation {
+            out.flags |= VR_FRAME_POSE_ORIENTATION;
+            out.orientation = orientation;
+        }
+        if let Some(velocity) = pose.angular_velocity {
+            out.flags |= VR_FRAME_POSE_ANGULAR_VELOCITY;
+            out.angular_velocity = velocity;
+        }
+        if let Some(acceleration) = pose.angular_acceleration {
+            out.flags |= VR_FRAME_POSE_ANGULAR_ACCELERATION;
+            out.angular_acceleration = acceleration;
+        }
+        if pose.emulated_position {
+            out.flags |= VR_FRAME_POSE_EMULATED_POSITION;
+        }
+        out
+    }
+}
+
+impl<'a> From<&'a VRFrameLayoutPose> for VRPose {
+    fn from(pose: &'a VRFrameLayoutPose) -> VRPose {
+        let has = |flag| pose.flags & flag != 0;
+        VRPose {
+            position: if has(VR_FRAME_POSE_POSITION) { Some(pose.position) } else { None },
+            linear_velocity: if has(VR_FRAME_POSE_LINEAR_VELOCITY) { Some(pose.linear_velocity) } else { None },
+            linear_acceleration: if has(VR_FRAME_POSE_LINEAR_ACCELERATION) { Some(pose.linear_acceleration) } else { None },
+            orientation: if has(VR_FRAME_POSE_ORIENTATION) { Some(pose.orientation) } else { None },
+            angular_velocity: if has(VR_FRAME_POSE_ANGULAR_VELOCITY) { Some(pose.angular_velocity) } else { None },
+            angular_acceleration: if has(VR_FRAME_POSE_ANGULAR_ACCELERATION) { Some(pose.angular_acceleration) } else { None },
+            emulated_position: has(VR_FRAME_POSE_EMULATED_POSITION),
+        }
+    }
+}
+
+impl<'a> From<&'a VRFrameData> for VRFrameLayoutData {
+    fn from(data: &'a VRFrameData) -> VRFrameLayoutData {
+        VRFrameLayoutData {
+            timestamp: data.timestamp,
+            left_projection_matrix: data.left_projection_matrix,
+            left_view_matrix: data.left_view_matrix,
+            right_projection_matrix: data.right_projection_matrix,
+            right_view_matrix: data.right_view_matrix,
+            pose: (&data.pose).into(),
+        }
+    }
+}
+
+impl<'a> From<&'a VRFrameLayoutData> for VRFrameData {
+    fn from(data: &'a VRFrameLayoutData) -> VRFrameData {
+        VRFrameData {
+            timestamp: data.timestamp,
+            left_projection_matrix: data.left_projection_matrix,
+            left_view_matrix: data.left_view_matrix,
+            right_projection_matrix: data.right_projection_matrix,
+            right_view_matrix: data.right_view_matrix,
+            pose: (&data.pose).into(),
+        }
+    }
+}
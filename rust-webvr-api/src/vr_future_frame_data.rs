@@ -19,30 +19,61 @@ enum State<T, U> {
     Blocked(U),
 }
 
+/// Called with the frame data when it's handed out by `block`.
+pub type VRFrameDataCallback = Box<dyn FnOnce(&VRFrameData) + Send>;
+
 #[cfg_attr(feature = "ipc", derive(Serialize, Deserialize))]
-pub struct VRFutureFrameData(State<VRFrameData, Receiver<VRFrameData>>);
+pub struct VRFutureFrameData(
+    State<VRFrameData, Receiver<VRFrameData>>,
+    #[cfg_attr(feature = "ipc", serde(skip))]
+    Option<VRFrameDataCallback>,
+);
 
 #[cfg_attr(feature = "ipc", derive(Serialize, Deserialize))]
 pub struct VRResolveFrameData(State<(), Sender<VRFrameData>>);
 
 impl VRFutureFrameData {
     pub fn resolved(data: VRFrameData) -> VRFutureFrameData {
-        VRFutureFrameData(State::Resolved(data))
+        VRFutureFrameData(State::Resolved(data), None)
     }
 
     pub fn blocked() -> (VRResolveFrameData, VRFutureFrameData) {
         let (send, recv) = channel();
         (
             VRResolveFrameData(State::Blocked(send)),
-            VRFutureFrameData(State::Blocked(recv)),
+            VRFutureFrameData(State::Blocked(recv), None),
         )
     }
 
+    /// Whether the frame data is available, so that `block` returns right away.
+    pub fn is_resolved(&self) -> bool {
+        matches!(self.0, State::Resolved(_))
+    }
+
+    /// Calls `f` with the frame data once it's resolved, from the thread blocking on it.
+    /// The callbacks are called in the order they were added.
+    pub fn on_resolve<F: FnOnce(&VRFrameData) + Send + 'static>(self, f: F) -> VRFutureFrameData {
+        let VRFutureFrameData(state, previous) = self;
+        let callback: VRFrameDataCallback = match previous {
+            Some(previous) => Box::new(move |data: &VRFrameData| {
+                previous(data);
+                f(data);
+            }),
+            None => Box::new(f),
+        };
+        VRFutureFrameData(state, Some(callback))
+    }
+
     pub fn block(self) -> VRFrameData {
-        match self {
-            VRFutureFrameData(State::Resolved(result)) => result,
-            VRFutureFrameData(State::Blocked(recv)) => recv.recv().expect("Failed to get frame data"),
+        let VRFutureFrameData(state, callback) = self;
+        let data = match state {
+            State::Resolved(result) => result,
+            State::Blocked(recv) => recv.recv().expect("Failed to get frame data"),
+        };
+        if let Some(callback) = callback {
+            callback(&data);
         }
+        data
     }
 }
 
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 use std::cell::RefCell;
+use VRDeviceIdentity;
 use VRPose;
 
 pub type VRGamepadPtr = Arc<RefCell<dyn VRGamepad>>;
@@ -47,6 +48,7 @@ pub enum VRGamepadHand {
 pub struct VRGamepadData {
     pub display_id: u32,
     pub name: String,
+    pub identity: VRDeviceIdentity,
     pub hand: VRGamepadHand
 }
 
@@ -55,6 +57,7 @@ impl Default for VRGamepadData {
          Self {
             display_id: 0,
             name: String::new(),
+            identity: VRDeviceIdentity::default(),
             hand: VRGamepadHand::Unknown
          }
      }
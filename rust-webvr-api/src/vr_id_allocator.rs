@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use VRDeviceIdentity;
+
+pub type VRIdAllocatorPtr = Arc<VRIdAllocator>;
+
+/// Hands out display and gamepad ids.
+/// Each `VRServiceManager` owns its own allocator, so ids only depend on
+/// the services registered in that manager.
+pub struct VRIdAllocator {
+    state: Mutex<VRIdAllocatorState>,
+}
+
+struct VRIdAllocatorState {
+    next_id: u32,
+    known: HashMap<VRDeviceIdentity, u32>,
+}
+
+impl VRIdAllocator {
+    pub fn new() -> VRIdAllocator {
+        VRIdAllocator {
+            state: Mutex::new(VRIdAllocatorState {
+                next_id: 0,
+                known: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Returns a fresh id for a device without a stable identity.
+    pub fn new_id(&self) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        id
+    }
+
+    /// Returns the id assigned to the identity, allocating one the first time it is seen.
+    /// Reconnecting the same device maps back to the same id.
+    pub fn id_for(&self, identity: &VRDeviceIdentity) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        if let Some(id) = state.known.get(identity) {
+            return *id;
+        }
+        let id = state.next_id;
+        state.next_id += 1;
+        state.known.insert(identity.clone(), id);
+        id
+    }
+
+    /// Returns the identity that was given the id, if any.
+    pub fn identity_for(&self, id: u32) -> Option<VRDeviceIdentity> {
+        let state = self.state.lock().unwrap();
+        state.known.iter().find(|&(_, &known)| known == id).map(|(identity, _)| identity.clone())
+    }
+}
+
+impl Default for VRIdAllocator {
+    fn default() -> VRIdAllocator {
+        VRIdAllocator::new()
+    }
+}
@@ -0,0 +1,991 @@
+//! Versioned C ABI used to load VR services from shared libraries.
+//!
+//! A plugin exports a `rust_webvr_plugin_entry` function returning a `VRPluginCreator`.
+//! Services, displays and gamepads cross the library boundary as an opaque object plus
+//! a vtable of `extern "C"` functions that mirror the `VRService`, `VRDisplay` and
+//! `VRGamepad` traits. All the data is exchanged using the `#[repr(C)]` types of this module
+//! and of `vr_frame_layout`.
+//!
+//! Plugins written in Rust don't need to implement the vtables by hand:
+//! `VRPluginService::export` wraps any `VRService`.
+
+use {VRClock, VRClockPtr, VRDeviceIdentity, VRDisplayCapabilities, VRDisplayData, VRDisplayEvent};
+use {VRDisplayEventReason, VRDisplayPtr, VREvent, VREyeParameters, VRFieldOfView, VRFramebuffer};
+use {VRFramebufferAttributes, VRGamepadButton, VRGamepadData, VRGamepadEvent};
+use {VRGamepadHand, VRGamepadPtr, VRGamepadState, VRLayer, VRService, VRStageParameters, VRViewport};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Arc;
+
+// The frame data layout is shared with the pose rings, the plugins keep their names for it
+pub use vr_frame_layout::VRFrameLayoutData as VRPluginFrameData;
+pub use vr_frame_layout::VRFrameLayoutPose as VRPluginPose;
+pub use vr_frame_layout::VR_FRAME_POSE_ANGULAR_ACCELERATION as VR_PLUGIN_POSE_ANGULAR_ACCELERATION;
+pub use vr_frame_layout::VR_FRAME_POSE_ANGULAR_VELOCITY as VR_PLUGIN_POSE_ANGULAR_VELOCITY;
+pub use vr_frame_layout::VR_FRAME_POSE_EMULATED_POSITION as VR_PLUGIN_POSE_EMULATED_POSITION;
+pub use vr_frame_layout::VR_FRAME_POSE_LINEAR_ACCELERATION as VR_PLUGIN_POSE_LINEAR_ACCELERATION;
+pub use vr_frame_layout::VR_FRAME_POSE_LINEAR_VELOCITY as VR_PLUGIN_POSE_LINEAR_VELOCITY;
+pub use vr_frame_layout::VR_FRAME_POSE_ORIENTATION as VR_PLUGIN_POSE_ORIENTATION;
+pub use vr_frame_layout::VR_FRAME_POSE_POSITION as VR_PLUGIN_POSE_POSITION;
+
+/// Version of the plugin ABI. Bumped on every incompatible change.
+pub const VR_PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Symbol exported by the plugins.
+pub const VR_PLUGIN_ENTRY_POINT: &[u8] = b"rust_webvr_plugin_entry\0";
+
+/// Signature of the `rust_webvr_plugin_entry` function.
+pub type VRPluginEntry = unsafe extern "C" fn() -> *const VRPluginCreator;
+
+/// Describes the service exported by a plugin.
+#[repr(C)]
+pub struct VRPluginCreator {
+    /// Must be `VR_PLUGIN_ABI_VERSION`.
+    pub abi_version: u32,
+    /// Nul terminated name of the service, used as `VRService::name`.
+    pub name: *const c_char,
+    /// Creates a new service that uses the provided clock.
+    pub new_service: unsafe extern "C" fn(clock: VRPluginClock) -> VRPluginService,
+}
+
+// The name points to static data.
+unsafe impl Sync for VRPluginCreator {}
+
+/// Clock provided by the host to the plugin services.
+/// The plugin must call `release` once it doesn't need the clock anymore.
+#[repr(C)]
+pub struct VRPluginClock {
+    pub ctx: *mut c_void,
+    pub now: unsafe extern "C" fn(ctx: *mut c_void) -> f64,
+    pub wait_until: unsafe extern "C" fn(ctx: *mut c_void, deadline: f64),
+    pub release: unsafe extern "C" fn(ctx: *mut c_void),
+}
+
+unsafe impl Send for VRPluginClock {}
+unsafe impl Sync for VRPluginClock {}
+
+impl VRPluginClock {
+    /// Shares a host clock with a plugin.
+    pub fn new(clock: VRClockPtr) -> VRPluginClock {
+        unsafe extern "C" fn now(ctx: *mut c_void) -> f64 {
+            catch(0.0, || (*(ctx as *const VRClockPtr)).now())
+        }
+        unsafe extern "C" fn wait_until(ctx: *mut c_void, deadline: f64) {
+            catch((), || (*(ctx as *const VRClockPtr)).wait_until(deadline))
+        }
+        unsafe extern "C" fn release(ctx: *mut c_void) {
+            catch((), || drop(Box::from_raw(ctx as *mut VRClockPtr)))
+        }
+        VRPluginClock {
+            ctx: Box::into_raw(Box::new(clock)) as *mut c_void,
+            now,
+            wait_until,
+            release,
+        }
+    }
+
+    /// Wraps the host clock so that it can be used by the services of the plugin.
+    pub fn into_clock(self) -> VRClockPtr {
+        Arc::new(self)
+    }
+}
+
+impl VRClock for VRPluginClock {
+    fn now(&self) -> f64 {
+        unsafe { (self.now)(self.ctx) }
+    }
+
+    fn wait_until(&self, deadline: f64) {
+        unsafe { (self.wait_until)(self.ctx, deadline) }
+    }
+}
+
+impl Drop for VRPluginClock {
+    fn drop(&mut self) {
+        unsafe { (self.release)(self.ctx) }
+    }
+}
+
+/// A service created by a plugin.
+#[repr(C)]
+pub struct VRPluginService {
+    pub obj: *mut c_void,
+    pub vtable: *const VRPluginServiceVTable,
+}
+
+/// Functions of a plugin service. Returned strings and handles are owned by the plugin:
+/// strings are valid until the next call on the same object, handles must be released.
+/// None of the functions may unwind: Rust plugins turn panics into failures.
+#[repr(C)]
+pub struct VRPluginServiceVTable {
+    pub destroy: unsafe extern "C" fn(obj: *mut c_void),
+    pub initialize: unsafe extern "C" fn(obj: *mut c_void) -> bool,
+    /// Error message of the last failed call.
+    pub last_error: unsafe extern "C" fn(obj: *mut c_void) -> *const c_char,
+    /// Enumerates the displays. Returns the number of displays or -1 on failure.
+    pub fetch_displays: unsafe extern "C" fn(obj: *mut c_void) -> i32,
+    /// Returns one of the displays found by the last `fetch_displays` call,
+    /// or a handle with a null `obj` if the index is out of range.
+    pub display_at: unsafe extern "C" fn(obj: *mut c_void, index: u32) -> VRPluginDisplay,
+    /// Enumerates the gamepads. Returns the number of gamepads or -1 on failure.
+    pub fetch_gamepads: unsafe extern "C" fn(obj: *mut c_void) -> i32,
+    /// Returns one of the gamepads found by the last `fetch_gamepads` call,
+    /// or a handle with a null `obj` if the index is out of range.
+    pub gamepad_at: unsafe extern "C" fn(obj: *mut c_void, index: u32) -> VRPluginGamepad,
+    pub is_available: unsafe extern "C" fn(obj: *mut c_void) -> bool,
+    /// Collects the pending events. Returns the number of events.
+    pub poll_events: unsafe extern "C" fn(obj: *mut c_void) -> u32,
+    /// Returns one of the events collected by the last `poll_events` call.
+    /// Out of range indices return a default event.
+    pub event_at: unsafe extern "C" fn(obj: *mut c_void, index: u32) -> VRPluginEvent,
+}
+
+/// A display owned by a plugin.
+#[repr(C)]
+pub struct VRPluginDisplay {
+    pub obj: *mut c_void,
+    pub vtable: *const VRPluginDisplayVTable,
+}
+
+#[repr(C)]
+pub struct VRPluginDisplayVTable {
+    pub release: unsafe extern "C" fn(obj: *mut c_void),
+    pub id: unsafe extern "C" fn(obj: *mut c_void) -> u32,
+    pub data: unsafe extern "C" fn(obj: *mut c_void, out: *mut VRPluginDisplayData),
+    pub immediate_frame_data: unsafe extern "C" fn(obj: *mut c_void, near: f64, far: f64, out: *mut VRPluginFrameData),
+    pub synced_frame_data: unsafe extern "C" fn(obj: *mut c_void, near: f64, far: f64, out: *mut VRPluginFrameData),
+    /// Blocks until the frame data of the next frame is available.
+    pub future_frame_data: unsafe extern "C" fn(obj: *mut c_void, near: f64, far: f64, out: *mut VRPluginFrameData),
+    pub reset_pose: unsafe extern "C" fn(obj: *mut c_void),
+    pub sync_poses: unsafe extern "C" fn(obj: *mut c_void),
+    pub bind_framebuffer: unsafe extern "C" fn(obj: *mut c_void, index: u32),
+    /// Returns the number of framebuffers, which can then be read with `framebuffer_at`.
+    pub framebuffer_count: unsafe extern "C" fn(obj: *mut c_void) -> u32,
+    /// `out` is left untouched if the index is out of range.
+    pub framebuffer_at: unsafe extern "C" fn(obj: *mut c_void, index: u32, out: *mut VRPluginFramebuffer),
+    pub render_layer: unsafe extern "C" fn(obj: *mut c_void, layer: *const VRPluginLayer),
+    pub submit_frame: unsafe extern "C" fn(obj: *mut c_void),
+    /// Renders the layer and submits the frame. The host GL bindings aren't shared with the plugin,
+    /// so the display renders with the GL context current on the calling thread.
+    pub submit_layer: unsafe extern "C" fn(obj: *mut c_void, layer: *const VRPluginLayer),
+    /// `attributes` may be null.
+    pub start_present: unsafe extern "C" fn(obj: *mut c_void, attributes: *const VRPluginFramebufferAttributes),
+    pub stop_present: unsafe extern "C" fn(obj: *mut c_void),
+}
+
+/// A gamepad owned by a plugin.
+#[repr(C)]
+pub struct VRPluginGamepad {
+    pub obj: *mut c_void,
+    pub vtable: *const VRPluginGamepadVTable,
+}
+
+#[repr(C)]
+pub struct VRPluginGamepadVTable {
+    pub release: unsafe extern "C" fn(obj: *mut c_void),
+    pub id: unsafe extern "C" fn(obj: *mut c_void) -> u32,
+    pub data: unsafe extern "C" fn(obj: *mut c_void, out: *mut VRPluginGamepadData),
+    pub state: unsafe extern "C" fn(obj: *mut c_void, out: *mut VRPluginGamepadState),
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VRPluginEyeParameters {
+    pub offset: [f32; 3],
+    pub render_width: u32,
+    pub render_height: u32,
+    pub up_degrees: f64,
+    pub right_degrees: f64,
+    pub down_degrees: f64,
+    pub left_degrees: f64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VRPluginStageParameters {
+    pub sitting_to_standing_transform: [f32; 16],
+    pub size_x: f32,
+    pub size_z: f32,
+}
+
+/// `VRDisplayData`. Strings are nul terminated and owned by the plugin.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct VRPluginDisplayData {
+    pub display_id: u32,
+    pub display_name: *const c_char,
+    pub backend: *const c_char,
+    pub serial: *const c_char,
+    pub connected: bool,
+    pub has_position: bool,
+    pub has_orientation: bool,
+    pub has_external_display: bool,
+    pub can_present: bool,
+    pub presented_by_browser: bool,
+    pub max_layers: u64,
+    pub has_stage_parameters: bool,
+    pub stage_parameters: VRPluginStageParameters,
+    pub left_eye_parameters: VRPluginEyeParameters,
+    pub right_eye_parameters: VRPluginEyeParameters,
+}
+
+impl Default for VRPluginDisplayData {
+    fn default() -> VRPluginDisplayData {
+        VRPluginDisplayData {
+            display_id: 0,
+            display_name: ptr::null(),
+            backend: ptr::null(),
+            serial: ptr::null(),
+            connected: false,
+            has_position: false,
+            has_orientation: false,
+            has_external_display: false,
+            can_present: false,
+            presented_by_browser: false,
+            max_layers: 0,
+            has_stage_parameters: false,
+            stage_parameters: VRPluginStageParameters::default(),
+            left_eye_parameters: VRPluginEyeParameters::default(),
+            right_eye_parameters: VRPluginEyeParameters::default(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VRPluginFramebufferAttributes {
+    pub multiview: bool,
+    pub depth: bool,
+    pub multisampling: bool,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VRPluginFramebuffer {
+    pub eye_index: u32,
+    pub attributes: VRPluginFramebufferAttributes,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VRPluginLayer {
+    pub texture_id: u32,
+    pub left_bounds: [f32; 4],
+    pub right_bounds: [f32; 4],
+    pub has_texture_size: bool,
+    pub texture_width: u32,
+    pub texture_height: u32,
+}
+
+pub const VR_PLUGIN_HAND_UNKNOWN: u32 = 0;
+pub const VR_PLUGIN_HAND_LEFT: u32 = 1;
+pub const VR_PLUGIN_HAND_RIGHT: u32 = 2;
+
+/// `VRGamepadData`. Strings are nul terminated and owned by the plugin.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct VRPluginGamepadData {
+    pub display_id: u32,
+    pub name: *const c_char,
+    pub backend: *const c_char,
+    pub serial: *const c_char,
+    pub hand: u32,
+}
+
+impl Default for VRPluginGamepadData {
+    fn default() -> VRPluginGamepadData {
+        VRPluginGamepadData {
+            display_id: 0,
+            name: ptr::null(),
+            backend: ptr::null(),
+            serial: ptr::null(),
+            hand: VR_PLUGIN_HAND_UNKNOWN,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VRPluginGamepadButton {
+    pub pressed: bool,
+    pub touched: bool,
+}
+
+/// `VRGamepadState`. Arrays are owned by the plugin.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct VRPluginGamepadState {
+    pub gamepad_id: u32,
+    pub connected: bool,
+    pub timestamp: f64,
+    pub axes: *const f64,
+    pub axes_count: u32,
+    pub buttons: *const VRPluginGamepadButton,
+    pub buttons_count: u32,
+    pub pose: VRPluginPose,
+}
+
+impl Default for VRPluginGamepadState {
+    fn default() -> VRPluginGamepadState {
+        VRPluginGamepadState {
+            gamepad_id: 0,
+            connected: false,
+            timestamp: 0.0,
+            axes: ptr::null(),
+            axes_count: 0,
+            buttons: ptr::null(),
+            buttons_count: 0,
+            pose: VRPluginPose::default(),
+        }
+    }
+}
+
+pub const VR_PLUGIN_EVENT_DISPLAY_CONNECT: u32 = 0;
+pub const VR_PLUGIN_EVENT_DISPLAY_DISCONNECT: u32 = 1;
+pub const VR_PLUGIN_EVENT_DISPLAY_ACTIVATE: u32 = 2;
+pub const VR_PLUGIN_EVENT_DISPLAY_DEACTIVATE: u32 = 3;
+pub const VR_PLUGIN_EVENT_DISPLAY_CHANGE: u32 = 4;
+pub const VR_PLUGIN_EVENT_DISPLAY_BLUR: u32 = 5;
+pub const VR_PLUGIN_EVENT_DISPLAY_FOCUS: u32 = 6;
+pub const VR_PLUGIN_EVENT_DISPLAY_PRESENT_CHANGE: u32 = 7;
+pub const VR_PLUGIN_EVENT_DISPLAY_PAUSE: u32 = 8;
+pub const VR_PLUGIN_EVENT_DISPLAY_RESUME: u32 = 9;
+pub const VR_PLUGIN_EVENT_DISPLAY_EXIT: u32 = 10;
+pub const VR_PLUGIN_EVENT_GAMEPAD_CONNECT: u32 = 11;
+pub const VR_PLUGIN_EVENT_GAMEPAD_DISCONNECT: u32 = 12;
+
+pub const VR_PLUGIN_REASON_NAVIGATION: u32 = 0;
+pub const VR_PLUGIN_REASON_MOUNTED: u32 = 1;
+pub const VR_PLUGIN_REASON_UNMOUNTED: u32 = 2;
+
+/// `VREvent`. `id` is the plugin id of the display or gamepad,
+/// the host gets the rest of the data from the device itself.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VRPluginEvent {
+    pub kind: u32,
+    pub id: u32,
+    pub reason: u32,
+    pub presenting: bool,
+}
+
+impl<'a> From<&'a VREyeParameters> for VRPluginEyeParameters {
+    fn from(eye: &'a VREyeParameters) -> VRPluginEyeParameters {
+        VRPluginEyeParameters {
+            offset: eye.offset,
+            render_width: eye.render_width,
+            render_height: eye.render_height,
+            up_degrees: eye.field_of_view.up_degrees,
+            right_degrees: eye.field_of_view.right_degrees,
+            down_degrees: eye.field_of_view.down_degrees,
+            left_degrees: eye.field_of_view.left_degrees,
+        }
+    }
+}
+
+impl<'a> From<&'a VRPluginEyeParameters> for VREyeParameters {
+    fn from(eye: &'a VRPluginEyeParameters) -> VREyeParameters {
+        VREyeParameters {
+            offset: eye.offset,
+            render_width: eye.render_width,
+            render_height: eye.render_height,
+            field_of_view: VRFieldOfView {
+                up_degrees: eye.up_degrees,
+                right_degrees: eye.right_degrees,
+                down_degrees: eye.down_degrees,
+                left_degrees: eye.left_degrees,
+            },
+        }
+    }
+}
+
+impl VRPluginDisplayData {
+    /// Converts the data, copying the strings.
+    ///
+    /// # Safety
+    ///
+    /// The string pointers must be null or valid nul terminated strings.
+    pub unsafe fn to_display_data(&self) -> VRDisplayData {
+        #[allow(deprecated)]
+        let capabilities = VRDisplayCapabilities {
+            has_position: self.has_position,
+            has_orientation: self.has_orientation,
+            has_external_display: self.has_external_display,
+            can_present: self.can_present,
+            presented_by_browser: self.presented_by_browser,
+            max_layers: self.max_layers,
+        };
+        VRDisplayData {
+            display_id: self.display_id,
+            display_name: string_from_ptr(self.display_name),
+            identity: VRDeviceIdentity::new(string_from_ptr(self.backend), string_from_ptr(self.serial)),
+            connected: self.connected,
+            capabilities,
+            stage_parameters: if self.has_stage_parameters {
+                Some(VRStageParameters {
+                    sitting_to_standing_transform: self.stage_parameters.sitting_to_standing_transform,
+                    size_x: self.stage_parameters.size_x,
+                    size_z: self.stage_parameters.size_z,
+                })
+            } else {
+                None
+            },
+            left_eye_parameters: (&self.left_eye_parameters).into(),
+            right_eye_parameters: (&self.right_eye_parameters).into(),
+        }
+    }
+}
+
+impl<'a> From<&'a VRFramebufferAttributes> for VRPluginFramebufferAttributes {
+    fn from(attributes: &'a VRFramebufferAttributes) -> VRPluginFramebufferAttributes {
+        VRPluginFramebufferAttributes {
+            multiview: attributes.multiview,
+            depth: attributes.depth,
+            multisampling: attributes.multisampling,
+        }
+    }
+}
+
+impl<'a> From<&'a VRPluginFramebufferAttributes> for VRFramebufferAttributes {
+    fn from(attributes: &'a VRPluginFramebufferAttributes) -> VRFramebufferAttributes {
+        VRFramebufferAttributes {
+            multiview: attributes.multiview,
+            depth: attributes.depth,
+            multisampling: attributes.multisampling,
+        }
+    }
+}
+
+impl<'a> From<&'a VRFramebuffer> for VRPluginFramebuffer {
+    fn from(framebuffer: &'a VRFramebuffer) -> VRPluginFramebuffer {
+        VRPluginFramebuffer {
+            eye_index: framebuffer.eye_index,
+            attributes: (&framebuffer.attributes).into(),
+            x: framebuffer.viewport.x,
+            y: framebuffer.viewport.y,
+            width: framebuffer.viewport.width,
+            height: framebuffer.viewport.height,
+        }
+    }
+}
+
+impl<'a> From<&'a VRPluginFramebuffer> for VRFramebuffer {
+    fn from(framebuffer: &'a VRPluginFramebuffer) -> VRFramebuffer {
+        VRFramebuffer {
+            eye_index: framebuffer.eye_index,
+            attributes: (&framebuffer.attributes).into(),
+            viewport: VRViewport::new(framebuffer.x, framebuffer.y, framebuffer.width, framebuffer.height),
+        }
+    }
+}
+
+impl<'a> From<&'a VRLayer> for VRPluginLayer {
+    fn from(layer: &'a VRLayer) -> VRPluginLayer {
+        let (width, height) = layer.texture_size.unwrap_or((0, 0));
+        VRPluginLayer {
+            texture_id: layer.texture_id,
+            left_bounds: layer.left_bounds,
+            right_bounds: layer.right_bounds,
+            has_texture_size: layer.texture_size.is_some(),
+            texture_width: width,
+            texture_height: height,
+        }
+    }
+}
+
+impl<'a> From<&'a VRPluginLayer> for VRLayer {
+    fn from(layer: &'a VRPluginLayer) -> VRLayer {
+        VRLayer {
+            texture_id: layer.texture_id,
+            left_bounds: layer.left_bounds,
+            right_bounds: layer.right_bounds,
+            texture_size: if layer.has_texture_size {
+                Some((layer.texture_width, layer.texture_height))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+impl VRPluginGamepadData {
+    /// Converts the data, copying the strings.
+    ///
+    /// # Safety
+    ///
+    /// The string pointers must be null or valid nul terminated strings.
+    pub unsafe fn to_gamepad_data(&self) -> VRGamepadData {
+        VRGamepadData {
+            display_id: self.display_id,
+            name: string_from_ptr(self.name),
+            identity: VRDeviceIdentity::new(string_from_ptr(self.backend), string_from_ptr(self.serial)),
+            hand: match self.hand {
+                VR_PLUGIN_HAND_LEFT => VRGamepadHand::Left,
+                VR_PLUGIN_HAND_RIGHT => VRGamepadHand::Right,
+                _ => VRGamepadHand::Unknown,
+            },
+        }
+    }
+}
+
+impl VRPluginGamepadState {
+    /// Converts the state, copying the arrays.
+    ///
+    /// # Safety
+    ///
+    /// The array pointers must be null or valid for the given counts.
+    pub unsafe fn to_gamepad_state(&self) -> VRGamepadState {
+        let axes = slice_from_ptr(self.axes, self.axes_count);
+        let buttons = slice_from_ptr(self.buttons, self.buttons_count);
+        VRGamepadState {
+            gamepad_id: self.gamepad_id,
+            connected: self.connected,
+            timestamp: self.timestamp,
+            axes: axes.to_vec(),
+            buttons: buttons.iter().map(|b| VRGamepadButton { pressed: b.pressed, touched: b.touched }).collect(),
+            pose: (&self.pose).into(),
+        }
+    }
+}
+
+impl VRPluginEvent {
+    /// Converts an event, keeping only the id of the device it refers to.
+    pub fn from_event(event: &VREvent) -> VRPluginEvent {
+        let event_for = |kind, id| VRPluginEvent { kind, id, .. Default::default() };
+        match *event {
+            VREvent::Display(ref event) => match *event {
+                VRDisplayEvent::Connect(ref data) => event_for(VR_PLUGIN_EVENT_DISPLAY_CONNECT, data.display_id),
+                VRDisplayEvent::Disconnect(id) => event_for(VR_PLUGIN_EVENT_DISPLAY_DISCONNECT, id),
+                VRDisplayEvent::Activate(ref data, reason) => VRPluginEvent {
+                    reason: reason_to_plugin(reason),
+                    .. event_for(VR_PLUGIN_EVENT_DISPLAY_ACTIVATE, data.display_id)
+                },
+                VRDisplayEvent::Deactivate(ref data, reason) => VRPluginEvent {
+                    reason: reason_to_plugin(reason),
+                    .. event_for(VR_PLUGIN_EVENT_DISPLAY_DEACTIVATE, data.display_id)
+                },
+                VRDisplayEvent::Change(ref data) => event_for(VR_PLUGIN_EVENT_DISPLAY_CHANGE, data.display_id),
+                VRDisplayEvent::Blur(ref data) => event_for(VR_PLUGIN_EVENT_DISPLAY_BLUR, data.display_id),
+                VRDisplayEvent::Focus(ref data) => event_for(VR_PLUGIN_EVENT_DISPLAY_FOCUS, data.display_id),
+                VRDisplayEvent::PresentChange(ref data, presenting) => VRPluginEvent {
+                    presenting,
+                    .. event_for(VR_PLUGIN_EVENT_DISPLAY_PRESENT_CHANGE, data.display_id)
+                },
+                VRDisplayEvent::Pause(id) => event_for(VR_PLUGIN_EVENT_DISPLAY_PAUSE, id),
+                VRDisplayEvent::Resume(id) => event_for(VR_PLUGIN_EVENT_DISPLAY_RESUME, id),
+                VRDisplayEvent::Exit(id) => event_for(VR_PLUGIN_EVENT_DISPLAY_EXIT, id),
+            },
+            VREvent::Gamepad(VRGamepadEvent::Connect(_, ref state)) => event_for(VR_PLUGIN_EVENT_GAMEPAD_CONNECT, state.gamepad_id),
+            VREvent::Gamepad(VRGamepadEvent::Disconnect(id)) => event_for(VR_PLUGIN_EVENT_GAMEPAD_DISCONNECT, id),
+        }
+    }
+
+    pub fn reason(&self) -> VRDisplayEventReason {
+        match self.reason {
+            VR_PLUGIN_REASON_MOUNTED => VRDisplayEventReason::Mounted,
+            VR_PLUGIN_REASON_UNMOUNTED => VRDisplayEventReason::Unmounted,
+            _ => VRDisplayEventReason::Navigation,
+        }
+    }
+}
+
+fn reason_to_plugin(reason: VRDisplayEventReason) -> u32 {
+    match reason {
+        VRDisplayEventReason::Navigation => VR_PLUGIN_REASON_NAVIGATION,
+        VRDisplayEventReason::Mounted => VR_PLUGIN_REASON_MOUNTED,
+        VRDisplayEventReason::Unmounted => VR_PLUGIN_REASON_UNMOUNTED,
+    }
+}
+
+/// Copies a nul terminated string, null pointers become empty strings.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a valid nul terminated string.
+pub unsafe fn string_from_ptr(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    }
+}
+
+unsafe fn slice_from_ptr<'a, T>(ptr: *const T, len: u32) -> &'a [T] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        ::std::slice::from_raw_parts(ptr, len as usize)
+    }
+}
+
+fn to_cstring(value: &str) -> CString {
+    CString::new(value.replace('\0', "")).unwrap()
+}
+
+// Panics must not unwind across the plugin boundary, they are turned into the fallback value
+fn catch<T, F: FnOnce() -> T>(fallback: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+fn catch_result<T, F: FnOnce() -> Result<T, String>>(f: F) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| Err("VR plugin panicked".into()))
+}
+
+// Plugin side: exposes Rust implementations of the traits through the vtables.
+
+struct ExportedService {
+    service: Box<dyn VRService>,
+    error: CString,
+    displays: Vec<VRDisplayPtr>,
+    gamepads: Vec<VRGamepadPtr>,
+    events: Vec<VRPluginEvent>,
+}
+
+impl VRPluginService {
+    /// Exposes a service through the plugin ABI.
+    /// Used by plugins to implement `VRPluginCreator::new_service`.
+    pub fn export(service: Box<dyn VRService>) -> VRPluginService {
+        let exported = ExportedService {
+            service,
+            error: CString::default(),
+            displays: Vec::new(),
+            gamepads: Vec::new(),
+            events: Vec::new(),
+        };
+        VRPluginService {
+            obj: Box::into_raw(Box::new(exported)) as *mut c_void,
+            vtable: &SERVICE_VTABLE,
+        }
+    }
+}
+
+static SERVICE_VTABLE: VRPluginServiceVTable = VRPluginServiceVTable {
+    destroy: service_destroy,
+    initialize: service_initialize,
+    last_error: service_last_error,
+    fetch_displays: service_fetch_displays,
+    display_at: service_display_at,
+    fetch_gamepads: service_fetch_gamepads,
+    gamepad_at: service_gamepad_at,
+    is_available: service_is_available,
+    poll_events: service_poll_events,
+    event_at: service_event_at,
+};
+
+unsafe fn service<'a>(obj: *mut c_void) -> &'a mut ExportedService {
+    &mut *(obj as *mut ExportedService)
+}
+
+unsafe extern "C" fn service_destroy(obj: *mut c_void) {
+    catch((), || drop(Box::from_raw(obj as *mut ExportedService)))
+}
+
+unsafe extern "C" fn service_initialize(obj: *mut c_void) -> bool {
+    let service = service(obj);
+    match catch_result(|| service.service.initialize()) {
+        Ok(()) => true,
+        Err(msg) => {
+            service.error = to_cstring(&msg);
+            false
+        }
+    }
+}
+
+unsafe extern "C" fn service_last_error(obj: *mut c_void) -> *const c_char {
+    service(obj).error.as_ptr()
+}
+
+unsafe extern "C" fn service_fetch_displays(obj: *mut c_void) -> i32 {
+    let service = service(obj);
+    match catch_result(|| service.service.fetch_displays()) {
+        Ok(displays) => {
+            service.displays = displays;
+            service.displays.len() as i32
+        }
+        Err(msg) => {
+            service.error = to_cstring(&msg);
+            -1
+        }
+    }
+}
+
+unsafe extern "C" fn service_display_at(obj: *mut c_void, index: u32) -> VRPluginDisplay {
+    let display = match service(obj).displays.get(index as usize) {
+        Some(display) => display.clone(),
+        None => return VRPluginDisplay { obj: ptr::null_mut(), vtable: ptr::null() },
+    };
+    let exported = ExportedDisplay {
+        display,
+        strings: Vec::new(),
+        framebuffers: Vec::new(),
+    };
+    VRPluginDisplay {
+        obj: Box::into_raw(Box::new(exported)) as *mut c_void,
+        vtable: &DISPLAY_VTABLE,
+    }
+}
+
+unsafe extern "C" fn service_fetch_gamepads(obj: *mut c_void) -> i32 {
+    let service = service(obj);
+    match catch_result(|| service.service.fetch_gamepads()) {
+        Ok(gamepads) => {
+            service.gamepads = gamepads;
+            service.gamepads.len() as i32
+        }
+        Err(msg) => {
+            service.error = to_cstring(&msg);
+            -1
+        }
+    }
+}
+
+unsafe extern "C" fn service_gamepad_at(obj: *mut c_void, index: u32) -> VRPluginGamepad {
+    let gamepad = match service(obj).gamepads.get(index as usize) {
+        Some(gamepad) => gamepad.clone(),
+        None => return VRPluginGamepad { obj: ptr::null_mut(), vtable: ptr::null() },
+    };
+    let exported = ExportedGamepad {
+        gamepad,
+        strings: Vec::new(),
+        axes: Vec::new(),
+        buttons: Vec::new(),
+    };
+    VRPluginGamepad {
+        obj: Box::into_raw(Box::new(exported)) as *mut c_void,
+        vtable: &GAMEPAD_VTABLE,
+    }
+}
+
+unsafe extern "C" fn service_is_available(obj: *mut c_void) -> bool {
+    let service = service(obj);
+    catch(false, || service.service.is_available())
+}
+
+unsafe extern "C" fn service_poll_events(obj: *mut c_void) -> u32 {
+    let service = service(obj);
+    let events = catch(Vec::new(), || service.service.poll_events());
+    service.events = events.iter().map(VRPluginEvent::from_event).collect();
+    service.events.len() as u32
+}
+
+unsafe extern "C" fn service_event_at(obj: *mut c_void, index: u32) -> VRPluginEvent {
+    service(obj).events.get(index as usize).cloned().unwrap_or_default()
+}
+
+struct ExportedDisplay {
+    display: VRDisplayPtr,
+    // Storage of the strings returned by the last data call
+    strings: Vec<CString>,
+    framebuffers: Vec<VRFramebuffer>,
+}
+
+static DISPLAY_VTABLE: VRPluginDisplayVTable = VRPluginDisplayVTable {
+    release: display_release,
+    id: display_id,
+    data: display_data,
+    immediate_frame_data: display_immediate_frame_data,
+    synced_frame_data: display_synced_frame_data,
+    future_frame_data: display_future_frame_data,
+    reset_pose: display_reset_pose,
+    sync_poses: display_sync_poses,
+    bind_framebuffer: display_bind_framebuffer,
+    framebuffer_count: display_framebuffer_count,
+    framebuffer_at: display_framebuffer_at,
+    render_layer: display_render_layer,
+    submit_frame: display_submit_frame,
+    submit_layer: display_submit_layer,
+    start_present: display_start_present,
+    stop_present: display_stop_present,
+};
+
+unsafe fn display<'a>(obj: *mut c_void) -> &'a mut ExportedDisplay {
+    &mut *(obj as *mut ExportedDisplay)
+}
+
+unsafe extern "C" fn display_release(obj: *mut c_void) {
+    catch((), || drop(Box::from_raw(obj as *mut ExportedDisplay)))
+}
+
+unsafe extern "C" fn display_id(obj: *mut c_void) -> u32 {
+    catch(0, || display(obj).display.borrow().id())
+}
+
+unsafe extern "C" fn display_data(obj: *mut c_void, out: *mut VRPluginDisplayData) {
+    let exported = display(obj);
+    let data = match catch(None, || Some(exported.display.borrow().data())) {
+        Some(data) => data,
+        None => return,
+    };
+    exported.strings = vec![to_cstring(&data.display_name),
+                            to_cstring(&data.identity.backend),
+                            to_cstring(&data.identity.serial)];
+    #[allow(deprecated)]
+    let presented_by_browser = data.capabilities.presented_by_browser;
+    *out = VRPluginDisplayData {
+        display_id: data.display_id,
+        display_name: exported.strings[0].as_ptr(),
+        backend: exported.strings[1].as_ptr(),
+        serial: exported.strings[2].as_ptr(),
+        connected: data.connected,
+        has_position: data.capabilities.has_position,
+        has_orientation: data.capabilities.has_orientation,
+        has_external_display: data.capabilities.has_external_display,
+        can_present: data.capabilities.can_present,
+        presented_by_browser,
+        max_layers: data.capabilities.max_layers,
+        has_stage_parameters: data.stage_parameters.is_some(),
+        stage_parameters: data.stage_parameters.as_ref().map(|stage| VRPluginStageParameters {
+            sitting_to_standing_transform: stage.sitting_to_standing_transform,
+            size_x: stage.size_x,
+            size_z: stage.size_z,
+        }).unwrap_or_default(),
+        left_eye_parameters: (&data.left_eye_parameters).into(),
+        right_eye_parameters: (&data.right_eye_parameters).into(),
+    };
+}
+
+unsafe extern "C" fn display_immediate_frame_data(obj: *mut c_void, near: f64, far: f64, out: *mut VRPluginFrameData) {
+    catch((), || *out = (&display(obj).display.borrow().immediate_frame_data(near, far)).into())
+}
+
+#[allow(deprecated)]
+unsafe extern "C" fn display_synced_frame_data(obj: *mut c_void, near: f64, far: f64, out: *mut VRPluginFrameData) {
+    catch((), || *out = (&display(obj).display.borrow().synced_frame_data(near, far)).into())
+}
+
+unsafe extern "C" fn display_future_frame_data(obj: *mut c_void, near: f64, far: f64, out: *mut VRPluginFrameData) {
+    catch((), || {
+        let future = display(obj).display.borrow_mut().future_frame_data(near, far);
+        *out = (&future.block()).into();
+    })
+}
+
+unsafe extern "C" fn display_reset_pose(obj: *mut c_void) {
+    catch((), || display(obj).display.borrow_mut().reset_pose())
+}
+
+#[allow(deprecated)]
+unsafe extern "C" fn display_sync_poses(obj: *mut c_void) {
+    catch((), || display(obj).display.borrow_mut().sync_poses())
+}
+
+unsafe extern "C" fn display_bind_framebuffer(obj: *mut c_void, index: u32) {
+    catch((), || display(obj).display.borrow_mut().bind_framebuffer(index))
+}
+
+unsafe extern "C" fn display_framebuffer_count(obj: *mut c_void) -> u32 {
+    let exported = display(obj);
+    exported.framebuffers = catch(Vec::new(), || exported.display.borrow().get_framebuffers());
+    exported.framebuffers.len() as u32
+}
+
+unsafe extern "C" fn display_framebuffer_at(obj: *mut c_void, index: u32, out: *mut VRPluginFramebuffer) {
+    if let Some(framebuffer) = display(obj).framebuffers.get(index as usize) {
+        *out = framebuffer.into();
+    }
+}
+
+#[allow(deprecated)]
+unsafe extern "C" fn display_render_layer(obj: *mut c_void, layer: *const VRPluginLayer) {
+    catch((), || display(obj).display.borrow_mut().render_layer(&(&*layer).into()))
+}
+
+#[allow(deprecated)]
+unsafe extern "C" fn display_submit_frame(obj: *mut c_void) {
+    catch((), || display(obj).display.borrow_mut().submit_frame())
+}
+
+// The host GL bindings can't cross the library boundary, the display renders the layer
+// with its own context like the default `VRDisplay::submit_layer` implementation.
+#[allow(deprecated)]
+unsafe extern "C" fn display_submit_layer(obj: *mut c_void, layer: *const VRPluginLayer) {
+    catch((), || {
+        let mut display = display(obj).display.borrow_mut();
+        display.render_layer(&(&*layer).into());
+        display.submit_frame();
+    })
+}
+
+unsafe extern "C" fn display_start_present(obj: *mut c_void, attributes: *const VRPluginFramebufferAttributes) {
+    let attributes = attributes.as_ref().map(|attributes| attributes.into());
+    catch((), || display(obj).display.borrow_mut().start_present(attributes))
+}
+
+unsafe extern "C" fn display_stop_present(obj: *mut c_void) {
+    catch((), || display(obj).display.borrow_mut().stop_present())
+}
+
+struct ExportedGamepad {
+    gamepad: VRGamepadPtr,
+    // Storage of the strings and arrays returned by the last data and state calls
+    strings: Vec<CString>,
+    axes: Vec<f64>,
+    buttons: Vec<VRPluginGamepadButton>,
+}
+
+static GAMEPAD_VTABLE: VRPluginGamepadVTable = VRPluginGamepadVTable {
+    release: gamepad_release,
+    id: gamepad_id,
+    data: gamepad_data,
+    state: gamepad_state,
+};
+
+unsafe fn gamepad<'a>(obj: *mut c_void) -> &'a mut ExportedGamepad {
+    &mut *(obj as *mut ExportedGamepad)
+}
+
+unsafe extern "C" fn gamepad_release(obj: *mut c_void) {
+    catch((), || drop(Box::from_raw(obj as *mut ExportedGamepad)))
+}
+
+unsafe extern "C" fn gamepad_id(obj: *mut c_void) -> u32 {
+    catch(0, || gamepad(obj).gamepad.borrow().id())
+}
+
+unsafe extern "C" fn gamepad_data(obj: *mut c_void, out: *mut VRPluginGamepadData) {
+    let exported = gamepad(obj);
+    let data = match catch(None, || Some(exported.gamepad.borrow().data())) {
+        Some(data) => data,
+        None => return,
+    };
+    exported.strings = vec![to_cstring(&data.name),
+                            to_cstring(&data.identity.backend),
+                            to_cstring(&data.identity.serial)];
+    *out = VRPluginGamepadData {
+        display_id: data.display_id,
+        name: exported.strings[0].as_ptr(),
+        backend: exported.strings[1].as_ptr(),
+        serial: exported.strings[2].as_ptr(),
+        hand: match data.hand {
+            VRGamepadHand::Unknown => VR_PLUGIN_HAND_UNKNOWN,
+            VRGamepadHand::Left => VR_PLUGIN_HAND_LEFT,
+            VRGamepadHand::Right => VR_PLUGIN_HAND_RIGHT,
+        },
+    };
+}
+
+unsafe extern "C" fn gamepad_state(obj: *mut c_void, out: *mut VRPluginGamepadState) {
+    let exported = gamepad(obj);
+    let state = match catch(None, || Some(exported.gamepad.borrow().state())) {
+        Some(state) => state,
+        None => return,
+    };
+    exported.axes = state.axes.clone();
+    exported.buttons = state.buttons.iter().map(|b| VRPluginGamepadButton { pressed: b.pressed, touched: b.touched }).collect();
+    *out = VRPluginGamepadState {
+        gamepad_id: state.gamepad_id,
+        connected: state.connected,
+        timestamp: state.timestamp,
+        axes: exported.axes.as_ptr(),
+        axes_count: exported.axes.len() as u32,
+        buttons: exported.buttons.as_ptr(),
+        buttons_count: exported.buttons.len() as u32,
+        pose: (&state.pose).into(),
+    };
+}
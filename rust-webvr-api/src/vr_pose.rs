@@ -25,4 +25,7 @@ pub struct VRPose {
     /// Linear acceleration of the sensor given in radians per second squared.
     /// May be None if the sensor is incapable of providing angular acceleration data.
     pub angular_acceleration: Option<[f32; 3]>,
+
+    /// True when the position is estimated rather than tracked, e.g. by a neck model.
+    pub emulated_position: bool,
 }
\ No newline at end of file
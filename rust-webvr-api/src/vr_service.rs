@@ -1,6 +1,8 @@
+use VRClockPtr;
 use VRDisplayPtr;
 use VREvent;
 use VRGamepadPtr;
+use VRIdAllocatorPtr;
 
 pub trait VRService: Send {
     fn initialize(&mut self) -> Result<(), String>;
@@ -12,8 +14,18 @@ pub trait VRService: Send {
     fn is_available(&self) -> bool;
 
     fn poll_events(&self) -> Vec<VREvent>;
+
+    /// Short name of the backend (e.g. "openvr"), used to configure the services.
+    fn name(&self) -> &str {
+        "unknown"
+    }
+
+    /// Returns false when the devices are simulated instead of backed by real hardware.
+    fn is_hardware(&self) -> bool {
+        true
+    }
 }
 
 pub trait VRServiceCreator {
-    fn new_service(&self) -> Box<dyn VRService>;
+    fn new_service(&self, clock: VRClockPtr, ids: VRIdAllocatorPtr) -> Box<dyn VRService>;
 }
@@ -2,7 +2,7 @@ use euclid::Angle;
 use euclid::default::RigidTransform3D;
 use euclid::Trig;
 use euclid::Vector3D;
-use rust_webvr_api::utils;
+use rust_webvr_api::VRDeviceIdentity;
 use rust_webvr_api::VRDisplay;
 use rust_webvr_api::VRDisplayCapabilities;
 use rust_webvr_api::VRDisplayData;
@@ -13,6 +13,7 @@ use rust_webvr_api::VRFutureFrameData;
 use rust_webvr_api::VRFramebuffer;
 use rust_webvr_api::VRFramebufferAttributes;
 use rust_webvr_api::VRGamepadPtr;
+use rust_webvr_api::VRIdAllocatorPtr;
 use rust_webvr_api::VRLayer;
 use rust_webvr_api::VRViewport;
 use sparkle::gl;
@@ -30,6 +31,7 @@ pub type GlWindowVRDisplayPtr = Arc<RefCell<GlWindowVRDisplay>>;
 
 pub struct GlWindowVRDisplay {
     id: u32,
+    identity: VRDeviceIdentity,
     name: String,
     size: PhysicalSize,
     sender: Sender<GlWindowVRMessage>,
@@ -84,6 +86,7 @@ impl VRDisplay for GlWindowVRDisplay {
         VRDisplayData {
             display_id: self.id,
             display_name: self.name.clone(),
+            identity: self.identity.clone(),
             connected: true,
             capabilities: capabilities,
             stage_parameters: None,
@@ -185,10 +188,13 @@ impl GlWindowVRDisplay {
     pub(crate) fn new(
         name: String,
         size: PhysicalSize,
-        sender: Sender<GlWindowVRMessage>
+        sender: Sender<GlWindowVRMessage>,
+        ids: &VRIdAllocatorPtr,
     ) -> GlWindowVRDisplay {
+        let identity = VRDeviceIdentity::new("glwindow", name.clone());
         GlWindowVRDisplay {
-            id: utils::new_id(),
+            id: ids.id_for(&identity),
+            identity: identity,
             name: name,
             size: size,
             sender: sender,
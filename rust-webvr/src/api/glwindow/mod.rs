@@ -2,5 +2,5 @@ mod display;
 mod service;
 mod heartbeat;
 
-pub use self::service::GlWindowVRService;
+pub use self::service::{EventsLoopFactory, GlWindowVRService};
 pub use self::heartbeat::GlWindowVRMainThreadHeartbeat;
@@ -6,6 +6,7 @@ use glutin::dpi::PhysicalSize;
 use rust_webvr_api::VRDisplayPtr;
 use rust_webvr_api::VREvent;
 use rust_webvr_api::VRGamepadPtr;
+use rust_webvr_api::VRIdAllocatorPtr;
 use rust_webvr_api::VRService;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -22,6 +23,7 @@ pub struct GlWindowVRService {
     size: PhysicalSize,
     sender: Sender<GlWindowVRMessage>,
     display: Option<GlWindowVRDisplayPtr>,
+    ids: VRIdAllocatorPtr,
 }
 
 // This is very very unsafe, but the API requires it.
@@ -48,6 +50,14 @@ impl VRService for GlWindowVRService {
     fn poll_events(&self) -> Vec<VREvent> {
         vec![]
     }
+
+    fn name(&self) -> &str {
+        "glwindow"
+    }
+
+    fn is_hardware(&self) -> bool {
+        false
+    }
 }
 
 impl GlWindowVRService {
@@ -57,6 +67,7 @@ impl GlWindowVRService {
         gl_context: WindowedContext<NotCurrent>,
 	events_loop_factory: EventsLoopFactory,
         gl: Rc<dyn Gl>,
+        ids: VRIdAllocatorPtr,
     ) -> (GlWindowVRService, GlWindowVRMainThreadHeartbeat) {
         let (sender, receiver) = channel();
         let size = gl_context.window().get_inner_size().expect("No window size");
@@ -67,16 +78,37 @@ impl GlWindowVRService {
             size: size.to_physical(hidpi),
             sender: sender,
             display: None,
+            ids: ids,
         };
         (service, heartbeat)
     }
 
+    // Same as new, but resizes the window to the given size in physical pixels first.
+    // This function should be called from the main thread.
+    pub fn new_with_window_size(
+        name: String,
+        gl_context: WindowedContext<NotCurrent>,
+        events_loop_factory: EventsLoopFactory,
+        gl: Rc<dyn Gl>,
+        ids: VRIdAllocatorPtr,
+        window_size: (u32, u32),
+    ) -> (GlWindowVRService, GlWindowVRMainThreadHeartbeat) {
+        let size = PhysicalSize::new(window_size.0 as f64, window_size.1 as f64);
+        let hidpi = gl_context.window().get_hidpi_factor();
+        gl_context.window().set_inner_size(size.to_logical(hidpi));
+        let (mut service, heartbeat) = GlWindowVRService::new(name, gl_context, events_loop_factory, gl, ids);
+        // The window may be resized asynchronously, don't wait for it to report the new size
+        service.size = size;
+        (service, heartbeat)
+    }
+
     fn get_display(&mut self) -> &mut GlWindowVRDisplayPtr {
         let name = &self.name;
         let sender = &self.sender;
         let size = self.size;
+        let ids = &self.ids;
         self.display.get_or_insert_with(|| {
-            let display = GlWindowVRDisplay::new(name.clone(), size, sender.clone());
+            let display = GlWindowVRDisplay::new(name.clone(), size, sender.clone(), ids);
             Arc::new(RefCell::new(display))
         })
     }
@@ -1,6 +1,6 @@
 #![cfg(feature = "googlevr")]
-use {VRDisplay, VRDisplayData, VRDisplayCapabilities, VRFramebuffer, VRFramebufferAttributes,
-    VREvent, VRDisplayEvent, VREyeParameters, VRFrameData, VRLayer, VRViewport, VRGamepadPtr};
+use {VRClockPtr, VRDeviceIdentity, VRDisplay, VRDisplayData, VRDisplayCapabilities, VRFramebuffer, VRFramebufferAttributes,
+    VREvent, VRDisplayEvent, VREyeParameters, VRFrameData, VRLayer, VRViewport, VRGamepadPtr, VRIdAllocatorPtr};
 use super::service::GoogleVRService;
 use super::gamepad::{GoogleVRGamepad, GoogleVRGamepadPtr};
 use rust_webvr_api::utils;
@@ -40,12 +40,14 @@ pub struct GoogleVRDisplay {
     fbo_id: u32,
     fbo_texture: u32,
     display_id: u32,
+    identity: VRDeviceIdentity,
     presenting: bool,
     paused: bool,
     new_events_hint: bool,
     pending_events: Mutex<Vec<VREvent>>,
     processed_events: Mutex<Vec<VREvent>>,
     attributes: VRFramebufferAttributes,
+    clock: VRClockPtr,
 }
 
 unsafe impl Send for GoogleVRDisplay {}
@@ -69,6 +71,7 @@ impl VRDisplay for GoogleVRDisplay {
             data.display_name = format!("{} {}", vendor, model);
         }
         data.display_id = self.display_id;
+        data.identity = self.identity.clone();
         data.connected = true;
     
         self.fetch_capabilities(&mut data.capabilities);
@@ -318,7 +321,9 @@ impl VRDisplay for GoogleVRDisplay {
 impl GoogleVRDisplay {
     pub unsafe fn new(service: *const GoogleVRService,
                       ctx: *mut gvr::gvr_context,
-                      controller_ctx: *mut gvr::gvr_controller_context) -> Arc<RefCell<GoogleVRDisplay>> {
+                      controller_ctx: *mut gvr::gvr_controller_context,
+                      clock: VRClockPtr,
+                      ids: &VRIdAllocatorPtr) -> Arc<RefCell<GoogleVRDisplay>> {
         let list = gvr::gvr_buffer_viewport_list_create(ctx);
 
         // gvr_refresh_viewer_profile must be called before getting recommended bufer viewports.
@@ -335,8 +340,12 @@ impl GoogleVRDisplay {
         let right_eye_vp = gvr::gvr_buffer_viewport_create(ctx);
         gvr::gvr_buffer_viewport_list_get_item(list, gvr::gvr_eye::GVR_RIGHT_EYE as usize, right_eye_vp);
 
-        let display_id = utils::new_id();
-        let gamepad = GoogleVRGamepad::new(ctx, controller_ctx, display_id).ok();
+        // GoogleVR doesn't expose serial numbers, the viewer model is the best we can get.
+        let identity = VRDeviceIdentity::new("googlevr", format!("{} {}",
+                                                                 to_string(gvr::gvr_get_viewer_vendor(ctx)),
+                                                                 to_string(gvr::gvr_get_viewer_model(ctx))));
+        let display_id = ids.id_for(&identity);
+        let gamepad = GoogleVRGamepad::new(ctx, controller_ctx, display_id, clock.clone(), ids).ok();
 
         if gamepad.is_none() {
             warn!("No googlevr gamepad found");
@@ -359,13 +368,15 @@ impl GoogleVRDisplay {
             fbo_id: 0,
             fbo_texture: 0,
             display_id,
+            identity,
             presenting: false,
             paused: false,
             new_events_hint: false,
             pending_events: Mutex::new(Vec::new()),
             processed_events: Mutex::new(Vec::new()),
             attributes: Default::default(),
-            gamepad
+            gamepad,
+            clock,
         }))
     }
 
@@ -505,7 +516,7 @@ impl GoogleVRDisplay {
         out.pose.position = Some([view_matrix[12], view_matrix[13], view_matrix[14]]);
 
         // Timestamp
-        out.timestamp = utils::timestamp();
+        out.timestamp = self.clock.now();
     }
 
     fn update_recommended_buffer_viewports(&self) {
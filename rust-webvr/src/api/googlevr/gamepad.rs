@@ -1,6 +1,5 @@
 #![cfg(feature = "googlevr")]
-use {VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadState, VRGamepadButton};
-use rust_webvr_api::utils;
+use {VRClockPtr, VRDeviceIdentity, VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadState, VRGamepadButton, VRIdAllocatorPtr};
 use gvr_sys as gvr;
 use gvr_sys::gvr_controller_api_status::*;
 use gvr_sys::gvr_controller_button::*;
@@ -18,9 +17,11 @@ pub struct GoogleVRGamepad {
     controller_ctx: *mut gvr::gvr_controller_context,
     state: *mut gvr::gvr_controller_state,
     gamepad_id: u32,
+    identity: VRDeviceIdentity,
     display_id: u32,
     paused: bool,
     system_paused: bool,
+    clock: VRClockPtr,
 }
 
 unsafe impl Send for GoogleVRGamepad {}
@@ -29,16 +30,22 @@ unsafe impl Sync for GoogleVRGamepad {}
 impl GoogleVRGamepad {
     pub unsafe fn new(ctx: *mut gvr::gvr_context,
                       controller_ctx: *mut gvr::gvr_controller_context,
-                      display_id: u32)
+                      display_id: u32,
+                      clock: VRClockPtr,
+                      ids: &VRIdAllocatorPtr)
                       -> Result<Arc<RefCell<GoogleVRGamepad>>, String> {
+        // There is a single Daydream controller per headset.
+        let identity = VRDeviceIdentity::new("googlevr", "daydream-controller");
         let gamepad = Self {
             ctx: ctx,
             controller_ctx: controller_ctx,
             state: gvr::gvr_controller_state_create(),
-            gamepad_id: utils::new_id(),
+            gamepad_id: ids.id_for(&identity),
+            identity: identity,
             display_id: display_id,
             paused: false,
             system_paused: false,
+            clock: clock,
         };
         gvr::gvr_controller_state_update(controller_ctx, 0, gamepad.state);
         let api_status = gvr::gvr_controller_state_get_api_status(gamepad.state);
@@ -107,6 +114,7 @@ impl VRGamepad for GoogleVRGamepad {
         VRGamepadData {
             display_id: self.display_id,
             name: "Daydream Controller".into(),
+            identity: self.identity.clone(),
             hand: hand
         }
     }
@@ -115,6 +123,7 @@ impl VRGamepad for GoogleVRGamepad {
         let mut out = VRGamepadState::default();
 
         out.gamepad_id = self.gamepad_id;
+        out.timestamp = self.clock.now();
         unsafe {
             gvr::gvr_controller_state_update(self.controller_ctx, 0, self.state);
             let connection_state = gvr::gvr_controller_state_get_connection_state(self.state);
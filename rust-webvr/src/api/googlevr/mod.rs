@@ -4,7 +4,7 @@ mod display;
 mod gamepad;
 mod service;
 
-use {VRService, VRServiceCreator};
+use {VRClockPtr, VRIdAllocatorPtr, VRService, VRServiceCreator};
 
 pub struct GoogleVRServiceCreator;
 
@@ -15,8 +15,8 @@ impl GoogleVRServiceCreator {
 }
 
 impl VRServiceCreator for GoogleVRServiceCreator {
-     fn new_service(&self) -> Box<VRService> {
-         Box::new(service::GoogleVRService::new())
+     fn new_service(&self, clock: VRClockPtr, ids: VRIdAllocatorPtr) -> Box<VRService> {
+         Box::new(service::GoogleVRService::new(clock, ids))
      }
 }
 
@@ -1,6 +1,6 @@
 #![cfg(feature = "googlevr")]
 
-use {VRService, VRDisplay, VRDisplayPtr, VREvent, VRGamepadPtr};
+use {VRClockPtr, VRIdAllocatorPtr, VRService, VRDisplay, VRDisplayPtr, VREvent, VRGamepadPtr};
 use super::display::{GoogleVRDisplay, GoogleVRDisplayPtr};
 #[cfg(target_os="android")]
 use rust_webvr_api::jni_utils::JNIScope;
@@ -17,6 +17,8 @@ pub struct GoogleVRService {
     ctx: *mut gvr::gvr_context,
     controller_ctx: *mut gvr::gvr_controller_context,
     display: Option<GoogleVRDisplayPtr>,
+    clock: VRClockPtr,
+    ids: VRIdAllocatorPtr,
     #[cfg(target_os="android")]
     pub java_object: ndk::jobject,
     #[cfg(target_os="android")]
@@ -68,26 +70,34 @@ impl VRService for GoogleVRService {
         }
         events
     }
+
+    fn name(&self) -> &str {
+        "googlevr"
+    }
 }
 
 impl GoogleVRService {
     #[cfg(target_os="android")]
-    pub fn new() -> GoogleVRService {
+    pub fn new(clock: VRClockPtr, ids: VRIdAllocatorPtr) -> GoogleVRService {
         GoogleVRService {
             ctx: ptr::null_mut(),
             controller_ctx: ptr::null_mut(),
             display: None,
+            clock: clock,
+            ids: ids,
             java_object: ptr::null_mut(),
             java_class: ptr::null_mut()
         }
     }
 
     #[cfg(not(target_os="android"))]
-    pub fn new() -> GoogleVRService {
+    pub fn new(clock: VRClockPtr, ids: VRIdAllocatorPtr) -> GoogleVRService {
         GoogleVRService {
             ctx: ptr::null_mut(),
             controller_ctx: ptr::null_mut(),
             display: None,
+            clock: clock,
+            ids: ids,
         }
     }
 
@@ -151,7 +161,7 @@ impl GoogleVRService {
         if let Some(ref d) = self.display {
             Ok(d)
         } else {
-            self.display = unsafe { Some(GoogleVRDisplay::new(self, self.ctx, self.controller_ctx)) };
+            self.display = unsafe { Some(GoogleVRDisplay::new(self, self.ctx, self.controller_ctx, self.clock.clone(), &self.ids)) };
             Ok(self.display.as_ref().unwrap())
         }
     }
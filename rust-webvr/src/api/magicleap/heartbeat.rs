@@ -7,12 +7,13 @@ use gleam::gl::Gl;
 use gleam::gl;
 use gleam::gl::types::GLuint;
 use gleam::gl::types::GLint;
-use rust_webvr_api::utils;
+use rust_webvr_api::VRDeviceIdentity;
 use rust_webvr_api::VRDisplayCapabilities;
 use rust_webvr_api::VRDisplayData;
 use rust_webvr_api::VREyeParameters;
 use rust_webvr_api::VRFieldOfView;
 use rust_webvr_api::VRFrameData;
+use rust_webvr_api::VRIdAllocatorPtr;
 use rust_webvr_api::VRLayer;
 use rust_webvr_api::VRPose;
 use rust_webvr_api::VRResolveFrameData;
@@ -80,6 +81,7 @@ pub struct MagicLeapVRMainThreadHeartbeat {
     frame_handle: MLHandle,
     cameras: MLGraphicsVirtualCameraInfoArray,
     timestamp: f64,
+    ids: VRIdAllocatorPtr,
 }
 
 impl VRMainThreadHeartbeat for MagicLeapVRMainThreadHeartbeat {
@@ -102,6 +104,7 @@ impl MagicLeapVRMainThreadHeartbeat {
         receiver: Receiver<MagicLeapVRMessage>,
         egl_context: EGLContext,
         gl: Rc<Gl>,
+        ids: VRIdAllocatorPtr,
     ) -> Result<MagicLeapVRMainThreadHeartbeat, MLResult> {
         info!("Creating VRMainThreadHeartbeat");
         let options = MLGraphicsOptions {
@@ -137,6 +140,7 @@ impl MagicLeapVRMainThreadHeartbeat {
             frame_handle: MLHandle::default(),
             cameras: MLGraphicsVirtualCameraInfoArray::default(),
             timestamp: 0.0,
+            ids,
         })
     }
 
@@ -318,6 +322,7 @@ impl MagicLeapVRMainThreadHeartbeat {
         let left_eye_parameters = Self::eye_parameters(&targets.buffers[0], &self.cameras.virtual_cameras[0], -EYE_DISTANCE);
         let right_eye_parameters = Self::eye_parameters(&targets.buffers[1], &self.cameras.virtual_cameras[1], EYE_DISTANCE);
         let display_name = self.display_name.clone();
+        let identity = VRDeviceIdentity::new("magicleap", display_name.clone());
         let capabilities = VRDisplayCapabilities {
             has_position: true,
             has_orientation: true,
@@ -327,13 +332,14 @@ impl MagicLeapVRMainThreadHeartbeat {
             max_layers: 1,
         };
         Ok(VRDisplayData {
+            display_id: self.ids.id_for(&identity),
             display_name,
+            identity,
             capabilities,
             left_eye_parameters,
             right_eye_parameters,
             connected: true,
             stage_parameters: None,
-            display_id: utils::new_id(),
         })
     }
 
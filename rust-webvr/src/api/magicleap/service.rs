@@ -5,6 +5,7 @@ use rust_webvr_api::VRDisplayEventReason;
 use rust_webvr_api::VRDisplayPtr;
 use rust_webvr_api::VREvent;
 use rust_webvr_api::VRGamepadPtr;
+use rust_webvr_api::VRIdAllocatorPtr;
 use rust_webvr_api::VRService;
 use std::cell::RefCell;
 use std::mem;
@@ -48,6 +49,10 @@ impl VRService for MagicLeapVRService {
     fn poll_events(&self) -> Vec<VREvent> {
         mem::replace(&mut *self.events.borrow_mut(), Vec::new())
     }
+
+    fn name(&self) -> &str {
+        "magicleap"
+    }
 }
 
 impl MagicLeapVRService {
@@ -56,10 +61,11 @@ impl MagicLeapVRService {
         display_name: String,
         egl: EGLContext,
         gl: Rc<dyn Gl>,
+        ids: VRIdAllocatorPtr,
     ) -> Result<(MagicLeapVRService, MagicLeapVRMainThreadHeartbeat), MLResult> {
         info!("Creating VRService");
         let (sender, receiver) = channel();
-        let heartbeat = MagicLeapVRMainThreadHeartbeat::new(display_name, receiver, egl, gl)?;
+        let heartbeat = MagicLeapVRMainThreadHeartbeat::new(display_name, receiver, egl, gl, ids)?;
         let service = MagicLeapVRService {
             sender: sender,
             display: None,
@@ -0,0 +1,168 @@
+use {VRClockPtr, VRLayer, VRResolveFrameData};
+use super::MockVRCompositorInit;
+use super::display::MockVRState;
+use super::profile::vsync_interval;
+use super::rng::MockRng;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+// Longest sleep while waiting for a vsync, in milliseconds
+const MAX_SLEEP: f64 = 1.0;
+
+pub enum MockVRCompositorMsg {
+    StartFrame(f64, f64, VRResolveFrameData),
+    // Layer, pixels and submission time
+    SubmitFrame(VRLayer, Option<Vec<u8>>, f64),
+}
+
+// A frame whose data was resolved and that waits to be submitted
+struct MockVRFrameInFlight {
+    timestamp: f64,
+    deadline: f64,
+}
+
+// Simulates the compositor of a headset: frame data is handed out at each vsync,
+// and a frame must be submitted before the next vsync to be presented.
+pub struct MockVRCompositor {
+    receiver: Receiver<MockVRCompositorMsg>,
+    state: Arc<Mutex<MockVRState>>,
+    clock: VRClockPtr,
+    rng: MockRng,
+    interval: f64,
+    jitter: f64,
+    pending: VecDeque<(f64, f64, VRResolveFrameData)>,
+    in_flight: Option<MockVRFrameInFlight>,
+    missed_vsyncs: u32,
+}
+
+impl MockVRCompositor {
+    pub fn spawn(init: MockVRCompositorInit,
+                 default_interval: f64,
+                 receiver: Receiver<MockVRCompositorMsg>,
+                 state: Arc<Mutex<MockVRState>>,
+                 clock: VRClockPtr) {
+        let compositor = MockVRCompositor {
+            receiver,
+            state,
+            clock,
+            rng: MockRng::new(init.seed),
+            interval: init.refresh_rate.map_or(default_interval, |refresh_rate| {
+                vsync_interval(refresh_rate).unwrap_or_else(|| {
+                    warn!("Invalid mock compositor refresh rate {}", refresh_rate);
+                    default_interval
+                })
+            }),
+            jitter: init.jitter,
+            pending: VecDeque::new(),
+            in_flight: None,
+            missed_vsyncs: 0,
+        };
+        thread::spawn(move || compositor.run());
+    }
+
+    fn run(mut self) {
+        let mut vsync = self.clock.now();
+        loop {
+            // The jitter never makes the vsyncs go backwards
+            let jitter = (self.rng.next_f64() * 2.0 - 1.0) * self.jitter;
+            vsync += (self.interval + jitter).max(0.0);
+            // Stops with the display
+            if !self.wait_for(vsync) {
+                break;
+            }
+            self.tick(vsync);
+        }
+    }
+
+    // Handles the messages until the vsync, returns false once the display went away.
+    // VRClock::wait_until is not used because a manual clock would jump to the deadline,
+    // here it is up to the test to move the clock.
+    fn wait_for(&mut self, vsync: f64) -> bool {
+        loop {
+            let remaining = vsync - self.clock.now();
+            if remaining <= 0.0 {
+                return self.handle_msgs();
+            }
+            let timeout = Duration::from_micros((remaining.min(MAX_SLEEP) * 1000.0) as u64);
+            match self.receiver.recv_timeout(timeout) {
+                Ok(msg) => self.handle_msg(msg),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return false,
+            }
+        }
+    }
+
+    fn handle_msgs(&mut self) -> bool {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(msg) => self.handle_msg(msg),
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+
+    fn handle_msg(&mut self, msg: MockVRCompositorMsg) {
+        match msg {
+            MockVRCompositorMsg::StartFrame(near, far, resolver) => {
+                self.pending.push_back((near, far, resolver));
+            }
+            MockVRCompositorMsg::SubmitFrame(layer, pixels, time) => {
+                self.submit_frame(layer, pixels, time);
+            }
+        }
+    }
+
+    fn submit_frame(&mut self, layer: VRLayer, pixels: Option<Vec<u8>>, time: f64) {
+        let state = self.state.lock().unwrap();
+        match self.in_flight.take() {
+            Some(frame) => {
+                let presented = time <= frame.deadline;
+                if !presented {
+                    warn!("Mock compositor dropped a frame submitted {}ms late", time - frame.deadline);
+                }
+                state.send_frame(layer, frame.timestamp, presented, self.missed_vsyncs, pixels);
+                if presented {
+                    self.missed_vsyncs = 0;
+                }
+            }
+            // Submitted without future_frame_data, nothing to check it against
+            None => state.send_frame(layer, time, true, 0, pixels),
+        }
+    }
+
+    fn tick(&mut self, vsync: f64) {
+        // The frame in flight couldn't make it for this vsync
+        let late = self.in_flight.as_ref().is_some_and(|frame| frame.deadline <= vsync);
+        if late {
+            self.missed_vsyncs += 1;
+        }
+
+        let (near, far, mut resolver) = match self.pending.pop_front() {
+            Some(request) => request,
+            None => return,
+        };
+        if self.in_flight.is_some() {
+            // A new frame was requested without submitting the previous one, which will never
+            // be presented. It counts as a missed vsync if it wasn't late already.
+            debug!("Mock compositor abandoned a frame");
+            if !late {
+                self.missed_vsyncs += 1;
+            }
+        }
+        let mut data = {
+            let mut state = self.state.lock().unwrap();
+            state.play_timeline(vsync);
+            state.frame_data(near, far)
+        };
+        data.timestamp = vsync;
+        let _ = resolver.resolve(data);
+        self.in_flight = Some(MockVRFrameInFlight {
+            timestamp: vsync,
+            deadline: vsync + self.interval,
+        });
+    }
+}
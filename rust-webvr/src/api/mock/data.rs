@@ -0,0 +1,48 @@
+use ron;
+use serde::de::DeserializeOwned;
+use serde_json;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use MockVRDeviceProfile;
+use MockVRTimeline;
+use super::profile::vsync_interval;
+
+// Loads a JSON or RON timeline file, depending on its extension
+pub fn load_timeline<P: AsRef<Path>>(path: P) -> Result<MockVRTimeline, String> {
+    let path = path.as_ref();
+    let timeline: MockVRTimeline = parse_file(&read_file(path)?, path)?;
+    if let Some(event) = timeline.events.iter().find(|event| !event.time.is_finite()) {
+        return Err(format!("Invalid time {} in mock timeline {}", event.time, path.display()));
+    }
+    Ok(timeline)
+}
+
+// Loads a JSON or RON device profile file, depending on its extension
+pub fn load_profile<P: AsRef<Path>>(path: P) -> Result<MockVRDeviceProfile, String> {
+    let path = path.as_ref();
+    parse_profile(&read_file(path)?, path)
+}
+
+fn parse_profile(contents: &str, path: &Path) -> Result<MockVRDeviceProfile, String> {
+    let profile: MockVRDeviceProfile = parse_file(contents, path)?;
+    if vsync_interval(profile.refresh_rate).is_none() {
+        return Err(format!("Invalid refresh rate {} in mock profile {}", profile.refresh_rate, path.display()));
+    }
+    Ok(profile)
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    let mut contents = String::new();
+    File::open(path).and_then(|mut file| file.read_to_string(&mut contents))
+                    .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+    Ok(contents)
+}
+
+fn parse_file<T: DeserializeOwned>(contents: &str, path: &Path) -> Result<T, String> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+                Some("json") => serde_json::from_str(contents).map_err(|e| e.to_string()),
+        Some("ron") => ron::de::from_str(contents).map_err(|e| e.to_string()),
+        _ => Err(format!("Unknown mock data format: {}", path.display())),
+    }
+}
@@ -1,35 +1,154 @@
-use {VRDisplay, VRDisplayData, VRDisplayEvent, VREvent, VRFramebuffer, VRFramebufferAttributes, VRFrameData, VRGamepadPtr, VRStageParameters, VRLayer, VRViewport};
-use rust_webvr_api::utils;
-use std::sync::{Arc, Mutex};
+use {VRClockPtr, VRDeviceIdentity, VRDisplay, VRDisplayData, VRDisplayEvent, VREvent, VRFramebuffer, VRFramebufferAttributes, VRFrameData, VRFutureFrameData, VRGamepadPtr, VRIdAllocatorPtr, VRResolveFrameData, VRStageParameters, VRLayer, VRViewport};
+use {MockVRFaultKind, VRFieldOfView, VRGamepad, VRGamepadButton, VRGamepadData, VRGamepadEvent, VRGamepadState, VRPose};
+use sparkle::gl;
+use sparkle::gl::Gl;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::mpsc::{channel, Sender};
 use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
 use std::mem;
 pub type MockVRDisplayPtr = Arc<RefCell<MockVRDisplay>>;
-use std::time::Duration;
-use std::thread;
-use super::{MockVRControlMsg, MockVRInit};
+
+// Messages of the replay service, reproducing recorded data as is on a mock display
+#[cfg(feature = "replay")]
+#[derive(Debug)]
+pub enum MockVRReplayMsg {
+    // Replaces the display data, the id, identity and connection state of the mock display are kept
+    SetDisplayData(VRDisplayData),
+    // Frame data returned as is instead of the simulated one, except for its timestamp.
+    // None goes back to the simulated frame data.
+    SetFrameData(Option<VRFrameData>),
+    // Replaces the state of a gamepad, including its timestamp. The id of the mock gamepad is kept.
+    SetGamepadState(u32, VRGamepadState),
+    EmitEvent(VREvent),
+}
+use super::{MockVRControlMsg, MockVRFrame, MockVRGamepadInit, MockVRInit, MockVRTimelineEvent, MockVRTracking};
+use super::{MockVRHitTestResult, MockVRInputSource, MockVRTargetRayMode, MockVRVisibility, MockVRWorld};
+use super::compositor::{MockVRCompositor, MockVRCompositorMsg};
+use super::fault::MockVRFaultsPtr;
+use super::gamepad::{MockVRGamepad, MockVRGamepadPtr};
+use super::profile::{mock_profile, vsync_interval};
+use super::world;
+use rust_webvr_api::utils;
+
+// Interval between simulated vsyncs, in milliseconds, when no profile is selected
+const VSYNC_INTERVAL: f64 = 1.0;
+// Number of GL error flags drained before reading a texture back
+const MAX_GL_ERRORS: usize = 8;
+// Height of the eyes above the floor, in meters, when MockVRInit doesn't set it
+const DEFAULT_EYE_LEVEL: f32 = 1.6;
 
 pub struct MockVRDisplay {
     display_id: u32,
     attributes: VRFramebufferAttributes,
     state: Arc<Mutex<MockVRState>>,
+    clock: VRClockPtr,
+    last_vsync: f64,
+    vsync_interval: f64,
+    gamepads: Vec<MockVRGamepadPtr>,
+    // Layer passed to render_layer, captured by submit_frame
+    pending_layer: Option<VRLayer>,
+    compositor: Option<Sender<MockVRCompositorMsg>>,
+    faults: MockVRFaultsPtr,
+    presenting: bool,
+    // Frames that won't be resolved, kept so that blocking on them hangs
+    // until the presentation stops
+    stalled_frames: Vec<VRResolveFrameData>,
 }
 
 pub struct MockVRState {
     display_data: VRDisplayData,
+    // Projection and view matrices are computed for each request, from the eye parameters
     frame_data: VRFrameData,
+    projection_override: Option<([f32; 16], [f32; 16])>,
+    // Replaces the simulated frame data when set, e.g. by a replay
+    frame_data_override: Option<VRFrameData>,
+    // Poses are relative to the eyes, the stage transform moves them up to the floor
+    eye_level: f32,
+    tracking: MockVRTracking,
+    // Whether the profile tracks the position, when the tracking isn't degraded
+    has_position: bool,
+    // Overrides the eye level when set
+    floor_origin: Option<([f32; 3], [f32; 4])>,
+    bounds: Vec<[f32; 2]>,
+    visibility: MockVRVisibility,
+    world: MockVRWorld,
     events: Vec<VREvent>,
+    // Receives the submitted frames, when capturing
+    frame_sender: Option<Sender<MockVRFrame>>,
+    capture_pixels: bool,
+    // Connected gamepads, by the index used in the control messages
+    gamepads: BTreeMap<u32, MockVRGamepadEntry>,
+    // Kept to be reconnected
+    disconnected_gamepads: BTreeMap<u32, MockVRGamepadEntry>,
+    ids: VRIdAllocatorPtr,
+    // Scheduled messages sorted by time, and the clock time they are relative to
+    timeline: VecDeque<MockVRTimelineEvent>,
+    timeline_start: f64,
 }
 
 unsafe impl Send for MockVRDisplay {}
 unsafe impl Sync for MockVRDisplay {}
 
 impl MockVRDisplay {
-    pub fn new(init: MockVRInit) -> MockVRDisplayPtr {
-        let display_id = utils::new_id();
+    pub fn new(init: MockVRInit, clock: VRClockPtr, ids: VRIdAllocatorPtr, faults: MockVRFaultsPtr) -> MockVRDisplayPtr {
+        let (display_id, identity) = MockVRDisplay::allocate_id(&init, &ids);
+        MockVRDisplay::new_with_id(display_id, identity, init, clock, ids, faults)
+    }
+
+    // Allows to know the display id before the display is created in the service thread
+    pub fn allocate_id(init: &MockVRInit, ids: &VRIdAllocatorPtr) -> (u32, VRDeviceIdentity) {
+        match init.serial {
+            Some(ref serial) => {
+                let identity = VRDeviceIdentity::new("mock", serial.as_str());
+                (ids.id_for(&identity), identity)
+            }
+            None => {
+                let display_id = ids.new_id();
+                (display_id, VRDeviceIdentity::new("mock", format!("mock-{}", display_id)))
+            }
+        }
+    }
+
+    pub fn new_with_id(display_id: u32,
+                       identity: VRDeviceIdentity,
+                       init: MockVRInit,
+                       clock: VRClockPtr,
+                       ids: VRIdAllocatorPtr,
+                       faults: MockVRFaultsPtr)
+                       -> MockVRDisplayPtr {
+        let last_vsync = clock.now();
+        let vsync_interval = match init.profile {
+            Some(ref profile) => vsync_interval(profile.refresh_rate).unwrap_or_else(|| {
+                warn!("Invalid mock profile refresh rate {}", profile.refresh_rate);
+                VSYNC_INTERVAL
+            }),
+            None => VSYNC_INTERVAL,
+        };
+        let compositor_init = init.compositor.clone();
+        let mut state = MockVRState::new(display_id, identity, init, ids);
+        state.timeline_start = last_vsync;
+        let state = Arc::new(Mutex::new(state));
+
+        let compositor = compositor_init.map(|compositor_init| {
+            let (sender, receiver) = channel();
+            MockVRCompositor::spawn(compositor_init, vsync_interval, receiver, state.clone(), clock.clone());
+            sender
+        });
+
         Arc::new(RefCell::new(MockVRDisplay {
             display_id,
             attributes: Default::default(),
-            state: Arc::new(Mutex::new(MockVRState::new(display_id, init))),
+            state,
+            clock,
+            last_vsync,
+            vsync_interval,
+            gamepads: Vec::new(),
+            pending_layer: None,
+            compositor,
+            faults,
+            presenting: false,
+            stalled_frames: Vec::new(),
         }))
     }
 
@@ -38,9 +157,53 @@ impl MockVRDisplay {
     }
 
     pub fn poll_events(&self) -> Vec<VREvent> {
-        let mut state = self.state.lock().unwrap();
+        let mut state = self.lock_state(self.clock.now());
         mem::replace(&mut state.events, vec![])
     }
+
+    fn capture_frame(&self, layer: VRLayer, pixels: Option<Vec<u8>>) {
+        match self.compositor {
+            // The compositor checks whether the frame made it in time
+            Some(ref compositor) => {
+                let _ = compositor.send(MockVRCompositorMsg::SubmitFrame(layer, pixels, self.clock.now()));
+            }
+            None => self.state.lock().unwrap().send_frame(layer, self.last_vsync, true, 0, pixels),
+        }
+    }
+
+    // Counts a new frame, injects the faults scheduled for it and rolls the
+    // queried ones for the next frame.
+    // Returns true when the frame must never be resolved.
+    fn start_frame(&mut self) -> bool {
+        let mut faults = self.faults.lock().unwrap();
+        let mut state = self.state.lock().unwrap();
+        state.next_frame();
+        if self.presenting {
+            if faults.check(MockVRFaultKind::Exit) {
+                state.events.push(VREvent::Display(VRDisplayEvent::Exit(self.display_id)));
+            }
+            if faults.check(MockVRFaultKind::DisconnectWhilePresenting) {
+                state.handle_msg(MockVRControlMsg::Disconnect);
+            }
+        }
+        let stall = faults.check(MockVRFaultKind::StallFrame);
+        faults.next_frame();
+        stall
+    }
+
+    fn wait_for_vsync(&mut self) {
+        // Simulate Vsync
+        let next_vsync = self.last_vsync + self.vsync_interval;
+        self.clock.wait_until(next_vsync);
+        self.last_vsync = self.clock.now();
+    }
+
+    // Locks the state once the timeline has been played up to the given time
+    fn lock_state(&self, now: f64) -> MutexGuard<'_, MockVRState> {
+        let mut state = self.state.lock().unwrap();
+        state.play_timeline(now);
+        state
+    }
 }
 
 impl VRDisplay for MockVRDisplay {
@@ -50,15 +213,40 @@ impl VRDisplay for MockVRDisplay {
     }
 
     fn data(&self) -> VRDisplayData {
-        self.state.lock().unwrap().display_data.clone()
+        self.lock_state(self.clock.now()).display_data.clone()
     }
 
-    fn immediate_frame_data(&self, _near_z: f64, _far_z: f64) -> VRFrameData {
-        self.state.lock().unwrap().frame_data.clone()
+    fn immediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let now = self.clock.now();
+        let mut data = self.lock_state(now).frame_data(near_z, far_z);
+        data.timestamp = now;
+        data
     }
 
     fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
-        self.immediate_frame_data(near_z, far_z)
+        let mut data = self.lock_state(self.last_vsync).frame_data(near_z, far_z);
+        data.timestamp = self.last_vsync;
+        data
+    }
+
+    #[allow(deprecated)]
+    fn future_frame_data(&mut self, near_z: f64, far_z: f64) -> VRFutureFrameData {
+        if self.start_frame() {
+            let (resolver, result) = VRFutureFrameData::blocked();
+            self.stalled_frames.push(resolver);
+            return result;
+        }
+        match self.compositor {
+            Some(ref compositor) => {
+                let (resolver, result) = VRFutureFrameData::blocked();
+                let _ = compositor.send(MockVRCompositorMsg::StartFrame(near_z, far_z, resolver));
+                result
+            }
+            None => {
+                self.wait_for_vsync();
+                VRFutureFrameData::resolved(self.synced_frame_data(near_z, far_z))
+            }
+        }
     }
 
     fn reset_pose(&mut self) {
@@ -66,8 +254,9 @@ impl VRDisplay for MockVRDisplay {
     }
 
     fn sync_poses(&mut self) {
-        // Simulate Vsync
-        thread::sleep(Duration::from_millis(1));
+        // Frame data can't stall here, it is read synchronously
+        self.start_frame();
+        self.wait_for_vsync();
     }
 
     fn bind_framebuffer(&mut self, _index: u32) {
@@ -75,38 +264,74 @@ impl VRDisplay for MockVRDisplay {
     }
 
     fn get_framebuffers(&self) -> Vec<VRFramebuffer> {
+        let eye = self.state.lock().unwrap().display_data.left_eye_parameters.clone();
+        let (width, height) = (eye.render_width as i32, eye.render_height as i32);
         vec![VRFramebuffer {
                 eye_index: 0,
                 attributes: self.attributes,
-                viewport: VRViewport::new(0, 0, 1512/2, 1680)
+                viewport: VRViewport::new(0, 0, width/2, height)
             },
             VRFramebuffer {
                 eye_index: 1,
                 attributes: self.attributes,
-                viewport: VRViewport::new(1512/2, 0, 1512/2, 1680)
+                viewport: VRViewport::new(width/2, 0, width/2, height)
             }]
     }
 
-    fn render_layer(&mut self, _layer: &VRLayer) {
-        // No op
+    fn render_layer(&mut self, layer: &VRLayer) {
+        self.pending_layer = Some(layer.clone());
     }
 
     fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>,String> {
-        Ok(Vec::new())
+        let connected = self.lock_state(self.clock.now()).gamepad_ids();
+        // Keep the same pointers for the gamepads that are still connected
+        self.gamepads.retain(|gamepad| connected.contains(&gamepad.borrow().id()));
+        for gamepad_id in connected {
+            if !self.gamepads.iter().any(|gamepad| gamepad.borrow().id() == gamepad_id) {
+                self.gamepads.push(MockVRGamepad::new(gamepad_id, self.state.clone(), self.clock.clone()));
+            }
+        }
+        Ok(self.gamepads.iter().map(|gamepad| gamepad.clone() as VRGamepadPtr).collect())
     }
 
     fn submit_frame(&mut self) {
-        // No op
+        if let Some(layer) = self.pending_layer.take() {
+            self.capture_frame(layer, None);
+        }
+    }
+
+    fn submit_layer(&mut self, gl: &Gl, layer: &VRLayer) {
+        let capture_pixels = self.state.lock().unwrap().capture_pixels;
+        let pixels = match layer.texture_size {
+            Some((width, height)) if capture_pixels => read_texture(gl, layer.texture_id, width, height),
+            _ => None,
+        };
+        self.capture_frame(layer.clone(), pixels);
     }
 
     fn start_present(&mut self, attributes: Option<VRFramebufferAttributes>) {
         if let Some(attributes) = attributes {
             self.attributes = attributes;
         }
+        self.presenting = true;
+    }
+
+    fn stop_present(&mut self) {
+        self.presenting = false;
+        // Blocking on a stalled frame fails instead of hanging from now on
+        self.stalled_frames.clear();
     }
 }
 
 impl MockVRState {
+    // Applies the scheduled messages whose time has come
+    pub fn play_timeline(&mut self, now: f64) {
+        while self.timeline.front().is_some_and(|event| self.timeline_start + event.time <= now) {
+            let event = self.timeline.pop_front().unwrap();
+            self.handle_msg(event.msg);
+        }
+    }
+
     pub fn handle_msg(&mut self, msg: MockVRControlMsg) {
         match msg {
             MockVRControlMsg::SetViewerPose(position, orientation) => {
@@ -116,64 +341,447 @@ impl MockVRState {
             MockVRControlMsg::SetViews(left, right) => {
                 self.display_data.left_eye_parameters.offset = left.offset;
                 self.display_data.right_eye_parameters.offset = right.offset;
-                self.frame_data.left_projection_matrix = left.projection;
-                self.frame_data.right_projection_matrix = right.projection;
+                self.frame_data.left_view_matrix = offset_view(&left.offset);
+                self.frame_data.right_view_matrix = offset_view(&right.offset);
+                self.display_data.left_eye_parameters.field_of_view = projection_fov(&left.projection);
+                self.display_data.right_eye_parameters.field_of_view = projection_fov(&right.projection);
                 self.events.push(VREvent::Display(VRDisplayEvent::Change(self.display_data.clone())))
             }
-            MockVRControlMsg::SetEyeLevel(_eye) => {
-                // do nothing for now
+            MockVRControlMsg::SetProjectionOverride(projections) => {
+                self.projection_override = projections;
+            }
+            MockVRControlMsg::SetEyeLevel(eye_level) => {
+                self.eye_level = eye_level;
+                // Devices without a stage only have a sitting space
+                // The floor origin, when set, takes precedence
+                if self.display_data.stage_parameters.is_some() && self.floor_origin.is_none() {
+                    self.update_stage(None);
+                }
+            }
+            MockVRControlMsg::SetStageSize(size_x, size_z) => {
+                self.update_stage(Some((size_x, size_z)));
+            }
+            MockVRControlMsg::SetTracking(tracking) => {
+                if tracking != self.tracking {
+                    self.tracking = tracking;
+                    // An emulated position isn't tracked either
+                    self.display_data.capabilities.has_position = self.has_position && tracking == MockVRTracking::Full;
+                    self.events.push(VREvent::Display(VRDisplayEvent::Change(self.display_data.clone())))
+                }
+            }
+            MockVRControlMsg::SetFloorOrigin(origin) => {
+                self.floor_origin = origin;
+                self.update_stage(None);
+            }
+            MockVRControlMsg::SetBoundsGeometry(points) => {
+                let size = match points.first() {
+                    Some(first) => {
+                        let (min, max) = points.iter().fold((*first, *first), |(min, max), point| {
+                            ([min[0].min(point[0]), min[1].min(point[1])], [max[0].max(point[0]), max[1].max(point[1])])
+                        });
+                        (max[0] - min[0], max[1] - min[1])
+                    }
+                    None => (0.0, 0.0),
+                };
+                self.bounds = points;
+                self.update_stage(Some(size));
+            }
+            MockVRControlMsg::SetVisibility(visibility) => {
+                self.set_visibility(visibility);
+            }
+            MockVRControlMsg::SetWorld(world) => {
+                self.world = *world;
+            }
+            MockVRControlMsg::ClearWorld => {
+                self.world = MockVRWorld::default();
             }
             MockVRControlMsg::Focus => {
+                self.visibility = MockVRVisibility::Visible;
                 self.events.push(VREvent::Display(VRDisplayEvent::Focus(self.display_data.clone())))
             }
             MockVRControlMsg::Blur => {
+                self.visibility = MockVRVisibility::VisibleBlurred;
                 self.events.push(VREvent::Display(VRDisplayEvent::Blur(self.display_data.clone())))
             }
+            MockVRControlMsg::Connect => {
+                if !self.display_data.connected {
+                    self.display_data.connected = true;
+                    self.events.push(VREvent::Display(VRDisplayEvent::Connect(self.display_data.clone())))
+                }
+            }
+            MockVRControlMsg::Disconnect => {
+                if self.display_data.connected {
+                    self.display_data.connected = false;
+                    self.events.push(VREvent::Display(VRDisplayEvent::Disconnect(self.display_data.display_id)))
+                }
+            }
+            MockVRControlMsg::ConnectGamepad(index, init) => {
+                if !self.gamepads.contains_key(&index) {
+                    self.disconnected_gamepads.remove(&index);
+                    let entry = self.new_gamepad(index, init);
+                    self.connect_gamepad(index, entry);
+                }
+            }
+            MockVRControlMsg::DisconnectGamepad(index) => {
+                if let Some(entry) = self.gamepads.remove(&index) {
+                    self.events.push(VREvent::Gamepad(VRGamepadEvent::Disconnect(entry.state.gamepad_id)));
+                    self.disconnected_gamepads.insert(index, entry);
+                }
+            }
+            MockVRControlMsg::ReconnectGamepad(index) => {
+                if let Some(entry) = self.disconnected_gamepads.remove(&index) {
+                    self.connect_gamepad(index, entry);
+                }
+            }
+            MockVRControlMsg::SetGamepadPose(index, position, orientation) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    entry.state.pose.position = Some(position);
+                    entry.state.pose.orientation = Some(orientation);
+                }
+            }
+            MockVRControlMsg::ClearGamepadPose(index) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    entry.state.pose.position = None;
+                    entry.state.pose.orientation = None;
+                }
+            }
+            MockVRControlMsg::SetGamepadPointerOrigin(index, origin) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    entry.pointer_origin = origin;
+                }
+            }
+            MockVRControlMsg::SetGamepadHand(index, hand) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    entry.data.hand = hand;
+                }
+            }
+            MockVRControlMsg::SetGamepadTargetRayMode(index, target_ray_mode) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    entry.target_ray_mode = target_ray_mode;
+                }
+            }
+            MockVRControlMsg::SetGamepadProfiles(index, profiles) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    entry.profiles = profiles;
+                }
+            }
+            MockVRControlMsg::SetGamepadButtons(index, mut buttons) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    // The number of buttons is fixed when the gamepad is connected
+                    buttons.resize(entry.state.buttons.len(), VRGamepadButton::new(false));
+                    entry.state.buttons = buttons;
+                }
+            }
+            MockVRControlMsg::SetGamepadAxes(index, mut axes) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    axes.resize(entry.state.axes.len(), 0.0);
+                    entry.state.axes = axes;
+                }
+            }
+            MockVRControlMsg::StartSelection(index) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    entry.set_selecting(true);
+                }
+            }
+            MockVRControlMsg::EndSelection(index) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    entry.set_selecting(false);
+                }
+            }
+            MockVRControlMsg::SimulateSelect(index) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    entry.set_selecting(true);
+                    // Released when the frame after the next one starts
+                    entry.click_frames = 2;
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "replay")]
+    pub fn handle_replay_msg(&mut self, msg: MockVRReplayMsg) {
+        match msg {
+            MockVRReplayMsg::SetDisplayData(mut data) => {
+                data.display_id = self.display_data.display_id;
+                data.identity = self.display_data.identity.clone();
+                data.connected = self.display_data.connected;
+                self.has_position = data.capabilities.has_position;
+                self.display_data = data;
+                // The tracking set by SetTracking still applies to the new data
+                self.display_data.capabilities.has_position = self.has_position && self.tracking == MockVRTracking::Full;
+            }
+            MockVRReplayMsg::SetFrameData(data) => {
+                self.frame_data_override = data;
+            }
+            MockVRReplayMsg::SetGamepadState(index, mut gamepad_state) => {
+                if let Some(entry) = self.gamepads.get_mut(&index) {
+                    gamepad_state.gamepad_id = entry.state.gamepad_id;
+                    entry.replayed_timestamp = Some(gamepad_state.timestamp);
+                    entry.state = gamepad_state;
+                }
+            }
+            MockVRReplayMsg::EmitEvent(event) => {
+                self.events.push(event);
+            }
+        }
+    }
+
+    // Sitting to standing transform, from the floor origin or the eye level
+    fn standing_transform(&self) -> [f32; 16] {
+        match self.floor_origin {
+            Some((position, orientation)) => {
+                let mut transform = [0.0; 16];
+                utils::inverse_matrix(&pose_matrix(&position, &orientation), &mut transform);
+                transform
+            }
+            None => eye_level_transform(self.eye_level),
+        }
+    }
+
+    // Updates the stage transform, and its size when given
+    fn update_stage(&mut self, size: Option<(f32, f32)>) {
+        let transform = self.standing_transform();
+        let stage = self.display_data.stage_parameters.get_or_insert(VRStageParameters {
+            sitting_to_standing_transform: transform,
+            size_x: 0.0,
+            size_z: 0.0
+        });
+        stage.sitting_to_standing_transform = transform;
+        if let Some((size_x, size_z)) = size {
+            stage.size_x = size_x;
+            stage.size_z = size_z;
+        }
+        self.events.push(VREvent::Display(VRDisplayEvent::Change(self.display_data.clone())))
+    }
+
+    // Hidden content pauses the presentation loop
+    fn set_visibility(&mut self, visibility: MockVRVisibility) {
+        if visibility == self.visibility {
+            return;
+        }
+        let previous = mem::replace(&mut self.visibility, visibility);
+        let display_id = self.display_data.display_id;
+        if previous == MockVRVisibility::Hidden {
+            self.events.push(VREvent::Display(VRDisplayEvent::Resume(display_id)));
+        }
+        if visibility == MockVRVisibility::Visible {
+            self.events.push(VREvent::Display(VRDisplayEvent::Focus(self.display_data.clone())));
+        } else if previous == MockVRVisibility::Visible {
+            self.events.push(VREvent::Display(VRDisplayEvent::Blur(self.display_data.clone())));
+        }
+        if visibility == MockVRVisibility::Hidden {
+            self.events.push(VREvent::Display(VRDisplayEvent::Pause(display_id)));
+        }
+    }
+
+    fn connect_gamepad(&mut self, index: u32, entry: MockVRGamepadEntry) {
+        self.events.push(VREvent::Gamepad(VRGamepadEvent::Connect(entry.data.clone(), entry.state.clone())));
+        self.gamepads.insert(index, entry);
+    }
+
+    pub fn visibility(&self) -> MockVRVisibility {
+        self.visibility
+    }
+
+    pub fn bounds_geometry(&self) -> Vec<[f32; 2]> {
+        self.bounds.clone()
+    }
+
+    pub fn input_sources(&self) -> Vec<MockVRInputSource> {
+        self.gamepads.values().map(|entry| {
+            let pose = &entry.state.pose;
+            MockVRInputSource {
+                gamepad_id: entry.state.gamepad_id,
+                hand: entry.data.hand.clone(),
+                target_ray_mode: entry.target_ray_mode,
+                profiles: entry.profiles.clone(),
+                pointer_origin: entry.pointer_origin,
+                grip_origin: match (pose.position, pose.orientation) {
+                    (Some(position), Some(orientation)) => Some((position, orientation)),
+                    _ => None,
+                },
+                selecting: entry.selecting,
+            }
+        }).collect()
+    }
+
+    pub fn hit_test(&self, origin: [f32; 3], direction: [f32; 3]) -> Vec<MockVRHitTestResult> {
+        world::hit_test(&self.world, origin, direction)
+    }
+
+    // Sends the frames submitted from now on, with their pixels if requested
+    pub fn capture_frames(&mut self, sender: Sender<MockVRFrame>, capture_pixels: bool) {
+        self.frame_sender = Some(sender);
+        self.capture_pixels = capture_pixels;
+    }
+
+    pub fn send_frame(&self, layer: VRLayer, timestamp: f64, presented: bool, missed_vsyncs: u32, pixels: Option<Vec<u8>>) {
+        if let Some(ref sender) = self.frame_sender {
+            let _ = sender.send(MockVRFrame {
+                display_id: self.display_data.display_id,
+                layer,
+                timestamp,
+                presented,
+                missed_vsyncs,
+                pixels,
+            });
+        }
+    }
+
+    pub fn frame_data(&self, near: f64, far: f64) -> VRFrameData {
+        if let Some(ref data) = self.frame_data_override {
+            return data.clone();
+        }
+        let mut data = self.frame_data.clone();
+        let left = &self.display_data.left_eye_parameters;
+        let right = &self.display_data.right_eye_parameters;
+        match self.projection_override {
+            Some((left_projection, right_projection)) => {
+                data.left_projection_matrix = left_projection;
+                data.right_projection_matrix = right_projection;
+            }
+            None => {
+                data.left_projection_matrix = fov_projection(&left.field_of_view, near, far);
+                data.right_projection_matrix = fov_projection(&right.field_of_view, near, far);
+            }
+        }
+        match self.tracking {
+            MockVRTracking::Full => {}
+            MockVRTracking::OrientationOnly => {
+                data.pose.position = None;
+                data.pose.linear_velocity = None;
+                data.pose.linear_acceleration = None;
+            }
+            MockVRTracking::Emulated => data.pose.emulated_position = true,
+            MockVRTracking::Lost => data.pose = VRPose::default(),
+        }
+        data
+    }
+
+    pub fn gamepad(&self, gamepad_id: u32) -> Option<&MockVRGamepadEntry> {
+        self.gamepads.values().find(|entry| entry.state.gamepad_id == gamepad_id)
+    }
+
+    // Ends the simulated clicks once they lasted a whole frame
+    fn next_frame(&mut self) {
+        for entry in self.gamepads.values_mut() {
+            if entry.click_frames > 0 {
+                entry.click_frames -= 1;
+                if entry.click_frames == 0 {
+                    entry.set_selecting(false);
+                }
+            }
+        }
+    }
+
+    fn gamepad_ids(&self) -> Vec<u32> {
+        self.gamepads.values().map(|entry| entry.state.gamepad_id).collect()
+    }
+
+    fn new_gamepad(&self, index: u32, init: MockVRGamepadInit) -> MockVRGamepadEntry {
+        // Reconnecting a gamepad at the same index keeps its id
+        let serial = init.serial.unwrap_or_else(|| format!("{}-gamepad-{}", self.display_data.identity.serial, index));
+        let identity = VRDeviceIdentity::new("mock", serial);
+        let gamepad_id = self.ids.id_for(&identity);
+
+        let data = VRGamepadData {
+            display_id: self.display_data.display_id,
+            name: init.name,
+            identity,
+            hand: init.hand,
+        };
+
+        let state = VRGamepadState {
+            gamepad_id,
+            connected: true,
+            buttons: vec![VRGamepadButton::new(false); init.buttons],
+            axes: vec![0.0; init.axes],
+            .. Default::default()
+        };
+
+        MockVRGamepadEntry {
+            data,
+            state,
+            target_ray_mode: init.target_ray_mode,
+            profiles: init.profiles,
+            pointer_origin: init.pointer_origin,
+            selecting: false,
+            click_frames: 0,
+            replayed_timestamp: None,
+        }
+    }
+}
+
+pub struct MockVRGamepadEntry {
+    pub data: VRGamepadData,
+    pub state: VRGamepadState,
+    target_ray_mode: MockVRTargetRayMode,
+    profiles: Vec<String>,
+    pointer_origin: Option<([f32; 3], [f32; 4])>,
+    selecting: bool,
+    // Frame starts left before the end of a selection started by SimulateSelect
+    click_frames: u32,
+    // Timestamp of the state set by the replay service, returned instead of the clock time
+    pub replayed_timestamp: Option<f64>,
+}
+
+impl MockVRGamepadEntry {
+    // The first button is the primary action
+    fn set_selecting(&mut self, selecting: bool) {
+        self.selecting = selecting;
+        if let Some(button) = self.state.buttons.first_mut() {
+            *button = VRGamepadButton::new(selecting);
         }
     }
 }
 
 impl MockVRState {
-    pub fn new(display_id: u32, init: MockVRInit) -> Self {
-        let mut display_data = VRDisplayData::default();
-        
-        // Mock display data
-        // Simulates a virtual HTC Vive
-
-        display_data.display_name = "Mock VRDisplay".into();
-        display_data.display_id = display_id;
-        display_data.connected = true;
-
-        display_data.capabilities.can_present = true;
-        display_data.capabilities.has_orientation = true;
-        display_data.capabilities.has_external_display = true;
-        display_data.capabilities.has_position = true;
-
-        // todo use init.eye_level
-        display_data.stage_parameters = Some(VRStageParameters {
-            sitting_to_standing_transform: [-0.9317312, 0.0, 0.36314875, 0.0, 0.0, 0.99999994, 0.0, 0.0, -0.36314875, 
-                                            0.0, -0.9317312, 0.0, 0.23767996, 1.6813644, 0.45370483, 1.0],
-            size_x: 2.0,
-            size_z: 2.0
+    pub fn new(display_id: u32, identity: VRDeviceIdentity, init: MockVRInit, ids: VRIdAllocatorPtr) -> Self {
+        let default_profile = init.profile.is_none();
+        let profile = init.profile.unwrap_or_else(|| {
+            // Simulates a virtual HTC Vive, without controllers
+            let mut profile = mock_profile("vive").unwrap();
+            profile.display_name = "Mock VRDisplay".into();
+            profile.controllers.clear();
+            profile
+        });
+
+        let mut display_data = VRDisplayData {
+            display_name: profile.display_name,
+            display_id,
+            identity,
+            connected: true,
+            .. Default::default()
+        };
+        let has_position = profile.capabilities.has_position;
+        display_data.capabilities = profile.capabilities;
+
+        let eye_level = init.eye_level.unwrap_or(DEFAULT_EYE_LEVEL);
+        display_data.stage_parameters = profile.stage_size.map(|(size_x, size_z)| VRStageParameters {
+            sitting_to_standing_transform: eye_level_transform(eye_level),
+            size_x,
+            size_z
         });
 
-        display_data.left_eye_parameters.render_width = 1512;
-        display_data.left_eye_parameters.render_height = 1680;
-        display_data.left_eye_parameters.field_of_view.up_degrees = 55.82093048095703;
-        display_data.left_eye_parameters.field_of_view.right_degrees = 51.26948547363281;
-        display_data.left_eye_parameters.field_of_view.down_degrees = 55.707801818847656;
-        display_data.left_eye_parameters.field_of_view.left_degrees = 54.42263412475586;
-
-        display_data.right_eye_parameters.render_width = 1512;
-        display_data.right_eye_parameters.render_height = 1680;
-        display_data.right_eye_parameters.field_of_view.up_degrees = 55.898048400878906;
-        display_data.right_eye_parameters.field_of_view.right_degrees = 54.37410354614258;
-        display_data.right_eye_parameters.field_of_view.down_degrees = 55.614715576171875;
-        display_data.right_eye_parameters.field_of_view.left_degrees = 51.304901123046875;
-        
+        display_data.left_eye_parameters.render_width = profile.render_width;
+        display_data.left_eye_parameters.render_height = profile.render_height;
+        display_data.left_eye_parameters.field_of_view = profile.left_field_of_view;
+
+        display_data.right_eye_parameters.render_width = profile.render_width;
+        display_data.right_eye_parameters.render_height = profile.render_height;
+        display_data.right_eye_parameters.field_of_view = profile.right_field_of_view;
 
         let mut frame_data = VRFrameData::default();
 
+        let mut timeline: Vec<_> = init.timeline.map(|timeline| timeline.events).unwrap_or_default();
+        timeline.retain(|event| {
+            if !event.time.is_finite() {
+                warn!("Ignoring mock timeline event at time {}", event.time);
+            }
+            event.time.is_finite()
+        });
+        timeline.sort_by(|a, b| a.time.total_cmp(&b.time));
+
         if let Some((position, orientation)) = init.viewer_origin {
             frame_data.pose.position = Some(position);
             frame_data.pose.orientation = Some(orientation);
@@ -188,40 +796,141 @@ impl MockVRState {
         if let Some((left, right)) = init.views {
             display_data.left_eye_parameters.offset = left.offset;
             display_data.right_eye_parameters.offset = right.offset;
-            frame_data.left_projection_matrix = left.projection;
-            frame_data.right_projection_matrix = right.projection;
-        } else {
+            display_data.left_eye_parameters.field_of_view = projection_fov(&left.projection);
+            display_data.right_eye_parameters.field_of_view = projection_fov(&right.projection);
+            frame_data.left_view_matrix = offset_view(&left.offset);
+            frame_data.right_view_matrix = offset_view(&right.offset);
+        } else if default_profile {
+            // The eyes of the original mock display, which existing users rely on
             display_data.left_eye_parameters.offset = [0.035949998, 0.0, 0.015];
             display_data.right_eye_parameters.offset = [-0.035949998, 0.0, 0.015];
-            // Simulates HTC Vive projections
-            frame_data.left_projection_matrix = [0.75620246, 0.0, 0.0, 0.0,
-                                           0.0, 0.68050665, 0.0, 0.0,
-                                          -0.05713458, -0.0021225351, -1.0000999, -1.0, 
-                                           0.0, 0.0, -0.10000999, 0.0];
-
-            frame_data.left_view_matrix = [1.0, 0.0, 0.0, 0.0, 
-                                     0.0, 1.0, 0.0, 0.0, 
-                                     0.0, 0.0, 1.0, 0.0, 
-                                    -0.035949998, 0.0, 0.015, 1.0];
-
-            frame_data.right_projection_matrix = [0.75646526, 0.0, 0.0, 0.0, 
-                                            0.0, 0.68069947, 0.0, 0.0, 
-                                            0.055611316, -0.005315368, -1.0000999, -1.0, 
-                                            0.0, 0.0, -0.10000999, 0.0];
+
+            frame_data.left_view_matrix = [1.0, 0.0, 0.0, 0.0,
+                                           0.0, 1.0, 0.0, 0.0,
+                                           0.0, 0.0, 1.0, 0.0,
+                                          -0.035949998, 0.0, 0.015, 1.0];
 
             frame_data.right_view_matrix = [1.0, 0.0, 0.0, 0.0,
-                                      0.0, 1.0, 0.0, 0.0,
-                                      0.0, 0.0, 1.0, 0.0,
-                                      0.035949998, 0.0, 0.015, 1.0];
+                                            0.0, 1.0, 0.0, 0.0,
+                                            0.0, 0.0, 1.0, 0.0,
+                                            0.035949998, 0.0, 0.015, 1.0];
+        } else {
+            let half_ipd = profile.ipd / 2.0;
+            display_data.left_eye_parameters.offset = [-half_ipd, 0.0, 0.0];
+            display_data.right_eye_parameters.offset = [half_ipd, 0.0, 0.0];
+            frame_data.left_view_matrix = offset_view(&display_data.left_eye_parameters.offset);
+            frame_data.right_view_matrix = offset_view(&display_data.right_eye_parameters.offset);
         };
 
-
-        frame_data.timestamp = utils::timestamp();
-
-        Self {
+        let mut state = Self {
             display_data,
             frame_data,
-            events: vec![]
+            projection_override: init.projection_override,
+            frame_data_override: None,
+            eye_level,
+            tracking: MockVRTracking::Full,
+            has_position,
+            floor_origin: None,
+            bounds: Vec::new(),
+            visibility: MockVRVisibility::Visible,
+            world: MockVRWorld::default(),
+            events: vec![],
+            frame_sender: None,
+            capture_pixels: false,
+            gamepads: BTreeMap::new(),
+            disconnected_gamepads: BTreeMap::new(),
+            ids,
+            timeline: timeline.into(),
+            timeline_start: 0.0,
+        };
+
+        // The controllers of the profile are already there when the display shows up
+        for (index, init) in profile.controllers.into_iter().enumerate() {
+            let entry = state.new_gamepad(index as u32, init);
+            state.gamepads.insert(index as u32, entry);
+        }
+
+        state
+    }
+}
+
+// Column major projection matrix for the given field of view
+fn fov_projection(fov: &VRFieldOfView, near: f64, far: f64) -> [f32; 16] {
+    let up = fov.up_degrees.to_radians().tan();
+    let right = fov.right_degrees.to_radians().tan();
+    let down = fov.down_degrees.to_radians().tan();
+    let left = fov.left_degrees.to_radians().tan();
+    let x_scale = 2.0 / (left + right);
+    let y_scale = 2.0 / (up + down);
+
+    [x_scale as f32, 0.0, 0.0, 0.0,
+     0.0, y_scale as f32, 0.0, 0.0,
+     ((right - left) * x_scale * 0.5) as f32, ((up - down) * y_scale * 0.5) as f32, ((near + far) / (near - far)) as f32, -1.0,
+     0.0, 0.0, (2.0 * far * near / (near - far)) as f32, 0.0]
+}
+
+// Inverse of fov_projection, the clip planes don't matter
+fn projection_fov(projection: &[f32; 16]) -> VRFieldOfView {
+    let (x_scale, y_scale) = (projection[0] as f64, projection[5] as f64);
+    let (x_offset, y_offset) = (projection[8] as f64, projection[9] as f64);
+    VRFieldOfView {
+        up_degrees: ((1.0 + y_offset) / y_scale).atan().to_degrees(),
+        right_degrees: ((1.0 + x_offset) / x_scale).atan().to_degrees(),
+        down_degrees: ((1.0 - y_offset) / y_scale).atan().to_degrees(),
+        left_degrees: ((1.0 - x_offset) / x_scale).atan().to_degrees(),
+    }
+}
+
+// Reads the texture back through a temporary framebuffer, restoring the current one.
+// Returns None if GL reported an error, the pixels can't be trusted then.
+fn read_texture(gl: &Gl, texture_id: u32, width: u32, height: u32) -> Option<Vec<u8>> {
+    // Errors left behind by the application are not ours to report. A lost context can keep
+    // returning the same error, so only the flags that can be set at once are drained.
+    for _ in 0..MAX_GL_ERRORS {
+        if gl.get_error() == gl::NO_ERROR {
+            break;
+        }
+    }
+
+    let mut current_fbo = [0];
+    unsafe { gl.get_integer_v(gl::READ_FRAMEBUFFER_BINDING, &mut current_fbo[..]) };
+    let fbo = gl.gen_framebuffers(1)[0];
+    gl.bind_framebuffer(gl::READ_FRAMEBUFFER, fbo);
+    gl.framebuffer_texture_2d(gl::READ_FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, texture_id, 0);
+    let pixels = gl.read_pixels(0, 0, width as gl::GLsizei, height as gl::GLsizei, gl::RGBA, gl::UNSIGNED_BYTE);
+    gl.bind_framebuffer(gl::READ_FRAMEBUFFER, current_fbo[0] as gl::GLuint);
+    gl.delete_framebuffers(&[fbo]);
+    match gl.get_error() {
+        gl::NO_ERROR => Some(pixels),
+        error => {
+            warn!("Failed to read back the pixels of mock layer texture {}: GL error {:#x}", texture_id, error);
+            None
         }
     }
 }
+
+// Column major transform from the sitting space, centered on the eyes, to the standing space,
+// centered on the floor
+fn eye_level_transform(eye_level: f32) -> [f32; 16] {
+    [1.0, 0.0, 0.0, 0.0,
+     0.0, 1.0, 0.0, 0.0,
+     0.0, 0.0, 1.0, 0.0,
+     0.0, eye_level, 0.0, 1.0]
+}
+
+// Column major transform of a pose, from a position and an orientation quaternion
+fn pose_matrix(position: &[f32; 3], orientation: &[f32; 4]) -> [f32; 16] {
+    let (x, y, z, w) = (orientation[0], orientation[1], orientation[2], orientation[3]);
+    [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + z * w), 2.0 * (x * z - y * w), 0.0,
+     2.0 * (x * y - z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + x * w), 0.0,
+     2.0 * (x * z + y * w), 2.0 * (y * z - x * w), 1.0 - 2.0 * (x * x + y * y), 0.0,
+     position[0], position[1], position[2], 1.0]
+}
+
+// Column major view matrix, moving the eye back to the origin
+fn offset_view(offset: &[f32; 3]) -> [f32; 16] {
+    [1.0, 0.0, 0.0, 0.0,
+     0.0, 1.0, 0.0, 0.0,
+     0.0, 0.0, 1.0, 0.0,
+     -offset[0], -offset[1], -offset[2], 1.0]
+}
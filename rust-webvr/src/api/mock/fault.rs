@@ -0,0 +1,83 @@
+use {MockVRFaultKind, MockVRFaultPlan, MockVRFaultTrigger};
+use super::rng::MockRng;
+use std::sync::{Arc, Mutex};
+
+pub type MockVRFaultsPtr = Arc<Mutex<MockVRFaults>>;
+
+// Faults that are queried rather than injected, rolled once per frame so that
+// querying them has no side effects
+const ROLLED_FAULTS: &[MockVRFaultKind] = &[MockVRFaultKind::Unavailable];
+
+// Fault plan of a mock service, shared with its displays
+pub struct MockVRFaults {
+    plan: MockVRFaultPlan,
+    rng: MockRng,
+    frame: u64,
+    // Whether each fault of the plan already fired, for the Frame triggers
+    fired: Vec<bool>,
+    // Rolled faults that happen during the current frame
+    rolled: Vec<MockVRFaultKind>,
+}
+
+impl MockVRFaults {
+    pub fn new(plan: MockVRFaultPlan) -> MockVRFaults {
+        let mut faults = MockVRFaults {
+            rng: MockRng::new(plan.seed),
+            frame: 0,
+            fired: vec![false; plan.faults.len()],
+            rolled: Vec::new(),
+            plan,
+        };
+        faults.roll();
+        faults
+    }
+
+    pub fn new_ptr(plan: Option<MockVRFaultPlan>) -> MockVRFaultsPtr {
+        Arc::new(Mutex::new(MockVRFaults::new(plan.unwrap_or_default())))
+    }
+
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+        self.roll();
+    }
+
+    fn roll(&mut self) {
+        self.rolled.clear();
+        for &kind in ROLLED_FAULTS {
+            if self.check(kind) {
+                self.rolled.push(kind);
+            }
+        }
+    }
+
+    // Whether a rolled fault of the given kind happens during the current frame.
+    // Unlike check, doesn't consume random draws or Frame triggers.
+    pub fn peek(&self, kind: MockVRFaultKind) -> bool {
+        self.rolled.contains(&kind)
+    }
+
+    // Whether a fault of the given kind happens now
+    pub fn check(&mut self, kind: MockVRFaultKind) -> bool {
+        let mut result = false;
+        for (index, fault) in self.plan.faults.iter().enumerate() {
+            if fault.kind != kind {
+                continue;
+            }
+            // Every fault is evaluated, so that the random draws don't depend on the other triggers
+            result |= match fault.trigger {
+                MockVRFaultTrigger::Always => true,
+                MockVRFaultTrigger::Random(probability) => self.rng.next_f64() < probability,
+                MockVRFaultTrigger::Frame(frame) => {
+                    let fire = !self.fired[index] && self.frame >= frame;
+                    self.fired[index] |= fire;
+                    fire
+                }
+                MockVRFaultTrigger::FromFrame(frame) => self.frame >= frame,
+            };
+        }
+        if result {
+            debug!("Mock fault {:?} at frame {}", kind, self.frame);
+        }
+        result
+    }
+}
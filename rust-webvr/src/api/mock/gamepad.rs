@@ -0,0 +1,49 @@
+use {VRClockPtr, VRGamepad, VRGamepadData, VRGamepadState};
+use super::display::MockVRState;
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+
+pub type MockVRGamepadPtr = Arc<RefCell<MockVRGamepad>>;
+
+// The gamepad data lives in the display state, so that the control messages can update it
+pub struct MockVRGamepad {
+    gamepad_id: u32,
+    state: Arc<Mutex<MockVRState>>,
+    clock: VRClockPtr,
+}
+
+unsafe impl Send for MockVRGamepad {}
+unsafe impl Sync for MockVRGamepad {}
+
+impl MockVRGamepad {
+    pub fn new(gamepad_id: u32, state: Arc<Mutex<MockVRState>>, clock: VRClockPtr) -> MockVRGamepadPtr {
+        Arc::new(RefCell::new(MockVRGamepad {
+            gamepad_id,
+            state,
+            clock,
+        }))
+    }
+}
+
+impl VRGamepad for MockVRGamepad {
+    fn id(&self) -> u32 {
+        self.gamepad_id
+    }
+
+    fn data(&self) -> VRGamepadData {
+        let state = self.state.lock().unwrap();
+        state.gamepad(self.gamepad_id).map(|entry| entry.data.clone()).unwrap_or_default()
+    }
+
+    fn state(&self) -> VRGamepadState {
+        let state = self.state.lock().unwrap();
+        let (mut gamepad_state, timestamp) = match state.gamepad(self.gamepad_id) {
+            Some(entry) => (entry.state.clone(), entry.replayed_timestamp),
+            // Disconnected
+            None => (VRGamepadState::default(), None),
+        };
+        gamepad_state.gamepad_id = self.gamepad_id;
+        gamepad_state.timestamp = timestamp.unwrap_or_else(|| self.clock.now());
+        gamepad_state
+    }
+}
@@ -0,0 +1,88 @@
+use {MockVRControlMsg, MockVRHitTestResult, MockVRInputSource, MockVRVisibility};
+use super::display::MockVRState;
+#[cfg(feature = "replay")]
+use super::display::MockVRReplayMsg;
+use std::sync::{Arc, Mutex};
+
+// Reads the parts of a mock display that the VRDisplay and VRGamepad APIs don't expose,
+// e.g. for a WebXR test API. Until the service created the display, the defaults are returned.
+#[derive(Clone, Default)]
+pub struct MockVRDisplayHandle {
+    state: Arc<Mutex<Option<Arc<Mutex<MockVRState>>>>>,
+    // Messages handled before the display was created
+    pending: Arc<Mutex<Vec<MockVRPendingMsg>>>,
+}
+
+enum MockVRPendingMsg {
+    Control(MockVRControlMsg),
+    #[cfg(feature = "replay")]
+    Replay(Box<MockVRReplayMsg>),
+}
+
+impl MockVRDisplayHandle {
+    pub fn new() -> MockVRDisplayHandle {
+        MockVRDisplayHandle {
+            state: Arc::new(Mutex::new(None)),
+            pending: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn set_state(&self, state: Arc<Mutex<MockVRState>>) {
+        let mut slot = self.state.lock().unwrap();
+        {
+            let mut state = state.lock().unwrap();
+            for msg in self.pending.lock().unwrap().drain(..) {
+                match msg {
+                    MockVRPendingMsg::Control(msg) => state.handle_msg(msg),
+                    #[cfg(feature = "replay")]
+                    MockVRPendingMsg::Replay(msg) => state.handle_replay_msg(*msg),
+                }
+            }
+        }
+        *slot = Some(state);
+    }
+
+    // Applies a control message right away, unlike the control channel whose messages are
+    // handled by another thread. Until the service created the display, the messages are
+    // kept and applied in order when it does.
+    pub fn handle_msg(&self, msg: MockVRControlMsg) {
+        match *self.state.lock().unwrap() {
+            Some(ref state) => state.lock().unwrap().handle_msg(msg),
+            None => self.pending.lock().unwrap().push(MockVRPendingMsg::Control(msg)),
+        }
+    }
+
+    // Same as handle_msg, for the messages of the replay service
+    #[cfg(feature = "replay")]
+    pub(crate) fn handle_replay_msg(&self, msg: MockVRReplayMsg) {
+        match *self.state.lock().unwrap() {
+            Some(ref state) => state.lock().unwrap().handle_replay_msg(msg),
+            None => self.pending.lock().unwrap().push(MockVRPendingMsg::Replay(Box::new(msg))),
+        }
+    }
+
+    fn with_state<T, F>(&self, default: T, f: F) -> T where F: FnOnce(&MockVRState) -> T {
+        match *self.state.lock().unwrap() {
+            Some(ref state) => f(&state.lock().unwrap()),
+            None => default,
+        }
+    }
+
+    pub fn visibility(&self) -> MockVRVisibility {
+        self.with_state(MockVRVisibility::Visible, |state| state.visibility())
+    }
+
+    // Points of the play area on the floor, as (x, z) pairs
+    pub fn bounds_geometry(&self) -> Vec<[f32; 2]> {
+        self.with_state(Vec::new(), |state| state.bounds_geometry())
+    }
+
+    pub fn input_sources(&self) -> Vec<MockVRInputSource> {
+        self.with_state(Vec::new(), |state| state.input_sources())
+    }
+
+    // Intersections of a ray with the world of the display, in its sitting space, closest first
+    pub fn hit_test(&self, origin: [f32; 3], direction: [f32; 3]) -> Vec<MockVRHitTestResult> {
+        self.with_state(Vec::new(), |state| state.hit_test(origin, direction))
+    }
+}
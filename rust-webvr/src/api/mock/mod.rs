@@ -1,8 +1,34 @@
+mod compositor;
+#[cfg(feature = "config")]
+mod data;
 mod display;
+mod fault;
+mod gamepad;
+mod handle;
+mod profile;
+mod rng;
+#[cfg(feature = "mock-server")]
+mod server;
 mod service;
+mod world;
 
-pub use {VRService, VRServiceCreator, VREyeParameters, VRStageParameters, MockVRControlMsg, MockVRInit, MockVRView};
+pub use {VRClockPtr, VRIdAllocatorPtr, VRService, VRServiceCreator, MockVRControlMsg, MockVRInit};
+pub use {MockVRCompositorInit, MockVRDeviceProfile, MockVRFault, MockVRFaultKind, MockVRFaultPlan, MockVRFaultTrigger, MockVRFrame, MockVRGamepadInit, MockVRTimelineEvent, MockVRTracking};
+pub use {MockVRHitTestResult, MockVRInputSource, MockVRTargetRayMode, MockVRVisibility, MockVRWorld};
+pub use self::profile::{MOCK_PROFILES, find_profile, mock_profile};
+pub use self::handle::MockVRDisplayHandle;
+#[cfg(feature = "replay")]
+pub(crate) use self::display::MockVRReplayMsg;
+pub use self::service::MockVRServiceRemote;
+#[cfg(feature = "mock-server")]
+pub use self::server::{MockVRControlServer, MockVRServerCommand, MockVRServerReply};
+#[cfg(feature = "config")]
+pub use self::data::{load_profile, load_timeline};
 use std::sync::mpsc::{channel, Sender};
+#[cfg(feature = "ipc")]
+use ipc_channel::ipc::{self, IpcSender};
+#[cfg(feature = "ipc")]
+use std::thread;
 
 pub struct MockServiceCreator;
 
@@ -11,15 +37,45 @@ impl MockServiceCreator {
         Box::new(MockServiceCreator)
     }
 
-    pub fn new_service_with_remote(init: MockVRInit) -> (Box<dyn VRService>, Sender<MockVRControlMsg>) {
+    pub fn new_service_with_init(init: MockVRInit, clock: VRClockPtr, ids: VRIdAllocatorPtr) -> Box<dyn VRService> {
+        Box::new(service::MockVRService::new(init, clock, ids))
+    }
+
+    pub fn new_service_with_remote(init: MockVRInit, clock: VRClockPtr, ids: VRIdAllocatorPtr)
+                                   -> (Box<dyn VRService>, Sender<MockVRControlMsg>) {
         let (send, rcv) = channel();
-        let service = service::MockVRService::new_with_receiver(rcv, init);
+        let service = service::MockVRService::new_with_receiver(rcv, init, clock, ids);
         (Box::new(service), send)
     }
+
+    // Same as new_service_with_remote, but the remote can be sent to another process
+    #[cfg(feature = "ipc")]
+    pub fn new_service_with_ipc_remote(init: MockVRInit, clock: VRClockPtr, ids: VRIdAllocatorPtr)
+                                       -> Result<(Box<dyn VRService>, IpcSender<MockVRControlMsg>), String> {
+        let (ipc_send, ipc_rcv) = ipc::channel().map_err(|e| format!("Failed to create IPC channel: {}", e))?;
+        let (send, rcv) = channel();
+        // Forwards the messages to the display until either side goes away
+        thread::spawn(move || {
+            while let Ok(msg) = ipc_rcv.recv() {
+                if send.send(msg).is_err() {
+                    break;
+                }
+            }
+        });
+        let service = service::MockVRService::new_with_receiver(rcv, init, clock, ids);
+        Ok((Box::new(service), ipc_send))
+    }
+
+    // Creates a mock service without displays, they are added and removed through the remote
+    pub fn new_service_with_displays(clock: VRClockPtr, ids: VRIdAllocatorPtr)
+                                     -> (Box<dyn VRService>, MockVRServiceRemote) {
+        let (service, remote) = service::MockVRService::new_with_service_remote(clock, ids);
+        (Box::new(service), remote)
+    }
 }
 
 impl VRServiceCreator for MockServiceCreator {
-     fn new_service(&self) -> Box<dyn VRService> {
-         Box::new(service::MockVRService::new(Default::default()))
+     fn new_service(&self, clock: VRClockPtr, ids: VRIdAllocatorPtr) -> Box<dyn VRService> {
+         Box::new(service::MockVRService::new(Default::default(), clock, ids))
      }
 }
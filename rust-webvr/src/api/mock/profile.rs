@@ -0,0 +1,177 @@
+use {MockVRDeviceProfile, MockVRGamepadInit, MockVRTargetRayMode, VRDisplayCapabilities, VRFieldOfView, VRGamepadHand};
+
+// Names of the built-in device profiles
+pub const MOCK_PROFILES: &[&str] = &["vive", "rift", "quest", "cardboard", "3dof", "desktop"];
+
+// Returns the built-in device profile with the given name
+pub fn mock_profile(name: &str) -> Option<MockVRDeviceProfile> {
+    match name {
+        "vive" => Some(vive()),
+        "rift" => Some(rift()),
+        "quest" => Some(quest()),
+        "cardboard" => Some(cardboard()),
+        "3dof" => Some(three_dof()),
+        "desktop" => Some(desktop()),
+        _ => None,
+    }
+}
+
+// Looks for a built-in profile first, then for a profile file
+pub fn find_profile(name: &str) -> Result<MockVRDeviceProfile, String> {
+    match mock_profile(name) {
+        Some(profile) => Ok(profile),
+        #[cfg(feature = "config")]
+        None => super::load_profile(name),
+        #[cfg(not(feature = "config"))]
+        None => Err(format!("Unknown mock profile: {}", name)),
+    }
+}
+
+// WebXR input profiles of the controllers, from the most to the least specific
+const VIVE_INPUT_PROFILES: &[&str] = &["htc-vive", "generic-trigger-squeeze-touchpad"];
+const RIFT_INPUT_PROFILES: &[&str] = &["oculus-touch", "generic-trigger-squeeze-thumbstick"];
+const QUEST_INPUT_PROFILES: &[&str] = &["oculus-touch-v2", "oculus-touch", "generic-trigger-squeeze-thumbstick"];
+const THREE_DOF_INPUT_PROFILES: &[&str] = &["generic-touchpad"];
+
+fn fov(up: f64, right: f64, down: f64, left: f64) -> VRFieldOfView {
+    VRFieldOfView {
+        up_degrees: up,
+        right_degrees: right,
+        down_degrees: down,
+        left_degrees: left,
+    }
+}
+
+fn capabilities(has_position: bool, has_external_display: bool) -> VRDisplayCapabilities {
+    VRDisplayCapabilities {
+        has_position,
+        has_orientation: true,
+        has_external_display,
+        can_present: true,
+        .. Default::default()
+    }
+}
+
+fn controller(name: &str, hand: VRGamepadHand, buttons: usize, axes: usize, profiles: &[&str]) -> MockVRGamepadInit {
+    MockVRGamepadInit {
+        name: name.into(),
+        hand,
+        buttons,
+        axes,
+        serial: None,
+        target_ray_mode: MockVRTargetRayMode::TrackedPointer,
+        profiles: profiles.iter().map(|profile| profile.to_string()).collect(),
+        pointer_origin: None,
+    }
+}
+
+fn vive() -> MockVRDeviceProfile {
+    MockVRDeviceProfile {
+        name: "vive".into(),
+        display_name: "Mock HTC Vive".into(),
+        render_width: 1512,
+        render_height: 1680,
+        left_field_of_view: fov(55.82093048095703, 51.26948547363281, 55.707801818847656, 54.42263412475586),
+        right_field_of_view: fov(55.898048400878906, 54.37410354614258, 55.614715576171875, 51.304901123046875),
+        ipd: 0.0719,
+        refresh_rate: 90.0,
+        capabilities: capabilities(true, true),
+        stage_size: Some((2.0, 2.0)),
+        // Trackpad, trigger, grip and menu buttons, trackpad axes
+        controllers: vec![controller("HTC Vive Controller", VRGamepadHand::Left, 4, 2, VIVE_INPUT_PROFILES),
+                          controller("HTC Vive Controller", VRGamepadHand::Right, 4, 2, VIVE_INPUT_PROFILES)],
+    }
+}
+
+fn rift() -> MockVRDeviceProfile {
+    MockVRDeviceProfile {
+        name: "rift".into(),
+        display_name: "Mock Oculus Rift".into(),
+        render_width: 1344,
+        render_height: 1600,
+        left_field_of_view: fov(41.65, 35.57, 48.00, 43.97),
+        right_field_of_view: fov(41.65, 43.97, 48.00, 35.57),
+        ipd: 0.064,
+        refresh_rate: 90.0,
+        capabilities: capabilities(true, true),
+        stage_size: Some((2.0, 2.0)),
+        // Thumbstick, trigger, grip, A/X and B/Y buttons, thumbstick axes
+        controllers: vec![controller("Oculus Touch (Left)", VRGamepadHand::Left, 5, 2, RIFT_INPUT_PROFILES),
+                          controller("Oculus Touch (Right)", VRGamepadHand::Right, 5, 2, RIFT_INPUT_PROFILES)],
+    }
+}
+
+fn quest() -> MockVRDeviceProfile {
+    MockVRDeviceProfile {
+        name: "quest".into(),
+        display_name: "Mock Oculus Quest".into(),
+        render_width: 1440,
+        render_height: 1600,
+        left_field_of_view: fov(48.0, 43.0, 53.0, 47.0),
+        right_field_of_view: fov(48.0, 47.0, 53.0, 43.0),
+        ipd: 0.064,
+        refresh_rate: 72.0,
+        capabilities: capabilities(true, false),
+        stage_size: Some((2.0, 2.0)),
+        controllers: vec![controller("Oculus Touch (Left)", VRGamepadHand::Left, 5, 2, QUEST_INPUT_PROFILES),
+                          controller("Oculus Touch (Right)", VRGamepadHand::Right, 5, 2, QUEST_INPUT_PROFILES)],
+    }
+}
+
+fn cardboard() -> MockVRDeviceProfile {
+    MockVRDeviceProfile {
+        name: "cardboard".into(),
+        display_name: "Mock Cardboard".into(),
+        render_width: 960,
+        render_height: 1080,
+        left_field_of_view: fov(40.0, 40.0, 40.0, 40.0),
+        right_field_of_view: fov(40.0, 40.0, 40.0, 40.0),
+        ipd: 0.064,
+        refresh_rate: 60.0,
+        capabilities: capabilities(false, false),
+        stage_size: None,
+        controllers: Vec::new(),
+    }
+}
+
+fn three_dof() -> MockVRDeviceProfile {
+    MockVRDeviceProfile {
+        name: "3dof".into(),
+        display_name: "Mock 3DoF headset".into(),
+        render_width: 1280,
+        render_height: 1440,
+        left_field_of_view: fov(45.0, 45.0, 45.0, 45.0),
+        right_field_of_view: fov(45.0, 45.0, 45.0, 45.0),
+        ipd: 0.064,
+        refresh_rate: 60.0,
+        capabilities: capabilities(false, false),
+        stage_size: None,
+        // Touchpad and app buttons, touchpad axes
+        controllers: vec![controller("3DoF Controller", VRGamepadHand::Right, 2, 2, THREE_DOF_INPUT_PROFILES)],
+    }
+}
+
+fn desktop() -> MockVRDeviceProfile {
+    MockVRDeviceProfile {
+        name: "desktop".into(),
+        display_name: "Mock desktop viewer".into(),
+        render_width: 1280,
+        render_height: 720,
+        left_field_of_view: fov(22.5, 40.0, 22.5, 40.0),
+        right_field_of_view: fov(22.5, 40.0, 22.5, 40.0),
+        ipd: 0.0,
+        refresh_rate: 60.0,
+        capabilities: capabilities(false, true),
+        stage_size: None,
+        controllers: Vec::new(),
+    }
+}
+
+// Milliseconds between two vsyncs, None if the refresh rate isn't a positive number
+pub fn vsync_interval(refresh_rate: f64) -> Option<f64> {
+    if refresh_rate > 0.0 && refresh_rate.is_finite() {
+        Some(1000.0 / refresh_rate)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,24 @@
+// Small seeded generator (xorshift64*), so that mock runs can be reproduced
+pub struct MockRng(u64);
+
+impl MockRng {
+    pub fn new(seed: u64) -> MockRng {
+        // xorshift gets stuck on 0
+        match seed ^ 0x9E37_79B9_7F4A_7C15 {
+            0 => MockRng(0x9E37_79B9_7F4A_7C15),
+            state => MockRng(state),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
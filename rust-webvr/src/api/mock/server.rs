@@ -0,0 +1,233 @@
+use {MockVRControlMsg, MockVRFaultPlan, MockVRInit, VREvent};
+use super::service::MockVRServiceRemote;
+use serde_json;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+
+// Command sent by a client, one JSON object per line, e.g.
+//
+//     {"AddDisplay": {"eye_level": 1.6}}
+//     {"Control": [1, {"SetViewerPose": [[0.0, 1.6, 0.0], [0.0, 0.0, 0.0, 1.0]]}]}
+//     {"Control": [1, "Blur"]}
+#[derive(Deserialize)]
+pub enum MockVRServerCommand {
+    AddDisplay(Box<MockVRInit>),
+    RemoveDisplay(u32),
+    Control(u32, MockVRControlMsg),
+    SetFaults(MockVRFaultPlan),
+}
+
+// Line sent back to the clients
+#[derive(Serialize)]
+pub enum MockVRServerReply {
+    DisplayAdded(u32),
+    // Events of the mock service, as they are polled by the application
+    Event(Box<VREvent>),
+    Error(String),
+}
+
+// Lets scripts written in any language drive a mock service, through line-delimited JSON
+// over a localhost TCP port or a Unix socket
+#[derive(Clone)]
+pub struct MockVRControlServer {
+    remote: MockVRServiceRemote,
+    // Control channels of the displays, shared by all the connections
+    displays: Arc<Mutex<HashMap<u32, Sender<MockVRControlMsg>>>>,
+}
+
+impl MockVRControlServer {
+    pub fn new(remote: MockVRServiceRemote) -> MockVRControlServer {
+        MockVRControlServer {
+            remote,
+            displays: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn add_display(&self, init: MockVRInit) -> u32 {
+        let (display_id, sender) = self.remote.add_display(init);
+        self.displays.lock().unwrap().insert(display_id, sender);
+        display_id
+    }
+
+    // Listens on "tcp:<port>" (port 0 picks a free one) or "unix:<path>" from a new thread.
+    // Returns the address actually listened on.
+    pub fn listen(self, address: &str) -> Result<String, String> {
+        if let Some(port) = address.strip_prefix("tcp:") {
+            let port = port.parse::<u16>().map_err(|e| format!("Invalid port in {}: {}", address, e))?;
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).map_err(|e| e.to_string())?;
+            let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => self.serve(stream),
+                        Err(e) => warn!("Mock control server connection failed: {}", e),
+                    }
+                }
+            });
+            Ok(format!("tcp:{}", port))
+        } else if let Some(path) = address.strip_prefix("unix:") {
+            self.listen_unix(path)?;
+            Ok(address.into())
+        } else {
+            Err(format!("Unknown mock control server address: {}", address))
+        }
+    }
+
+    #[cfg(unix)]
+    fn listen_unix(self, path: &str) -> Result<(), String> {
+        // A socket left behind by a previous server that nobody listens on anymore
+        let is_socket = fs::symlink_metadata(path).map(|m| m.file_type().is_socket()).unwrap_or(false);
+        if is_socket && UnixStream::connect(path).is_err() {
+            fs::remove_file(path).map_err(|e| format!("Error removing stale socket {}: {}", path, e))?;
+        }
+        let listener = UnixListener::bind(path).map_err(|e| format!("Error binding {}: {}", path, e))?;
+        let socket = MockVRSocketFile(PathBuf::from(path));
+        thread::spawn(move || {
+            let _socket = socket;
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => self.serve(stream),
+                    Err(e) => warn!("Mock control server connection failed: {}", e),
+                }
+            }
+        });
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn listen_unix(self, _path: &str) -> Result<(), String> {
+        Err("Unix sockets are not supported on this platform".into())
+    }
+
+    // Handles a client from its own threads
+    fn serve<S: MockVRStream>(&self, reader: S) {
+        let mut writer = match reader.try_clone() {
+            Ok(writer) => writer,
+            Err(e) => return warn!("Mock control server connection failed: {}", e),
+        };
+        let (reply_sender, reply_receiver) = channel();
+
+        let events = self.remote.observe_events();
+        let event_sender = reply_sender.clone();
+        thread::spawn(move || {
+            for event in events {
+                if event_sender.send(MockVRServerReply::Event(Box::new(event))).is_err() {
+                    break;
+                }
+            }
+        });
+
+        thread::spawn(move || {
+            for reply in reply_receiver {
+                let line = match serde_json::to_string(&reply) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        // Also ends the reading thread
+                        error!("Failed to serialize mock control server reply: {}", e);
+                        let _ = writer.shutdown();
+                        break;
+                    }
+                };
+                if writeln!(writer, "{}", line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let server = self.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let reply = match serde_json::from_str(&line) {
+                    Ok(command) => server.handle_command(command),
+                    Err(e) => Some(MockVRServerReply::Error(format!("Invalid command: {}", e))),
+                };
+                if let Some(reply) = reply {
+                    let _ = reply_sender.send(reply);
+                }
+            }
+        });
+    }
+
+    fn handle_command(&self, command: MockVRServerCommand) -> Option<MockVRServerReply> {
+        match command {
+            MockVRServerCommand::AddDisplay(init) => {
+                Some(MockVRServerReply::DisplayAdded(self.add_display(*init)))
+            }
+            MockVRServerCommand::RemoveDisplay(display_id) => {
+                self.displays.lock().unwrap().remove(&display_id);
+                self.remote.remove_display(display_id);
+                None
+            }
+            MockVRServerCommand::Control(display_id, msg) => {
+                match self.displays.lock().unwrap().get(&display_id) {
+                    Some(sender) => {
+                        let _ = sender.send(msg);
+                        None
+                    }
+                    None => Some(MockVRServerReply::Error(format!("Unknown mock display: {}", display_id))),
+                }
+            }
+            MockVRServerCommand::SetFaults(plan) => {
+                self.remote.set_faults(plan);
+                None
+            }
+        }
+    }
+}
+
+// Connection of a client
+trait MockVRStream: Read + Write + Send + Sized + 'static {
+    fn try_clone(&self) -> io::Result<Self>;
+    fn shutdown(&self) -> io::Result<()>;
+}
+
+impl MockVRStream for TcpStream {
+    fn try_clone(&self) -> io::Result<TcpStream> {
+        TcpStream::try_clone(self)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        TcpStream::shutdown(self, Shutdown::Both)
+    }
+}
+
+#[cfg(unix)]
+impl MockVRStream for UnixStream {
+    fn try_clone(&self) -> io::Result<UnixStream> {
+        UnixStream::try_clone(self)
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        UnixStream::shutdown(self, Shutdown::Both)
+    }
+}
+
+// Removes the socket file once its listener goes away
+#[cfg(unix)]
+struct MockVRSocketFile(PathBuf);
+
+#[cfg(unix)]
+impl Drop for MockVRSocketFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
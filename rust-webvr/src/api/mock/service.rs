@@ -1,46 +1,150 @@
-use {VRService, VRDisplayPtr, VREvent, VRGamepadPtr};
+use {VRClockPtr, VRDeviceIdentity, VRDisplay, VRIdAllocatorPtr, VRService, VRDisplayPtr, VRDisplayEvent, VREvent, VRGamepadPtr};
 use super::display::{MockVRDisplay, MockVRDisplayPtr};
-use super::{MockVRControlMsg, MockVRInit};
+use super::{MockVRControlMsg, MockVRFaultKind, MockVRFaultPlan, MockVRFrame, MockVRInit};
+use super::fault::{MockVRFaults, MockVRFaultsPtr};
+use super::handle::MockVRDisplayHandle;
+use std::cell::RefCell;
+use std::mem;
 use std::thread;
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{channel, Receiver, Sender};
 
 pub struct MockVRService {
-    display: MockVRDisplayPtr,
+    displays: RefCell<Vec<MockVRDisplayPtr>>,
+    clock: VRClockPtr,
+    ids: VRIdAllocatorPtr,
+    // Displays added or removed through a MockVRServiceRemote
+    receiver: Option<Receiver<MockVRServiceMsg>>,
+    events: RefCell<Vec<VREvent>>,
+    faults: MockVRFaultsPtr,
+    // Receive a copy of the events returned by poll_events
+    observers: RefCell<Vec<Sender<VREvent>>>,
 }
 
 unsafe impl Send for MockVRService {}
 
+enum MockVRServiceMsg {
+    AddDisplay(u32, VRDeviceIdentity, Box<MockVRInit>, Receiver<MockVRControlMsg>, MockVRDisplayHandle),
+    RemoveDisplay(u32),
+    CaptureFrames(u32, Sender<MockVRFrame>, bool),
+    SetFaults(MockVRFaultPlan),
+    ObserveEvents(Sender<VREvent>),
+}
+
+// Adds and removes the displays of a mock service at runtime
+#[derive(Clone)]
+pub struct MockVRServiceRemote {
+    sender: Sender<MockVRServiceMsg>,
+    ids: VRIdAllocatorPtr,
+}
+
 impl VRService for MockVRService {
     fn initialize(&mut self) -> Result<(), String> { 
-        Ok(())
+        self.handle_service_msgs();
+        self.check_fault(MockVRFaultKind::FailInitialize)
     }
 
     fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>,String> {
-        Ok(vec![self.display.clone()])
+        self.handle_service_msgs();
+        self.check_fault(MockVRFaultKind::FailFetchDisplays)?;
+        // An unplugged display is not reported until it gets connected again
+        Ok(self.displays.borrow().iter()
+                                 .filter(|display| display.borrow().data().connected)
+                                 .map(|display| display.clone() as VRDisplayPtr)
+                                 .collect())
     }
 
     fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>,String> {
-        Ok(Vec::new())
+        self.handle_service_msgs();
+        self.check_fault(MockVRFaultKind::FailFetchGamepads)?;
+        let mut gamepads = Vec::new();
+        for display in self.displays.borrow().iter() {
+            // Gamepads go away with their display
+            if display.borrow().data().connected {
+                gamepads.extend(display.borrow_mut().fetch_gamepads()?);
+            }
+        }
+        Ok(gamepads)
     }
 
     fn is_available(&self) -> bool {
-        true   
+        !self.faults.lock().unwrap().peek(MockVRFaultKind::Unavailable)
     }
 
     fn poll_events(&self) -> Vec<VREvent> {
-        self.display.borrow().poll_events()
+        self.handle_service_msgs();
+        let mut events = mem::take(&mut *self.events.borrow_mut());
+        for display in self.displays.borrow().iter() {
+            events.extend(display.borrow().poll_events());
+        }
+        // Observers that went away are forgotten
+        self.observers.borrow_mut().retain(|observer| {
+            events.iter().all(|event| observer.send(event.clone()).is_ok())
+        });
+        events
+    }
+
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn is_hardware(&self) -> bool {
+        false
     }
 }
 
 impl MockVRService {
-    pub fn new(init: MockVRInit) -> MockVRService {
+    pub fn new(mut init: MockVRInit, clock: VRClockPtr, ids: VRIdAllocatorPtr) -> MockVRService {
+        let faults = MockVRFaults::new_ptr(init.faults.take());
+        let display = MockVRDisplay::new(init, clock.clone(), ids.clone(), faults.clone());
+        MockVRService::from_displays(vec![display], None, clock, ids, faults)
+    }
+
+    pub fn new_with_receiver(rcv: Receiver<MockVRControlMsg>,
+                             mut init: MockVRInit,
+                             clock: VRClockPtr,
+                             ids: VRIdAllocatorPtr)
+                             -> MockVRService {
+        let faults = MockVRFaults::new_ptr(init.faults.take());
+        let display = MockVRDisplay::new(init, clock.clone(), ids.clone(), faults.clone());
+        MockVRService::spawn_receiver(&display, rcv);
+        MockVRService::from_displays(vec![display], None, clock, ids, faults)
+    }
+
+    pub fn new_with_service_remote(clock: VRClockPtr, ids: VRIdAllocatorPtr) -> (MockVRService, MockVRServiceRemote) {
+        let (sender, receiver) = channel();
+        let remote = MockVRServiceRemote {
+            sender,
+            ids: ids.clone(),
+        };
+        let faults = MockVRFaults::new_ptr(None);
+        (MockVRService::from_displays(Vec::new(), Some(receiver), clock, ids, faults), remote)
+    }
+
+    fn from_displays(displays: Vec<MockVRDisplayPtr>,
+                     receiver: Option<Receiver<MockVRServiceMsg>>,
+                     clock: VRClockPtr,
+                     ids: VRIdAllocatorPtr,
+                     faults: MockVRFaultsPtr)
+                     -> MockVRService {
         MockVRService {
-            display: MockVRDisplay::new(init),
+            displays: RefCell::new(displays),
+            clock,
+            ids,
+            receiver,
+            events: RefCell::new(Vec::new()),
+            faults,
+            observers: RefCell::new(Vec::new()),
         }
     }
 
-    pub fn new_with_receiver(rcv: Receiver<MockVRControlMsg>, init: MockVRInit) -> MockVRService {
-        let display = MockVRDisplay::new(init);
+    fn check_fault(&self, kind: MockVRFaultKind) -> Result<(), String> {
+        if self.faults.lock().unwrap().check(kind) {
+            return Err(format!("Mock fault: {:?}", kind));
+        }
+        Ok(())
+    }
+
+    fn spawn_receiver(display: &MockVRDisplayPtr, rcv: Receiver<MockVRControlMsg>) {
         let state = display.borrow().state_handle();
         thread::spawn(move || {
             while let Ok(msg) = rcv.recv() {
@@ -51,9 +155,92 @@ impl MockVRService {
                 state.lock().unwrap().handle_msg(msg);
             }
         });
-        MockVRService {
-            display,
+    }
+
+    fn handle_service_msgs(&self) {
+        let receiver = match self.receiver {
+            Some(ref receiver) => receiver,
+            None => return,
+        };
+        while let Ok(msg) = receiver.try_recv() {
+            match msg {
+                MockVRServiceMsg::AddDisplay(display_id, identity, init, rcv, handle) => {
+                    // The fault plan belongs to the service, see MockVRServiceRemote::set_faults
+                    let display = MockVRDisplay::new_with_id(display_id, identity, *init, self.clock.clone(),
+                                                             self.ids.clone(), self.faults.clone());
+                    MockVRService::spawn_receiver(&display, rcv);
+                    handle.set_state(display.borrow().state_handle());
+                    let data = display.borrow().data();
+                    if data.connected {
+                        self.events.borrow_mut().push(VRDisplayEvent::Connect(data).into());
+                    }
+                    self.displays.borrow_mut().push(display);
+                }
+                MockVRServiceMsg::RemoveDisplay(display_id) => {
+                    let mut displays = self.displays.borrow_mut();
+                    if let Some(index) = displays.iter().position(|display| display.borrow().id() == display_id) {
+                        let display = displays.remove(index);
+                        if display.borrow().data().connected {
+                            self.events.borrow_mut().push(VRDisplayEvent::Disconnect(display_id).into());
+                        }
+                    }
+                }
+                MockVRServiceMsg::CaptureFrames(display_id, sender, capture_pixels) => {
+                    let displays = self.displays.borrow();
+                    if let Some(display) = displays.iter().find(|display| display.borrow().id() == display_id) {
+                        display.borrow().state_handle().lock().unwrap().capture_frames(sender, capture_pixels);
+                    }
+                }
+                MockVRServiceMsg::SetFaults(plan) => {
+                    *self.faults.lock().unwrap() = MockVRFaults::new(plan);
+                }
+                MockVRServiceMsg::ObserveEvents(sender) => {
+                    self.observers.borrow_mut().push(sender);
+                }
+            }
         }
     }
 }
 
+impl MockVRServiceRemote {
+    // Returns the id of the new display and its control channel
+    pub fn add_display(&self, init: MockVRInit) -> (u32, Sender<MockVRControlMsg>) {
+        let (display_id, sender, _) = self.add_display_with_handle(init);
+        (display_id, sender)
+    }
+
+    // Also returns a handle to read the state that the VRDisplay API doesn't expose
+    pub fn add_display_with_handle(&self, init: MockVRInit) -> (u32, Sender<MockVRControlMsg>, MockVRDisplayHandle) {
+        let (display_id, identity) = MockVRDisplay::allocate_id(&init, &self.ids);
+        let (sender, receiver) = channel();
+        let handle = MockVRDisplayHandle::new();
+        let _ = self.sender.send(MockVRServiceMsg::AddDisplay(display_id, identity, Box::new(init), receiver, handle.clone()));
+        (display_id, sender, handle)
+    }
+
+    pub fn remove_display(&self, display_id: u32) {
+        let _ = self.sender.send(MockVRServiceMsg::RemoveDisplay(display_id));
+    }
+
+    // Returns the frames submitted to the display, once the service handled the request
+    // (i.e. after the next fetch_displays or poll_events call).
+    // The pixels are read back from the layer textures when capture_pixels is set.
+    pub fn capture_frames(&self, display_id: u32, capture_pixels: bool) -> Receiver<MockVRFrame> {
+        let (sender, receiver) = channel();
+        let _ = self.sender.send(MockVRServiceMsg::CaptureFrames(display_id, sender, capture_pixels));
+        receiver
+    }
+
+    // Replaces the fault plan of the service and restarts the frame count,
+    // once the service handled the request
+    pub fn set_faults(&self, plan: MockVRFaultPlan) {
+        let _ = self.sender.send(MockVRServiceMsg::SetFaults(plan));
+    }
+
+    // Returns a copy of the events of the service, as they are returned by poll_events
+    pub fn observe_events(&self) -> Receiver<VREvent> {
+        let (sender, receiver) = channel();
+        let _ = self.sender.send(MockVRServiceMsg::ObserveEvents(sender));
+        receiver
+    }
+}
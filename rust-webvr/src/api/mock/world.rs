@@ -0,0 +1,82 @@
+use {MockVRHitTestResult, MockVRWorld};
+
+// Intersections of a ray with the faces of the world, closest first
+pub fn hit_test(world: &MockVRWorld, origin: [f32; 3], direction: [f32; 3]) -> Vec<MockVRHitTestResult> {
+    let direction = normalize(direction);
+    let mut results = Vec::new();
+    for region in &world.regions {
+        for face in &region.faces {
+            if let Some((distance, mut normal)) = intersect(face, origin, direction) {
+                // Faces can be hit from both sides
+                if dot(normal, direction) > 0.0 {
+                    normal = [-normal[0], -normal[1], -normal[2]];
+                }
+                results.push(MockVRHitTestResult {
+                    position: add(origin, scale(direction, distance)),
+                    normal,
+                    distance,
+                    kind: region.kind,
+                });
+            }
+        }
+    }
+    results.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    results
+}
+
+// Möller–Trumbore ray-triangle intersection, returns the distance and the normal of the face
+fn intersect(face: &[[f32; 3]; 3], origin: [f32; 3], direction: [f32; 3]) -> Option<(f32, [f32; 3])> {
+    const EPSILON: f32 = 1e-6;
+    let edge1 = sub(face[1], face[0]);
+    let edge2 = sub(face[2], face[0]);
+    let p = cross(direction, edge2);
+    let det = dot(edge1, p);
+    // Parallel to the face, or degenerate face
+    if det.abs() < EPSILON {
+        return None;
+    }
+    let t = sub(origin, face[0]);
+    let u = dot(t, p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = cross(t, edge1);
+    let v = dot(direction, q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = dot(edge2, q) / det;
+    // Also skips the rays or faces with non-finite coordinates
+    if !distance.is_finite() || distance < 0.0 {
+        return None;
+    }
+    Some((distance, normalize(cross(edge1, edge2))))
+}
+
+fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f32; 3], s: f32) -> [f32; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = dot(a, a).sqrt();
+    if length == 0.0 {
+        return a;
+    }
+    scale(a, 1.0 / length)
+}
@@ -8,18 +8,50 @@ pub use self::vrexternal::VRExternalServiceCreator;
 #[cfg(feature = "mock")]
 mod mock;
 #[cfg(feature = "mock")]
-pub use self::mock::{MockServiceCreator, MockVRControlMsg, MockVRInit};
+pub use self::mock::{MockServiceCreator, MockVRCompositorInit, MockVRControlMsg, MockVRFault, MockVRFaultKind, MockVRFaultPlan, MockVRFaultTrigger, MockVRFrame, MockVRGamepadInit, MockVRInit, MockVRServiceRemote, MockVRTracking};
+#[cfg(feature = "mock")]
+pub use self::mock::MockVRDisplayHandle;
+#[cfg(feature = "mock")]
+pub use self::mock::{MOCK_PROFILES, MockVRDeviceProfile, find_profile as find_mock_profile, mock_profile};
+#[cfg(feature = "mock-server")]
+pub use self::mock::{MockVRControlServer, MockVRServerCommand, MockVRServerReply};
+#[cfg(all(feature = "mock", feature = "config"))]
+pub use self::mock::{load_profile as load_mock_profile, load_timeline as load_mock_timeline};
 
 #[cfg(feature = "glwindow")]
 mod glwindow;
 #[cfg(feature = "glwindow")]
-pub use self::glwindow::GlWindowVRService;
+pub use self::glwindow::{EventsLoopFactory, GlWindowVRMainThreadHeartbeat, GlWindowVRService};
 
 #[cfg(feature = "magicleap")]
 mod magicleap;
 #[cfg(feature = "magicleap")]
 pub use self::magicleap::MagicLeapVRService;
 
+#[cfg(feature = "plugin")]
+mod plugin;
+#[cfg(feature = "plugin")]
+pub use self::plugin::PluginServiceCreator;
+
+#[cfg(feature = "ipc")]
+mod proxy;
+#[cfg(feature = "ipc")]
+pub use self::proxy::{ProxyVRService, VRProxyDisplayRequest, VRProxyRequest, VRProxyServer};
+
+#[cfg(feature = "recording")]
+mod recording;
+#[cfg(feature = "recording")]
+pub use self::recording::{RecordingVRService, VRRecord, VRRecordEntry, VRRecorder, VRRecorderPtr, VRRecording, VRRecordingHeader};
+#[cfg(feature = "recording")]
+pub use self::recording::{VR_RECORDING_MAGIC, VR_RECORDING_VERSION};
+#[cfg(feature = "replay")]
+pub use self::recording::{ReplayVRService, VRReplayMode, VRReplayRemote};
+
+#[cfg(feature = "pose-ring")]
+mod pose_ring;
+#[cfg(feature = "pose-ring")]
+pub use self::pose_ring::{VRPoseRingError, VRPoseRingFrame, VRPoseRingReader, VRPoseRingWriter};
+
 #[cfg(all(target_os="windows", feature = "openvr"))]
 mod openvr;
 #[cfg(all(target_os="windows", feature = "openvr"))]
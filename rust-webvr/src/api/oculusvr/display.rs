@@ -1,8 +1,8 @@
 #![cfg(target_os="android")]
 #![cfg(feature = "oculusvr")]
 
-use {VRDisplay, VRDisplayData, VRDisplayCapabilities, VREvent, VRDisplayEvent, 
-    VREyeParameters, VRFramebuffer, VRFramebufferAttributes, VRFrameData, VRGamepadPtr, VRLayer, VRViewport};
+use {VRClockPtr, VRDeviceIdentity, VRDisplay, VRDisplayData, VRDisplayCapabilities, VREvent, VRDisplayEvent, 
+    VREyeParameters, VRFramebuffer, VRFramebufferAttributes, VRFrameData, VRGamepadPtr, VRIdAllocatorPtr, VRLayer, VRViewport};
 use android_injected_glue::ffi as ndk;
 use gl;
 use egl;
@@ -19,7 +19,6 @@ use std::sync::{Arc, Condvar, Mutex};
 use super::gamepad::{OculusVRGamepad, OculusVRGamepadPtr};
 use rust_webvr_api::jni_utils::JNIScope;
 use super::service::{OVRJava, OVRServiceJava};
-use rust_webvr_api::utils;
 
 pub type OculusVRDisplayPtr = Arc<RefCell<OculusVRDisplay>>;
 const OVR_INSERT_FENCE: bool = false;
@@ -36,6 +35,7 @@ enum LifeCycleAction {
 
 pub struct OculusVRDisplay {
     display_id: u32,
+    identity: VRDeviceIdentity,
     ovr: *mut ovr::ovrMobile,
     service_java: OVRServiceJava,
     // Used in the data query thread. Shared with OVRService.
@@ -60,6 +60,8 @@ pub struct OculusVRDisplay {
     // Gamepads linked to this display
     gamepads: Vec<OculusVRGamepadPtr>,
     attributes: VRFramebufferAttributes,
+    clock: VRClockPtr,
+    ids: VRIdAllocatorPtr,
 }
 
 unsafe impl Send for OculusVRDisplay {}
@@ -76,6 +78,7 @@ impl VRDisplay for OculusVRDisplay {
 
         data.display_name = "Oculus VR".into();
         data.display_id = self.display_id;
+        data.identity = self.identity.clone();
         data.connected = true;
     
         self.fetch_capabilities(&mut data.capabilities);
@@ -140,7 +143,7 @@ impl VRDisplay for OculusVRDisplay {
         self.predicted_tracking = unsafe { ovr::vrapi_GetPredictedTracking2(self.ovr, self.predicted_display_time) };
 
         if self.gamepads.len() < 2 {
-            OculusVRGamepad::refresh_available_gamepads(self.ovr, self.display_id, &mut self.gamepads);
+            OculusVRGamepad::refresh_available_gamepads(self.ovr, self.display_id, &self.clock, &self.ids, &mut self.gamepads);
         }
 
         for gamepad in &self.gamepads {
@@ -321,10 +324,16 @@ impl VRDisplay for OculusVRDisplay {
 
 impl OculusVRDisplay {
     pub fn new(service_java: OVRServiceJava,
-               ovr_java: *const ovr::ovrJava)
+               ovr_java: *const ovr::ovrJava,
+               clock: VRClockPtr,
+               ids: VRIdAllocatorPtr)
                -> Arc<RefCell<OculusVRDisplay>> {
+        // VrApi doesn't expose serial numbers, use the device type instead.
+        let device_type = unsafe { ovr::vrapi_GetSystemPropertyInt(ovr_java, VRAPI_SYS_PROP_DEVICE_TYPE) };
+        let identity = VRDeviceIdentity::new("oculusvr", format!("device-type-{}", device_type));
         Arc::new(RefCell::new(OculusVRDisplay {
-            display_id: utils::new_id(),
+            display_id: ids.id_for(&identity),
+            identity: identity,
             ovr: ptr::null_mut(),
             service_java: service_java,
             data_ovr_java: ovr_java,
@@ -345,6 +354,8 @@ impl OculusVRDisplay {
             leave_vr_condition: (Mutex::new(false), Condvar::new()),
             gamepads: Vec::new(),
             attributes: Default::default(),
+            clock: clock,
+            ids: ids,
         }))
     }
 
@@ -390,7 +401,7 @@ impl OculusVRDisplay {
         }
 
         // Refresh gamepads after entering VR mode
-        OculusVRGamepad::refresh_available_gamepads(self.ovr, self.display_id, &mut self.gamepads);
+        OculusVRGamepad::refresh_available_gamepads(self.ovr, self.display_id, &self.clock, &self.ids, &mut self.gamepads);
     }
 
     fn exit_vr_mode(&mut self) {
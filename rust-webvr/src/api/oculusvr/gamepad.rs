@@ -1,7 +1,7 @@
 #![cfg(target_os="android")]
 #![cfg(feature = "oculusvr")]
 
-use {VRGamepad, VRGamepadButton, VRGamepadData, VRGamepadHand, VRGamepadState};
+use {VRClockPtr, VRDeviceIdentity, VRGamepad, VRGamepadButton, VRGamepadData, VRGamepadHand, VRGamepadState, VRIdAllocatorPtr};
 use ovr_mobile_sys as ovr;
 use ovr_mobile_sys::ovrButton::*;
 use ovr_mobile_sys::ovrControllerCapabilties::*;
@@ -11,7 +11,6 @@ use std::mem;
 use std::ptr;
 use std::sync::Arc;
 use super::display::{ovr_quat_to_array, ovr_vec3_to_array};
-use rust_webvr_api::utils;
 
 pub type OculusVRGamepadPtr = Arc<RefCell<OculusVRGamepad>>;
 
@@ -22,8 +21,10 @@ pub struct OculusVRGamepad {
     connected: bool,
     capabilities: InputCapabilities,
     gamepad_id: u32,
+    identity: VRDeviceIdentity,
     display_id: u32,
     predicted_display_time: Cell<f64>,
+    clock: VRClockPtr,
 }
 
 unsafe impl Send for OculusVRGamepad {}
@@ -33,9 +34,12 @@ impl OculusVRGamepad {
     pub fn new(ovr: *mut ovr::ovrMobile,
                ovr_id: ovr::ovrDeviceID,
                ovr_type: ovr::ovrControllerType,
-               display_id: u32)
+               display_id: u32,
+               clock: VRClockPtr,
+               ids: &VRIdAllocatorPtr)
                -> Arc<RefCell<OculusVRGamepad>>
     {
+        let identity = VRDeviceIdentity::new("oculusvr", format!("controller-{}", ovr_id));
         let capabilities = InputCapabilities::from_ovr(ovr, ovr_type, ovr_id);
 
         let gamepad = Self {
@@ -44,9 +48,11 @@ impl OculusVRGamepad {
             ovr_type: ovr_type,
             connected: true,
             capabilities: capabilities.unwrap_or_default(),
-            gamepad_id: utils::new_id(),
+            gamepad_id: ids.id_for(&identity),
+            identity: identity,
             display_id: display_id,
             predicted_display_time: Cell::new(0.0),
+            clock: clock,
         };
 
         Arc::new(RefCell::new(gamepad))
@@ -54,6 +60,8 @@ impl OculusVRGamepad {
 
     pub fn refresh_available_gamepads(ovr: *mut ovr::ovrMobile,
                                       display_id: u32,
+                                      clock: &VRClockPtr,
+                                      ids: &VRIdAllocatorPtr,
                                       out: &mut Vec<OculusVRGamepadPtr>) {
         let mut index = 0;
         // Reset connected status
@@ -87,7 +95,7 @@ impl OculusVRGamepad {
             }
 
             // Create new Gamepad instance
-            let gamepad = OculusVRGamepad::new(ovr, caps.DeviceID, caps.Type, display_id);
+            let gamepad = OculusVRGamepad::new(ovr, caps.DeviceID, caps.Type, display_id, clock.clone(), ids);
             out.push(gamepad);
         }
     }
@@ -205,6 +213,7 @@ impl VRGamepad for OculusVRGamepad {
         VRGamepadData {
             display_id: self.display_id,
             name: name.into(),
+            identity: self.identity.clone(),
             hand: hand,
         }
     }
@@ -213,6 +222,7 @@ impl VRGamepad for OculusVRGamepad {
         let mut out = VRGamepadState::default();
 
         out.gamepad_id = self.gamepad_id;
+        out.timestamp = self.clock.now();
         out.connected = self.connected && !self.ovr.is_null();
 
         if out.connected {
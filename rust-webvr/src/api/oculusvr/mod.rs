@@ -5,7 +5,7 @@ mod display;
 mod gamepad;
 mod service;
 
-use {VRService, VRServiceCreator};
+use {VRClockPtr, VRIdAllocatorPtr, VRService, VRServiceCreator};
 
 pub struct OculusVRServiceCreator;
 
@@ -16,8 +16,8 @@ impl OculusVRServiceCreator {
 }
 
 impl VRServiceCreator for OculusVRServiceCreator {
-     fn new_service(&self) -> Box<VRService> {
-         Box::new(service::OculusVRService::new())
+     fn new_service(&self, clock: VRClockPtr, ids: VRIdAllocatorPtr) -> Box<VRService> {
+         Box::new(service::OculusVRService::new(clock, ids))
      }
 }
 
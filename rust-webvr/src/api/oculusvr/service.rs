@@ -1,7 +1,7 @@
 #![cfg(target_os="android")]
 #![cfg(feature = "oculusvr")]
 
-use {VRDisplay, VRService, VRDisplayPtr, VREvent, VRGamepadPtr};
+use {VRClockPtr, VRIdAllocatorPtr, VRDisplay, VRService, VRDisplayPtr, VREvent, VRGamepadPtr};
 use android_injected_glue as android;
 use android_injected_glue::ffi as ndk;
 use ovr_mobile_sys as ovr;
@@ -22,6 +22,8 @@ pub struct OculusVRService {
     pause_received: bool,
     surface_create_received: bool,
     surface_destroy_received: bool,
+    clock: VRClockPtr,
+    ids: VRIdAllocatorPtr,
 }
 
 unsafe impl Send for OculusVRService {}
@@ -62,10 +64,14 @@ impl VRService for OculusVRService {
         }
         events
     }
+
+    fn name(&self) -> &str {
+        "oculusvr"
+    }
 }
 
 impl OculusVRService {
-    pub fn new() -> OculusVRService {
+    pub fn new(clock: VRClockPtr, ids: VRIdAllocatorPtr) -> OculusVRService {
         OculusVRService {
             initialized: false,
             display: None,
@@ -75,6 +81,8 @@ impl OculusVRService {
             pause_received: false,
             surface_create_received: false,
             surface_destroy_received: false,
+            clock: clock,
+            ids: ids,
         }
     }
 
@@ -85,7 +93,7 @@ impl OculusVRService {
         if let Some(ref d) = self.display {
             Ok(d)
         } else {
-            self.display = Some(OculusVRDisplay::new(self.service_java.clone(), self.ovr_java.handle()));
+            self.display = Some(OculusVRDisplay::new(self.service_java.clone(), self.ovr_java.handle(), self.clock.clone(), self.ids.clone()));
             Ok(self.display.as_ref().unwrap())
         }
     }
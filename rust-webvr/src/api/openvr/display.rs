@@ -1,5 +1,5 @@
-use {VRDisplay, VRDisplayData, VRDisplayCapabilities, VREyeParameters, VRFrameData};
-use {VRFramebuffer, VRPose, VRStageParameters, VRFieldOfView, VRGamepadPtr, VRLayer};
+use {VRClockPtr, VRDeviceIdentity, VRDisplay, VRDisplayData, VRDisplayCapabilities, VREyeParameters, VRFrameData};
+use {VRFramebuffer, VRPose, VRStageParameters, VRFieldOfView, VRGamepadPtr, VRIdAllocatorPtr, VRLayer};
 use super::binding as openvr;
 use super::binding::ETrackedPropertyError::*;
 use super::binding::ETrackedDeviceProperty::*;
@@ -23,6 +23,7 @@ pub type OpenVRDisplayPtr = Arc<RefCell<OpenVRDisplay>>;
 
 pub struct OpenVRDisplay {
     display_id: u32,
+    identity: VRDeviceIdentity,
     lib: *const OpenVRLibrary,
     index: openvr::TrackedDeviceIndex_t,
     system: *mut openvr::VR_IVRSystem_FnTable,
@@ -32,6 +33,7 @@ pub struct OpenVRDisplay {
     left_bounds: openvr::VRTextureBounds_t,
     right_bounds: openvr::VRTextureBounds_t,
     gamepads: Vec<OpenVRGamepadPtr>,
+    clock: VRClockPtr,
 }
 
 unsafe impl Send for OpenVRDisplay {}
@@ -41,10 +43,14 @@ impl OpenVRDisplay {
     pub fn new(lib: *const OpenVRLibrary,
                index: openvr::TrackedDeviceIndex_t,
                system: *mut openvr::VR_IVRSystem_FnTable,
-               chaperone: *mut openvr::VR_IVRChaperone_FnTable) 
+               chaperone: *mut openvr::VR_IVRChaperone_FnTable,
+               clock: VRClockPtr,
+               ids: &VRIdAllocatorPtr)
                -> Arc<RefCell<OpenVRDisplay>> {
+        let identity = OpenVRDisplay::device_identity(system, index);
         Arc::new(RefCell::new(OpenVRDisplay {
-            display_id: utils::new_id(),
+            display_id: ids.id_for(&identity),
+            identity: identity,
             lib: lib,
             index: index,
             system: system,
@@ -58,6 +64,7 @@ impl OpenVRDisplay {
             left_bounds: unsafe { mem::zeroed() },
             right_bounds: unsafe { mem::zeroed() },
             gamepads: Vec::new(),
+            clock: clock,
         }))
     }
 }
@@ -82,6 +89,7 @@ impl VRDisplay for OpenVRDisplay {
         self.fetch_eye_parameters(&mut data.left_eye_parameters, &mut data.right_eye_parameters);
         self.fetch_stage_parameters(&mut data);
         data.display_id = self.display_id;
+        data.identity = self.identity.clone();
         data.display_name = format!("{} {}",
                             self.get_string_property(ETrackedDeviceProperty_Prop_ManufacturerName_String),
                             self.get_string_property(ETrackedDeviceProperty_Prop_ModelNumber_String));
@@ -191,15 +199,21 @@ impl OpenVRDisplay {
     }
 
     fn get_string_property(&self, name: openvr::ETrackedDeviceProperty) -> String {
+        OpenVRDisplay::string_property(self.system, self.index, name)
+    }
+
+    pub fn string_property(system: *mut openvr::VR_IVRSystem_FnTable,
+                           index: openvr::TrackedDeviceIndex_t,
+                           name: openvr::ETrackedDeviceProperty) -> String {
         let max_size = 256;
         let result = String::with_capacity(max_size);
         let mut error = ETrackedPropertyError_TrackedProp_Success;
         let size;
         unsafe {
-            size = (*self.system).GetStringTrackedDeviceProperty.unwrap()(self.index, name, 
-                                                                          result.as_ptr() as *mut i8, 
-                                                                          max_size as u32, 
-                                                                          &mut error)
+            size = (*system).GetStringTrackedDeviceProperty.unwrap()(index, name, 
+                                                                     result.as_ptr() as *mut i8, 
+                                                                     max_size as u32, 
+                                                                     &mut error)
         };
 
         if size > 0 && error as u32 == ETrackedPropertyError_TrackedProp_Success as u32 {
@@ -338,7 +352,7 @@ impl OpenVRDisplay {
         utils::inverse_matrix(&out.right_view_matrix, &mut view_matrix);
         out.right_view_matrix = view_matrix;
 
-        out.timestamp = utils::timestamp();
+        out.timestamp = self.clock.now();
     }
 
     fn fetch_projection_matrix(&self, eye: openvr::EVREye, near: f32, far: f32, out: &mut [f32; 16]) {
@@ -392,6 +406,18 @@ impl OpenVRDisplay {
         self.index
     }
 
+    // Builds a stable identity from the serial number of the tracked device.
+    // Falls back to the device index when the runtime doesn't report a serial.
+    pub fn device_identity(system: *mut openvr::VR_IVRSystem_FnTable,
+                           index: openvr::TrackedDeviceIndex_t) -> VRDeviceIdentity {
+        let serial = OpenVRDisplay::string_property(system, index, ETrackedDeviceProperty_Prop_SerialNumber_String);
+        if serial.is_empty() {
+            VRDeviceIdentity::new("openvr", format!("device-{}", index))
+        } else {
+            VRDeviceIdentity::new("openvr", serial)
+        }
+    }
+
     // Computing seconds to photons
     // More info: https://github.com/ValveSoftware/openvr/wiki/IVRSystem::GetDeviceToAbsoluteTrackingPose
     fn get_seconds_to_photons(&self) -> f32 {
@@ -3,19 +3,20 @@ use super::binding::ETrackingUniverseOrigin::*;
 use super::binding::EVRButtonId;
 use super::binding::EVRButtonId::*;
 use super::display::OpenVRDisplay;
-use rust_webvr_api::utils;
 use std::cell::RefCell;
 use std::mem;
 use std::sync::Arc;
-use {VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadState, VRGamepadButton};
+use {VRClockPtr, VRDeviceIdentity, VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadState, VRGamepadButton, VRIdAllocatorPtr};
 
 pub type OpenVRGamepadPtr = Arc<RefCell<OpenVRGamepad>>;
 
 pub struct OpenVRGamepad {
     gamepad_id: u32,
+    identity: VRDeviceIdentity,
     display_id: u32,
     index: openvr::TrackedDeviceIndex_t,
-    system: *mut openvr::VR_IVRSystem_FnTable
+    system: *mut openvr::VR_IVRSystem_FnTable,
+    clock: VRClockPtr,
 }
 
 unsafe impl Send for OpenVRGamepad {}
@@ -24,13 +25,18 @@ unsafe impl Sync for OpenVRGamepad {}
 impl OpenVRGamepad {
     pub fn new(index: openvr::TrackedDeviceIndex_t,
                system: *mut openvr::VR_IVRSystem_FnTable,
-               display_id: u32)
+               display_id: u32,
+               clock: VRClockPtr,
+               ids: &VRIdAllocatorPtr)
                -> Arc<RefCell<OpenVRGamepad>> {
+        let identity = OpenVRDisplay::device_identity(system, index);
         Arc::new(RefCell::new(OpenVRGamepad {
-            gamepad_id: utils::new_id(),
+            gamepad_id: ids.id_for(&identity),
+            identity: identity,
             display_id: display_id,
             index: index,
-            system: system
+            system: system,
+            clock: clock,
         }))
     }
 
@@ -48,6 +54,7 @@ impl VRGamepad for OpenVRGamepad {
         VRGamepadData {
             display_id: self.display_id,
             name: format!("OpenVR {:?}", self.index),
+            identity: self.identity.clone(),
             hand: VRGamepadHand::Unknown
         }
     }
@@ -56,6 +63,7 @@ impl VRGamepad for OpenVRGamepad {
         let mut state = VRGamepadState::default();
 
         state.gamepad_id = self.gamepad_id;
+        state.timestamp = self.clock.now();
         let mut controller: openvr::VRControllerState_t = unsafe { mem::uninitialized() };
         let mut tracked_poses: [openvr::TrackedDevicePose_t; openvr::k_unMaxTrackedDeviceCount as usize]
                               = unsafe { mem::uninitialized() };
@@ -5,7 +5,7 @@ mod library;
 mod gamepad;
 mod service;
 
-use {VRService, VRServiceCreator};
+use {VRClockPtr, VRIdAllocatorPtr, VRService, VRServiceCreator};
 
 pub struct OpenVRServiceCreator;
 
@@ -17,7 +17,7 @@ impl OpenVRServiceCreator {
 
 impl VRServiceCreator for OpenVRServiceCreator {
 
-     fn new_service(&self) -> Box<VRService> {
-         Box::new(service::OpenVRService::new())
+     fn new_service(&self, clock: VRClockPtr, ids: VRIdAllocatorPtr) -> Box<VRService> {
+         Box::new(service::OpenVRService::new(clock, ids))
      }
 }
\ No newline at end of file
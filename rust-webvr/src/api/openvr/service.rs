@@ -10,7 +10,7 @@ use super::library::OpenVRLibrary;
 use std::ffi::CString;
 use std::ptr;
 use std::mem;
-use {VRService, VRDisplay, VRDisplayPtr, VREvent, VRDisplayEvent, VRDisplayEventReason,
+use {VRClockPtr, VRIdAllocatorPtr, VRService, VRDisplay, VRDisplayPtr, VREvent, VRDisplayEvent, VRDisplayEventReason,
     VRGamepadEvent, VRGamepad, VRGamepadPtr};
 
 // OpenVR Service implementation
@@ -21,6 +21,8 @@ pub struct OpenVRService {
     gamepads: Vec<OpenVRGamepadPtr>,
     system: *mut openvr::VR_IVRSystem_FnTable,
     chaperone: *mut openvr::VR_IVRChaperone_FnTable,
+    clock: VRClockPtr,
+    ids: VRIdAllocatorPtr,
 }
 
 unsafe impl Send for OpenVRService {}
@@ -168,6 +170,10 @@ impl VRService for OpenVRService {
         
         result
     }
+
+    fn name(&self) -> &str {
+        "openvr"
+    }
 }
 
 impl Drop for OpenVRService {
@@ -184,14 +190,16 @@ impl Drop for OpenVRService {
 }
 
 impl OpenVRService {
-    pub fn new() -> OpenVRService {
+    pub fn new(clock: VRClockPtr, ids: VRIdAllocatorPtr) -> OpenVRService {
         OpenVRService {
             initialized: false,
             lib: None,
             displays: Vec::new(),
             gamepads: Vec::new(),
             system: ptr::null_mut(),
-            chaperone: ptr::null_mut()
+            chaperone: ptr::null_mut(),
+            clock: clock,
+            ids: ids,
         }
     }
 
@@ -213,7 +221,7 @@ impl OpenVRService {
             
             match device_class {
                 ETrackedDeviceClass_TrackedDeviceClass_HMD => {
-                    self.displays.push(OpenVRDisplay::new(self.lib.as_ref().unwrap(), i, self.system, self.chaperone));
+                    self.displays.push(OpenVRDisplay::new(self.lib.as_ref().unwrap(), i, self.system, self.chaperone, self.clock.clone(), &self.ids));
                 },
                 ETrackedDeviceClass_TrackedDeviceClass_Controller => {
                     gamepad_ids.push(i);
@@ -230,7 +238,7 @@ impl OpenVRService {
  
 
         for id in gamepad_ids {
-            self.gamepads.push(OpenVRGamepad::new(id, self.system, display_id));
+            self.gamepads.push(OpenVRGamepad::new(id, self.system, display_id, self.clock.clone(), &self.ids));
         }
 
         if let Some(ref d) = self.displays.first() {
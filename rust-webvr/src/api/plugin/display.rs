@@ -0,0 +1,142 @@
+use {VRDeviceIdentity, VRDisplay, VRDisplayData, VRFramebuffer, VRFramebufferAttributes, VRFrameData};
+use {VRFutureFrameData, VRGamepadPtr, VRIdAllocatorPtr, VRLayer};
+use rust_webvr_api::vr_plugin::*;
+use sparkle::gl::Gl;
+use std::cell::RefCell;
+use std::ptr;
+use std::sync::Arc;
+use super::service::PluginLibrary;
+
+pub type PluginVRDisplayPtr = Arc<RefCell<PluginVRDisplay>>;
+
+pub struct PluginVRDisplay {
+    // Keeps the plugin loaded while the display is alive
+    _library: PluginLibrary,
+    handle: VRPluginDisplay,
+    display_id: u32,
+    identity: VRDeviceIdentity,
+}
+
+unsafe impl Send for PluginVRDisplay {}
+unsafe impl Sync for PluginVRDisplay {}
+
+impl PluginVRDisplay {
+    pub fn new(library: PluginLibrary, handle: VRPluginDisplay, ids: &VRIdAllocatorPtr) -> PluginVRDisplayPtr {
+        let identity = plugin_data(&handle).identity;
+        let display_id = if identity.serial.is_empty() {
+            ids.new_id()
+        } else {
+            ids.id_for(&identity)
+        };
+        Arc::new(RefCell::new(PluginVRDisplay {
+            _library: library,
+            handle,
+            display_id,
+            identity,
+        }))
+    }
+
+    fn vtable(&self) -> &VRPluginDisplayVTable {
+        unsafe { &*self.handle.vtable }
+    }
+}
+
+impl Drop for PluginVRDisplay {
+    fn drop(&mut self) {
+        unsafe {
+            (self.vtable().release)(self.handle.obj);
+        }
+    }
+}
+
+impl VRDisplay for PluginVRDisplay {
+    fn id(&self) -> u32 {
+        self.display_id
+    }
+
+    fn data(&self) -> VRDisplayData {
+        let mut data = plugin_data(&self.handle);
+        data.display_id = self.display_id;
+        data.identity = self.identity.clone();
+        data
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String> {
+        // Plugin gamepads are exposed through the service
+        Ok(Vec::new())
+    }
+
+    fn immediate_frame_data(&self, near: f64, far: f64) -> VRFrameData {
+        let mut out = VRPluginFrameData::default();
+        unsafe { (self.vtable().immediate_frame_data)(self.handle.obj, near, far, &mut out) };
+        (&out).into()
+    }
+
+    fn synced_frame_data(&self, near: f64, far: f64) -> VRFrameData {
+        let mut out = VRPluginFrameData::default();
+        unsafe { (self.vtable().synced_frame_data)(self.handle.obj, near, far, &mut out) };
+        (&out).into()
+    }
+
+    fn future_frame_data(&mut self, near: f64, far: f64) -> VRFutureFrameData {
+        let mut out = VRPluginFrameData::default();
+        unsafe { (self.vtable().future_frame_data)(self.handle.obj, near, far, &mut out) };
+        VRFutureFrameData::resolved((&out).into())
+    }
+
+    fn reset_pose(&mut self) {
+        unsafe { (self.vtable().reset_pose)(self.handle.obj) }
+    }
+
+    fn sync_poses(&mut self) {
+        unsafe { (self.vtable().sync_poses)(self.handle.obj) }
+    }
+
+    fn bind_framebuffer(&mut self, index: u32) {
+        unsafe { (self.vtable().bind_framebuffer)(self.handle.obj, index) }
+    }
+
+    fn get_framebuffers(&self) -> Vec<VRFramebuffer> {
+        let vtable = self.vtable();
+        unsafe {
+            let count = (vtable.framebuffer_count)(self.handle.obj);
+            (0..count).map(|index| {
+                let mut out = VRPluginFramebuffer::default();
+                (vtable.framebuffer_at)(self.handle.obj, index, &mut out);
+                (&out).into()
+            }).collect()
+        }
+    }
+
+    fn render_layer(&mut self, layer: &VRLayer) {
+        let layer: VRPluginLayer = layer.into();
+        unsafe { (self.vtable().render_layer)(self.handle.obj, &layer) }
+    }
+
+    fn submit_frame(&mut self) {
+        unsafe { (self.vtable().submit_frame)(self.handle.obj) }
+    }
+
+    fn submit_layer(&mut self, _gl: &Gl, layer: &VRLayer) {
+        let layer: VRPluginLayer = layer.into();
+        unsafe { (self.vtable().submit_layer)(self.handle.obj, &layer) }
+    }
+
+    fn start_present(&mut self, attributes: Option<VRFramebufferAttributes>) {
+        let attributes: Option<VRPluginFramebufferAttributes> = attributes.as_ref().map(|a| a.into());
+        let attributes_ptr = attributes.as_ref().map_or(ptr::null(), |a| a as *const _);
+        unsafe { (self.vtable().start_present)(self.handle.obj, attributes_ptr) }
+    }
+
+    fn stop_present(&mut self) {
+        unsafe { (self.vtable().stop_present)(self.handle.obj) }
+    }
+}
+
+fn plugin_data(handle: &VRPluginDisplay) -> VRDisplayData {
+    let mut out = VRPluginDisplayData::default();
+    unsafe {
+        ((*handle.vtable).data)(handle.obj, &mut out);
+        out.to_display_data()
+    }
+}
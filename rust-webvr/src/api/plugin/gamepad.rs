@@ -0,0 +1,79 @@
+use {VRDeviceIdentity, VRGamepad, VRGamepadData, VRGamepadState, VRIdAllocatorPtr};
+use rust_webvr_api::vr_plugin::*;
+use std::cell::RefCell;
+use std::sync::Arc;
+use super::service::{PluginDisplayIds, PluginLibrary};
+
+pub type PluginVRGamepadPtr = Arc<RefCell<PluginVRGamepad>>;
+
+pub struct PluginVRGamepad {
+    // Keeps the plugin loaded while the gamepad is alive
+    _library: PluginLibrary,
+    handle: VRPluginGamepad,
+    gamepad_id: u32,
+    identity: VRDeviceIdentity,
+    display_ids: PluginDisplayIds,
+}
+
+impl PluginVRGamepad {
+    pub fn new(library: PluginLibrary,
+               handle: VRPluginGamepad,
+               display_ids: PluginDisplayIds,
+               ids: &VRIdAllocatorPtr)
+               -> PluginVRGamepadPtr {
+        let identity = plugin_data(&handle).identity;
+        let gamepad_id = if identity.serial.is_empty() {
+            ids.new_id()
+        } else {
+            ids.id_for(&identity)
+        };
+        Arc::new(RefCell::new(PluginVRGamepad {
+            _library: library,
+            handle,
+            gamepad_id,
+            identity,
+            display_ids,
+        }))
+    }
+}
+
+impl Drop for PluginVRGamepad {
+    fn drop(&mut self) {
+        unsafe {
+            ((*self.handle.vtable).release)(self.handle.obj);
+        }
+    }
+}
+
+impl VRGamepad for PluginVRGamepad {
+    fn id(&self) -> u32 {
+        self.gamepad_id
+    }
+
+    fn data(&self) -> VRGamepadData {
+        let mut data = plugin_data(&self.handle);
+        if let Some(display_id) = self.display_ids.lock().unwrap().get(&data.display_id) {
+            data.display_id = *display_id;
+        }
+        data.identity = self.identity.clone();
+        data
+    }
+
+    fn state(&self) -> VRGamepadState {
+        let mut out = VRPluginGamepadState::default();
+        let mut state = unsafe {
+            ((*self.handle.vtable).state)(self.handle.obj, &mut out);
+            out.to_gamepad_state()
+        };
+        state.gamepad_id = self.gamepad_id;
+        state
+    }
+}
+
+fn plugin_data(handle: &VRPluginGamepad) -> VRGamepadData {
+    let mut out = VRPluginGamepadData::default();
+    unsafe {
+        ((*handle.vtable).data)(handle.obj, &mut out);
+        out.to_gamepad_data()
+    }
+}
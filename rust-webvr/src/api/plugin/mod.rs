@@ -0,0 +1,61 @@
+mod display;
+mod gamepad;
+mod service;
+
+use {VRClockPtr, VRIdAllocatorPtr, VRService, VRServiceCreator};
+use plugin_libloading as lib;
+use rust_webvr_api::vr_plugin::{VRPluginClock, VRPluginCreator, VRPluginEntry, VR_PLUGIN_ABI_VERSION, VR_PLUGIN_ENTRY_POINT};
+use rust_webvr_api::vr_plugin::string_from_ptr;
+use std::path::Path;
+use std::sync::Arc;
+
+// Creates services from a shared library implementing the plugin ABI
+pub struct PluginServiceCreator {
+    library: service::PluginLibrary,
+    creator: *const VRPluginCreator,
+}
+
+impl PluginServiceCreator {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Box<dyn VRServiceCreator>, String> {
+        let path = path.as_ref();
+        let library = lib::Library::new(path).map_err(|e| format!("Error loading VR plugin {}: {}", path.display(), e))?;
+        let creator = unsafe {
+            let entry = library.get::<VRPluginEntry>(VR_PLUGIN_ENTRY_POINT)
+                               .map_err(|e| format!("Invalid VR plugin {}: {}", path.display(), e))?;
+            entry()
+        };
+        if creator.is_null() {
+            return Err(format!("VR plugin {} didn't return a service creator", path.display()));
+        }
+        let creator = PluginServiceCreator::new(Some(Arc::new(library)), creator)
+            .map_err(|e| format!("VR plugin {} {}", path.display(), e))?;
+        Ok(Box::new(creator))
+    }
+
+    // Creates services from a plugin linked into the executable
+    pub fn from_creator(creator: &'static VRPluginCreator) -> Result<Box<dyn VRServiceCreator>, String> {
+        let creator = PluginServiceCreator::new(None, creator).map_err(|e| format!("VR plugin {}", e))?;
+        Ok(Box::new(creator))
+    }
+
+    fn new(library: service::PluginLibrary, creator: *const VRPluginCreator) -> Result<PluginServiceCreator, String> {
+        let abi_version = unsafe { (*creator).abi_version };
+        if abi_version != VR_PLUGIN_ABI_VERSION {
+            return Err(format!("uses ABI version {}, expected {}", abi_version, VR_PLUGIN_ABI_VERSION));
+        }
+
+        Ok(PluginServiceCreator {
+            library,
+            creator,
+        })
+    }
+}
+
+impl VRServiceCreator for PluginServiceCreator {
+    fn new_service(&self, clock: VRClockPtr, ids: VRIdAllocatorPtr) -> Box<dyn VRService> {
+        let (name, handle) = unsafe {
+            (string_from_ptr((*self.creator).name), ((*self.creator).new_service)(VRPluginClock::new(clock)))
+        };
+        Box::new(service::PluginVRService::new(self.library.clone(), name, handle, ids))
+    }
+}
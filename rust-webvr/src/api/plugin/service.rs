@@ -0,0 +1,215 @@
+use {VRDisplay, VRDisplayEvent, VRDisplayPtr, VREvent, VRGamepad, VRGamepadEvent, VRGamepadPtr};
+use {VRIdAllocatorPtr, VRService};
+use plugin_libloading::Library;
+use rust_webvr_api::vr_plugin::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use super::display::{PluginVRDisplay, PluginVRDisplayPtr};
+use super::gamepad::{PluginVRGamepad, PluginVRGamepadPtr};
+
+// Maps the display ids used by the plugin to the ids assigned by the VRServiceManager
+pub type PluginDisplayIds = Arc<Mutex<HashMap<u32, u32>>>;
+
+// Keeps the plugin loaded while its devices are alive, None for plugins linked into the executable
+pub type PluginLibrary = Option<Arc<Library>>;
+
+pub struct PluginVRService {
+    library: PluginLibrary,
+    handle: VRPluginService,
+    name: String,
+    ids: VRIdAllocatorPtr,
+    display_ids: PluginDisplayIds,
+    // Keyed by plugin id
+    displays: RefCell<HashMap<u32, PluginVRDisplayPtr>>,
+    gamepads: RefCell<HashMap<u32, PluginVRGamepadPtr>>,
+}
+
+unsafe impl Send for PluginVRService {}
+
+impl VRService for PluginVRService {
+    fn initialize(&mut self) -> Result<(), String> {
+        if unsafe { (self.vtable().initialize)(self.handle.obj) } {
+            Ok(())
+        } else {
+            Err(self.last_error())
+        }
+    }
+
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, String> {
+        let displays = self.enumerate_displays()?;
+        Ok(displays.into_iter().map(|d| d as VRDisplayPtr).collect())
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String> {
+        let gamepads = self.enumerate_gamepads()?;
+        Ok(gamepads.into_iter().map(|g| g as VRGamepadPtr).collect())
+    }
+
+    fn is_available(&self) -> bool {
+        unsafe { (self.vtable().is_available)(self.handle.obj) }
+    }
+
+    fn poll_events(&self) -> Vec<VREvent> {
+        let vtable = self.vtable();
+        let events: Vec<VRPluginEvent> = unsafe {
+            let count = (vtable.poll_events)(self.handle.obj);
+            (0..count).map(|index| (vtable.event_at)(self.handle.obj, index)).collect()
+        };
+        events.iter().filter_map(|event| self.convert_event(event)).collect()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Drop for PluginVRService {
+    fn drop(&mut self) {
+        self.gamepads.borrow_mut().clear();
+        self.displays.borrow_mut().clear();
+        unsafe {
+            (self.vtable().destroy)(self.handle.obj);
+        }
+    }
+}
+
+impl PluginVRService {
+    pub fn new(library: PluginLibrary, name: String, handle: VRPluginService, ids: VRIdAllocatorPtr) -> PluginVRService {
+        PluginVRService {
+            library,
+            handle,
+            name,
+            ids,
+            display_ids: Arc::new(Mutex::new(HashMap::new())),
+            displays: RefCell::new(HashMap::new()),
+            gamepads: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn vtable(&self) -> &VRPluginServiceVTable {
+        unsafe { &*self.handle.vtable }
+    }
+
+    fn last_error(&self) -> String {
+        unsafe { string_from_ptr((self.vtable().last_error)(self.handle.obj)) }
+    }
+
+    fn enumerate_displays(&self) -> Result<Vec<PluginVRDisplayPtr>, String> {
+        let vtable = self.vtable();
+        let count = unsafe { (vtable.fetch_displays)(self.handle.obj) };
+        if count < 0 {
+            return Err(self.last_error());
+        }
+
+        let mut displays = self.displays.borrow_mut();
+        let mut plugin_ids = HashSet::new();
+        let mut result = Vec::new();
+        for index in 0..count as u32 {
+            let handle = unsafe { (vtable.display_at)(self.handle.obj, index) };
+            if handle.obj.is_null() {
+                continue;
+            }
+            let plugin_id = unsafe { ((*handle.vtable).id)(handle.obj) };
+            plugin_ids.insert(plugin_id);
+            if let Some(display) = displays.get(&plugin_id) {
+                unsafe { ((*handle.vtable).release)(handle.obj) };
+                result.push(display.clone());
+                continue;
+            }
+            let display = PluginVRDisplay::new(self.library.clone(), handle, &self.ids);
+            self.display_ids.lock().unwrap().insert(plugin_id, display.borrow().id());
+            displays.insert(plugin_id, display.clone());
+            result.push(display);
+        }
+
+        // Forget the displays that the plugin doesn't report anymore
+        displays.retain(|plugin_id, _| plugin_ids.contains(plugin_id));
+        self.display_ids.lock().unwrap().retain(|plugin_id, _| plugin_ids.contains(plugin_id));
+        Ok(result)
+    }
+
+    fn enumerate_gamepads(&self) -> Result<Vec<PluginVRGamepadPtr>, String> {
+        let vtable = self.vtable();
+        let count = unsafe { (vtable.fetch_gamepads)(self.handle.obj) };
+        if count < 0 {
+            return Err(self.last_error());
+        }
+
+        let mut gamepads = self.gamepads.borrow_mut();
+        let mut plugin_ids = HashSet::new();
+        let mut result = Vec::new();
+        for index in 0..count as u32 {
+            let handle = unsafe { (vtable.gamepad_at)(self.handle.obj, index) };
+            if handle.obj.is_null() {
+                continue;
+            }
+            let plugin_id = unsafe { ((*handle.vtable).id)(handle.obj) };
+            plugin_ids.insert(plugin_id);
+            if let Some(gamepad) = gamepads.get(&plugin_id) {
+                unsafe { ((*handle.vtable).release)(handle.obj) };
+                result.push(gamepad.clone());
+                continue;
+            }
+            let gamepad = PluginVRGamepad::new(self.library.clone(), handle, self.display_ids.clone(), &self.ids);
+            gamepads.insert(plugin_id, gamepad.clone());
+            result.push(gamepad);
+        }
+
+        gamepads.retain(|plugin_id, _| plugin_ids.contains(plugin_id));
+        Ok(result)
+    }
+
+    // Finds a display by its plugin id, enumerating the displays again if it's a new one
+    fn display(&self, plugin_id: u32) -> Option<PluginVRDisplayPtr> {
+        if !self.displays.borrow().contains_key(&plugin_id) {
+            let _ = self.enumerate_displays();
+        }
+        self.displays.borrow().get(&plugin_id).cloned()
+    }
+
+    fn gamepad(&self, plugin_id: u32) -> Option<PluginVRGamepadPtr> {
+        if !self.gamepads.borrow().contains_key(&plugin_id) {
+            let _ = self.enumerate_gamepads();
+        }
+        self.gamepads.borrow().get(&plugin_id).cloned()
+    }
+
+    fn convert_event(&self, event: &VRPluginEvent) -> Option<VREvent> {
+        let display_id = |plugin_id| self.display_ids.lock().unwrap().get(&plugin_id).cloned();
+        let data = |plugin_id| self.display(plugin_id).map(|display| display.borrow().data());
+
+        let event = match event.kind {
+            VR_PLUGIN_EVENT_DISPLAY_CONNECT => VRDisplayEvent::Connect(data(event.id)?).into(),
+            VR_PLUGIN_EVENT_DISPLAY_DISCONNECT => {
+                self.displays.borrow_mut().remove(&event.id);
+                let display_id = self.display_ids.lock().unwrap().remove(&event.id)?;
+                VRDisplayEvent::Disconnect(display_id).into()
+            }
+            VR_PLUGIN_EVENT_DISPLAY_ACTIVATE => VRDisplayEvent::Activate(data(event.id)?, event.reason()).into(),
+            VR_PLUGIN_EVENT_DISPLAY_DEACTIVATE => VRDisplayEvent::Deactivate(data(event.id)?, event.reason()).into(),
+            VR_PLUGIN_EVENT_DISPLAY_CHANGE => VRDisplayEvent::Change(data(event.id)?).into(),
+            VR_PLUGIN_EVENT_DISPLAY_BLUR => VRDisplayEvent::Blur(data(event.id)?).into(),
+            VR_PLUGIN_EVENT_DISPLAY_FOCUS => VRDisplayEvent::Focus(data(event.id)?).into(),
+            VR_PLUGIN_EVENT_DISPLAY_PRESENT_CHANGE => VRDisplayEvent::PresentChange(data(event.id)?, event.presenting).into(),
+            VR_PLUGIN_EVENT_DISPLAY_PAUSE => VRDisplayEvent::Pause(display_id(event.id)?).into(),
+            VR_PLUGIN_EVENT_DISPLAY_RESUME => VRDisplayEvent::Resume(display_id(event.id)?).into(),
+            VR_PLUGIN_EVENT_DISPLAY_EXIT => VRDisplayEvent::Exit(display_id(event.id)?).into(),
+            VR_PLUGIN_EVENT_GAMEPAD_CONNECT => {
+                let gamepad = self.gamepad(event.id)?;
+                let gamepad = gamepad.borrow();
+                VRGamepadEvent::Connect(gamepad.data(), gamepad.state()).into()
+            }
+            VR_PLUGIN_EVENT_GAMEPAD_DISCONNECT => {
+                let gamepad = self.gamepads.borrow_mut().remove(&event.id)?;
+                let gamepad_id = gamepad.borrow().id();
+                VRGamepadEvent::Disconnect(gamepad_id).into()
+            }
+            kind => {
+                warn!("Unknown VR plugin event: {}", kind);
+                return None;
+            }
+        };
+        Some(event)
+    }
+}
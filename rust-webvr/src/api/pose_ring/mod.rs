@@ -0,0 +1,58 @@
+use VRFrameData;
+use rust_webvr_api::vr_frame_layout::VRFrameLayoutData;
+use std::mem;
+use std::sync::atomic::AtomicU64;
+
+mod reader;
+mod shmem;
+mod writer;
+
+pub use self::reader::VRPoseRingReader;
+pub use self::writer::VRPoseRingWriter;
+
+// Shared memory layout: a VRPoseRingHeader followed by `capacity` VRPoseRingSlots.
+// Frame n goes to slot n % capacity. Each slot is protected by a seqlock: its sequence
+// is odd while the slot is written, and 2 * (n + 1) once frame n is written.
+const VR_POSE_RING_MAGIC: u32 = 0x5652_5052; // "VRPR"
+const VR_POSE_RING_VERSION: u32 = 1;
+
+#[repr(C)]
+struct VRPoseRingHeader {
+    magic: u32,
+    version: u32,
+    capacity: u32,
+    padding: u32,
+    // Number of frames written so far
+    written: AtomicU64,
+}
+
+#[repr(C)]
+struct VRPoseRingSlot {
+    sequence: AtomicU64,
+    display_id: u32,
+    padding: u32,
+    data: VRFrameLayoutData,
+}
+
+fn ring_size(capacity: u32) -> usize {
+    mem::size_of::<VRPoseRingHeader>() + capacity as usize * mem::size_of::<VRPoseRingSlot>()
+}
+
+// A frame read from a pose ring
+#[derive(Debug, Clone)]
+pub struct VRPoseRingFrame {
+    // Number of the frame since the writer was created
+    pub frame: u64,
+    pub display_id: u32,
+    pub data: VRFrameData,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VRPoseRingError {
+    // The frame wasn't published yet
+    NotWritten,
+    // The slot of the frame was reused by a newer frame
+    Overwritten,
+    // The frame was written while it was read
+    Torn,
+}
@@ -0,0 +1,112 @@
+use VRFrameData;
+use super::{VRPoseRingError, VRPoseRingFrame, VRPoseRingHeader, VRPoseRingSlot, VR_POSE_RING_MAGIC, VR_POSE_RING_VERSION, ring_size};
+use super::shmem::VRSharedMemory;
+use std::ptr;
+use std::sync::atomic::{fence, Ordering};
+
+// Attempts to read the latest frame before giving up, when the writer keeps overwriting it
+const MAX_ATTEMPTS: u32 = 16;
+
+// Reads the frames published by a VRPoseRingWriter without ever blocking it
+pub struct VRPoseRingReader {
+    memory: VRSharedMemory,
+    capacity: u32,
+}
+
+impl VRPoseRingReader {
+    pub fn open(name: &str) -> Result<VRPoseRingReader, String> {
+        let memory = VRSharedMemory::open(name)?;
+        if memory.is_empty() {
+            return Err(format!("Pose ring {} is still being created", name));
+        }
+        if memory.len() < ring_size(0) {
+            return Err(format!("{} is not a pose ring", name));
+        }
+        let (magic, version, capacity) = unsafe {
+            let header = &*(memory.as_ptr() as *const VRPoseRingHeader);
+            (header.magic, header.version, header.capacity)
+        };
+        if magic != VR_POSE_RING_MAGIC {
+            return Err(format!("{} is not a pose ring", name));
+        }
+        if version != VR_POSE_RING_VERSION {
+            return Err(format!("Unsupported pose ring version: {}", version));
+        }
+        if memory.len() < ring_size(capacity) {
+            return Err(format!("Pose ring {} is truncated", name));
+        }
+        Ok(VRPoseRingReader {
+            memory,
+            capacity,
+        })
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    // Number of frames published so far
+    pub fn written(&self) -> u64 {
+        self.header().written.load(Ordering::Acquire)
+    }
+
+    // The most recent frame, None before the first one is published
+    pub fn latest(&self) -> Option<VRPoseRingFrame> {
+        for _ in 0..MAX_ATTEMPTS {
+            let written = self.written();
+            if written == 0 {
+                return None;
+            }
+            // A torn or overwritten read means that a newer frame is there
+            if let Ok(frame) = self.read(written - 1) {
+                return Some(frame);
+            }
+        }
+        None
+    }
+
+    // Reads a given frame, as long as its slot wasn't reused
+    pub fn read(&self, frame: u64) -> Result<VRPoseRingFrame, VRPoseRingError> {
+        let expected = frame * 2 + 2;
+        unsafe {
+            let slot = self.slot(frame);
+            let before = (*slot).sequence.load(Ordering::Acquire);
+            if before < expected - 1 {
+                return Err(VRPoseRingError::NotWritten);
+            }
+            if before > expected {
+                return Err(VRPoseRingError::Overwritten);
+            }
+            if before != expected {
+                return Err(VRPoseRingError::Torn);
+            }
+            let display_id = ptr::read_volatile(&(*slot).display_id);
+            let data = ptr::read_volatile(&(*slot).data);
+            fence(Ordering::Acquire);
+            if (*slot).sequence.load(Ordering::Relaxed) != before {
+                return Err(VRPoseRingError::Torn);
+            }
+            Ok(VRPoseRingFrame {
+                frame,
+                display_id,
+                data: VRFrameData::from(&data),
+            })
+        }
+    }
+
+    // The frames published after the given one that are still in the ring, oldest first
+    pub fn frames_since(&self, frame: u64) -> Vec<VRPoseRingFrame> {
+        let written = self.written();
+        let first = (frame + 1).max(written.saturating_sub(self.capacity as u64));
+        (first..written).filter_map(|frame| self.read(frame).ok()).collect()
+    }
+
+    fn header(&self) -> &VRPoseRingHeader {
+        unsafe { &*(self.memory.as_ptr() as *const VRPoseRingHeader) }
+    }
+
+    unsafe fn slot(&self, frame: u64) -> *const VRPoseRingSlot {
+        let slots = self.memory.as_ptr().add(ring_size(0)) as *const VRPoseRingSlot;
+        slots.add((frame % self.capacity as u64) as usize)
+    }
+}
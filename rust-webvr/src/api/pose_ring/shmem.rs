@@ -0,0 +1,141 @@
+use std::ffi::CString;
+
+// A named shared memory mapping, unlinked by its creator when dropped
+pub struct VRSharedMemory {
+    ptr: *mut u8,
+    len: usize,
+    name: CString,
+    owner: bool,
+}
+
+unsafe impl Send for VRSharedMemory {}
+unsafe impl Sync for VRSharedMemory {}
+
+// POSIX shared memory names start with a slash
+fn shm_name(name: &str) -> Result<CString, String> {
+    let name = if name.starts_with('/') { name.to_owned() } else { format!("/{}", name) };
+    CString::new(name).map_err(|e| e.to_string())
+}
+
+#[cfg(unix)]
+impl VRSharedMemory {
+    // Fails when a shared memory with the same name already exists
+    pub fn create(name: &str, len: usize) -> Result<VRSharedMemory, String> {
+        use libc;
+        use std::io;
+
+        let name = shm_name(name)?;
+        unsafe {
+            let fd = libc::shm_open(name.as_ptr(), libc::O_CREAT | libc::O_EXCL | libc::O_RDWR, 0o600);
+            if fd < 0 {
+                return Err(format!("Error creating shared memory {:?}: {}", name, io::Error::last_os_error()));
+            }
+            if libc::ftruncate(fd, len as libc::off_t) != 0 {
+                let error = io::Error::last_os_error();
+                libc::close(fd);
+                libc::shm_unlink(name.as_ptr());
+                return Err(format!("Error resizing shared memory {:?}: {}", name, error));
+            }
+            let result = VRSharedMemory::map(fd, len, name.clone(), true);
+            if result.is_err() {
+                libc::shm_unlink(name.as_ptr());
+            }
+            result
+        }
+    }
+
+    pub fn open(name: &str) -> Result<VRSharedMemory, String> {
+        use libc;
+        use std::io;
+        use std::mem;
+
+        let name = shm_name(name)?;
+        unsafe {
+            let fd = libc::shm_open(name.as_ptr(), libc::O_RDWR, 0);
+            if fd < 0 {
+                return Err(format!("Error opening shared memory {:?}: {}", name, io::Error::last_os_error()));
+            }
+            let mut stat: libc::stat = mem::zeroed();
+            if libc::fstat(fd, &mut stat) != 0 {
+                let error = io::Error::last_os_error();
+                libc::close(fd);
+                return Err(format!("Error reading the size of shared memory {:?}: {}", name, error));
+            }
+            VRSharedMemory::map(fd, stat.st_size as usize, name, false)
+        }
+    }
+
+    // Takes ownership of the file descriptor, which isn't needed once mapped
+    unsafe fn map(fd: libc::c_int, len: usize, name: CString, owner: bool) -> Result<VRSharedMemory, String> {
+        use libc;
+        use std::io;
+        use std::ptr;
+
+        // Nothing to map while the creator didn't resize it yet
+        if len == 0 {
+            libc::close(fd);
+            return Ok(VRSharedMemory {
+                ptr: ptr::null_mut(),
+                len,
+                name,
+                owner,
+            });
+        }
+        let ptr = libc::mmap(ptr::null_mut(), len, libc::PROT_READ | libc::PROT_WRITE, libc::MAP_SHARED, fd, 0);
+        let error = io::Error::last_os_error();
+        libc::close(fd);
+        if ptr == libc::MAP_FAILED {
+            return Err(format!("Error mapping shared memory {:?}: {}", name, error));
+        }
+        Ok(VRSharedMemory {
+            ptr: ptr as *mut u8,
+            len,
+            name,
+            owner,
+        })
+    }
+}
+
+#[cfg(not(unix))]
+impl VRSharedMemory {
+    pub fn create(_name: &str, _len: usize) -> Result<VRSharedMemory, String> {
+        Err("Shared memory pose rings are not supported on this platform".into())
+    }
+
+    pub fn open(_name: &str) -> Result<VRSharedMemory, String> {
+        Err("Shared memory pose rings are not supported on this platform".into())
+    }
+}
+
+impl VRSharedMemory {
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Drop for VRSharedMemory {
+    #[cfg(unix)]
+    fn drop(&mut self) {
+        use libc;
+        unsafe {
+            if !self.is_empty() {
+                libc::munmap(self.ptr as *mut libc::c_void, self.len);
+            }
+            // Readers keep their mapping, new ones can't open it anymore
+            if self.owner {
+                libc::shm_unlink(self.name.as_ptr());
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn drop(&mut self) {}
+}
@@ -0,0 +1,62 @@
+use VRFrameData;
+use rust_webvr_api::vr_frame_layout::VRFrameLayoutData;
+use super::{VRPoseRingHeader, VRPoseRingSlot, VR_POSE_RING_MAGIC, VR_POSE_RING_VERSION, ring_size};
+use super::shmem::VRSharedMemory;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+
+// Publishes the frame data of the displays to a shared memory ring, read by any number
+// of VRPoseRingReaders in this process or others. There must be a single writer per ring,
+// usually the render thread of the display process.
+pub struct VRPoseRingWriter {
+    memory: VRSharedMemory,
+    capacity: u32,
+    // Number of the next frame
+    next: u64,
+}
+
+impl VRPoseRingWriter {
+    // Creates the shared memory ring, which goes away with the writer.
+    // The capacity is the number of recent frames kept for the readers.
+    pub fn create(name: &str, capacity: u32) -> Result<VRPoseRingWriter, String> {
+        if capacity == 0 {
+            return Err("The capacity of a pose ring can't be 0".into());
+        }
+        let memory = VRSharedMemory::create(name, ring_size(capacity))?;
+        // The new shared memory is zeroed, i.e. no slot is written yet
+        unsafe {
+            let header = memory.as_ptr() as *mut VRPoseRingHeader;
+            ptr::write(header, VRPoseRingHeader {
+                magic: VR_POSE_RING_MAGIC,
+                version: VR_POSE_RING_VERSION,
+                capacity,
+                padding: 0,
+                written: AtomicU64::new(0),
+            });
+        }
+        Ok(VRPoseRingWriter {
+            memory,
+            capacity,
+            next: 0,
+        })
+    }
+
+    // Returns the number of the published frame
+    pub fn publish(&mut self, display_id: u32, data: &VRFrameData) -> u64 {
+        let frame = self.next;
+        self.next += 1;
+        unsafe {
+            let header = &*(self.memory.as_ptr() as *const VRPoseRingHeader);
+            let slots = self.memory.as_ptr().add(ring_size(0)) as *mut VRPoseRingSlot;
+            let slot = slots.add((frame % self.capacity as u64) as usize);
+
+            (*slot).sequence.store(frame * 2 + 1, Ordering::Relaxed);
+            fence(Ordering::Release);
+            ptr::write_volatile(&mut (*slot).display_id, display_id);
+            ptr::write_volatile(&mut (*slot).data, VRFrameLayoutData::from(data));
+            (*slot).sequence.store(frame * 2 + 2, Ordering::Release);
+            header.written.store(frame + 1, Ordering::Release);
+        }
+        frame
+    }
+}
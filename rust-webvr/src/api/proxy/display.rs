@@ -0,0 +1,153 @@
+use {VRDisplay, VRDisplayData, VRFramebuffer, VRFramebufferAttributes, VRFrameData, VRFutureFrameData, VRGamepadPtr, VRLayer};
+use super::{ProxyVRIdsPtr, VRProxyDisplayRequest, VRProxyRequest, request};
+use super::gamepad::{ProxyVRGamepadPtr, proxy_gamepads};
+use ipc_channel::ipc::IpcSender;
+use sparkle::gl::Gl;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub type ProxyVRDisplayPtr = Arc<RefCell<ProxyVRDisplay>>;
+
+// Forwards the calls to a display of a VRProxyServer, usually in another process
+pub struct ProxyVRDisplay {
+    display_id: u32,
+    // Id of the display in the served service, used by the requests
+    served_id: u32,
+    sender: IpcSender<VRProxyRequest>,
+    ids: ProxyVRIdsPtr,
+    // Returned, disconnected, when the server went away
+    data: RefCell<VRDisplayData>,
+    gamepads: HashMap<u32, ProxyVRGamepadPtr>,
+}
+
+unsafe impl Send for ProxyVRDisplay {}
+unsafe impl Sync for ProxyVRDisplay {}
+
+impl ProxyVRDisplay {
+    // Takes the served data, before its id is mapped
+    pub fn new(mut data: VRDisplayData, sender: IpcSender<VRProxyRequest>, ids: ProxyVRIdsPtr) -> ProxyVRDisplayPtr {
+        let served_id = data.display_id;
+        ids.lock().unwrap().map_display_data(&mut data);
+        Arc::new(RefCell::new(ProxyVRDisplay {
+            display_id: data.display_id,
+            served_id,
+            sender,
+            ids,
+            data: RefCell::new(data),
+            gamepads: HashMap::new(),
+        }))
+    }
+
+    // Takes the served data, before its id is mapped
+    pub fn set_data(&self, mut data: VRDisplayData) {
+        data.display_id = self.display_id;
+        *self.data.borrow_mut() = data;
+    }
+
+    fn request<T, F>(&self, make_request: F) -> Result<T, String>
+        where T: ::serde::Serialize + ::serde::de::DeserializeOwned,
+              F: FnOnce(IpcSender<T>) -> VRProxyDisplayRequest {
+        let served_id = self.served_id;
+        request(&self.sender, |sender| VRProxyRequest::Display(served_id, make_request(sender)))
+    }
+
+    // For the requests without answer
+    fn send(&self, request: VRProxyDisplayRequest) {
+        if let Err(e) = self.sender.send(VRProxyRequest::Display(self.served_id, request)) {
+            warn!("VR proxy server went away: {:?}", e);
+        }
+    }
+
+    fn frame_data_or_default(&self, result: Result<VRFrameData, String>) -> VRFrameData {
+        result.unwrap_or_else(|msg| {
+            error!("Error reading the frame data of proxy display {}: {}", self.display_id, msg);
+            VRFrameData::default()
+        })
+    }
+}
+
+impl VRDisplay for ProxyVRDisplay {
+    fn id(&self) -> u32 {
+        self.display_id
+    }
+
+    fn data(&self) -> VRDisplayData {
+        match self.request(VRProxyDisplayRequest::Data) {
+            Ok(data) => self.set_data(data),
+            Err(msg) => {
+                warn!("Error reading the data of proxy display {}: {}", self.display_id, msg);
+                self.data.borrow_mut().connected = false;
+            }
+        }
+        self.data.borrow().clone()
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String> {
+        let gamepads = self.request(VRProxyDisplayRequest::FetchGamepads)??;
+        Ok(proxy_gamepads(gamepads, &mut self.gamepads, &self.sender, &self.ids))
+    }
+
+    fn immediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let result = self.request(|sender| VRProxyDisplayRequest::ImmediateFrameData(near_z, far_z, sender));
+        self.frame_data_or_default(result)
+    }
+
+    fn future_frame_data(&mut self, near_z: f64, far_z: f64) -> VRFutureFrameData {
+        let (resolver, result) = VRFutureFrameData::blocked();
+        match self.sender.send(VRProxyRequest::Display(self.served_id, VRProxyDisplayRequest::FutureFrameData(near_z, far_z, resolver))) {
+            Ok(()) => result,
+            Err(e) => {
+                error!("VR proxy server went away: {:?}", e);
+                VRFutureFrameData::resolved(VRFrameData::default())
+            }
+        }
+    }
+
+    fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let result = self.request(|sender| VRProxyDisplayRequest::SyncedFrameData(near_z, far_z, sender));
+        self.frame_data_or_default(result)
+    }
+
+    fn reset_pose(&mut self) {
+        self.send(VRProxyDisplayRequest::ResetPose);
+    }
+
+    fn sync_poses(&mut self) {
+        if let Err(msg) = self.request(VRProxyDisplayRequest::SyncPoses) {
+            error!("Error syncing the poses of proxy display {}: {}", self.display_id, msg);
+        }
+    }
+
+    fn bind_framebuffer(&mut self, index: u32) {
+        self.send(VRProxyDisplayRequest::BindFramebuffer(index));
+    }
+
+    fn get_framebuffers(&self) -> Vec<VRFramebuffer> {
+        self.request(VRProxyDisplayRequest::GetFramebuffers).unwrap_or_default()
+    }
+
+    fn render_layer(&mut self, layer: &VRLayer) {
+        self.send(VRProxyDisplayRequest::RenderLayer(layer.clone()));
+    }
+
+    fn submit_frame(&mut self) {
+        self.send(VRProxyDisplayRequest::SubmitFrame);
+    }
+
+    // The GL context stays in this process, the texture of the layer must be shared
+    // with the server process by the embedder
+    #[allow(deprecated)]
+    fn submit_layer(&mut self, _gl: &Gl, layer: &VRLayer) {
+        self.render_layer(layer);
+        self.submit_frame();
+    }
+
+    fn start_present(&mut self, attributes: Option<VRFramebufferAttributes>) {
+        self.send(VRProxyDisplayRequest::StartPresent(attributes));
+    }
+
+    fn stop_present(&mut self) {
+        self.send(VRProxyDisplayRequest::StopPresent);
+    }
+}
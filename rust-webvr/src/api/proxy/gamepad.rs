@@ -0,0 +1,65 @@
+use {VRGamepad, VRGamepadData, VRGamepadPtr, VRGamepadState};
+use super::{ProxyVRIdsPtr, VRProxyRequest, request};
+use ipc_channel::ipc::IpcSender;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub type ProxyVRGamepadPtr = Arc<RefCell<ProxyVRGamepad>>;
+
+// Gamepad of a VRProxyServer, its state is requested each time it is read
+pub struct ProxyVRGamepad {
+    gamepad_id: u32,
+    // Id of the gamepad in the served service
+    served_id: u32,
+    // Updated each time the gamepads are fetched
+    data: VRGamepadData,
+    sender: IpcSender<VRProxyRequest>,
+}
+
+unsafe impl Send for ProxyVRGamepad {}
+unsafe impl Sync for ProxyVRGamepad {}
+
+impl VRGamepad for ProxyVRGamepad {
+    fn id(&self) -> u32 {
+        self.gamepad_id
+    }
+
+    fn data(&self) -> VRGamepadData {
+        self.data.clone()
+    }
+
+    fn state(&self) -> VRGamepadState {
+        let served_id = self.served_id;
+        let mut state = request(&self.sender, |sender| VRProxyRequest::GamepadState(served_id, sender)).unwrap_or_else(|msg| {
+            warn!("Error reading the state of proxy gamepad {}: {}", self.gamepad_id, msg);
+            // Disconnected
+            VRGamepadState::default()
+        });
+        state.gamepad_id = self.gamepad_id;
+        state
+    }
+}
+
+// Keeps the same proxy for a gamepad across fetches, by served id
+pub fn proxy_gamepads(gamepads: Vec<(u32, VRGamepadData)>,
+                      proxies: &mut HashMap<u32, ProxyVRGamepadPtr>,
+                      sender: &IpcSender<VRProxyRequest>,
+                      ids: &ProxyVRIdsPtr)
+                      -> Vec<VRGamepadPtr> {
+    let mut ids = ids.lock().unwrap();
+    gamepads.into_iter().map(|(served_id, mut data)| {
+        let gamepad_id = ids.gamepad_id(served_id, &data);
+        ids.map_gamepad_data(&mut data);
+        let proxy = proxies.entry(served_id).or_insert_with(|| {
+            Arc::new(RefCell::new(ProxyVRGamepad {
+                gamepad_id,
+                served_id,
+                data: data.clone(),
+                sender: sender.clone(),
+            }))
+        });
+        proxy.borrow_mut().data = data;
+        proxy.clone() as VRGamepadPtr
+    }).collect()
+}
@@ -0,0 +1,115 @@
+use {VRDisplayData, VREvent, VRFramebuffer, VRFramebufferAttributes, VRFrameData, VRGamepadData, VRGamepadState, VRLayer, VRResolveFrameData};
+use {VRDeviceIdentity, VRIdAllocatorPtr};
+use ipc_channel::ipc::{self, IpcSender};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+mod display;
+mod gamepad;
+mod server;
+mod service;
+
+pub use self::server::VRProxyServer;
+pub use self::service::ProxyVRService;
+
+// Request sent by a proxy to the VRProxyServer, answered through the included sender
+#[derive(Serialize, Deserialize)]
+pub enum VRProxyRequest {
+    // Name of the served service and whether it is backed by real hardware
+    Describe(IpcSender<(String, bool)>),
+    Initialize(IpcSender<Result<(), String>>),
+    FetchDisplays(IpcSender<Result<Vec<VRDisplayData>, String>>),
+    FetchGamepads(IpcSender<Result<Vec<(u32, VRGamepadData)>, String>>),
+    IsAvailable(IpcSender<bool>),
+    PollEvents(IpcSender<Vec<VREvent>>),
+    Display(u32, VRProxyDisplayRequest),
+    GamepadState(u32, IpcSender<VRGamepadState>),
+}
+
+// Request for a display of the served service, by display id.
+// Requests for unknown displays are dropped along with their sender,
+// except for the future frame data which is resolved with default data.
+#[derive(Serialize, Deserialize)]
+pub enum VRProxyDisplayRequest {
+    Data(IpcSender<VRDisplayData>),
+    FetchGamepads(IpcSender<Result<Vec<(u32, VRGamepadData)>, String>>),
+    ImmediateFrameData(f64, f64, IpcSender<VRFrameData>),
+    // Resolved by the server once the served display resolves its own future
+    FutureFrameData(f64, f64, VRResolveFrameData),
+    SyncPoses(IpcSender<()>),
+    SyncedFrameData(f64, f64, IpcSender<VRFrameData>),
+    ResetPose,
+    BindFramebuffer(u32),
+    GetFramebuffers(IpcSender<Vec<VRFramebuffer>>),
+    RenderLayer(VRLayer),
+    SubmitFrame,
+    StartPresent(Option<VRFramebufferAttributes>),
+    StopPresent,
+}
+
+// Sends a request and waits for the answer
+fn request<T, F>(sender: &IpcSender<VRProxyRequest>, make_request: F) -> Result<T, String>
+    where T: Serialize + DeserializeOwned, F: FnOnce(IpcSender<T>) -> VRProxyRequest {
+    let (reply_sender, reply_receiver) = ipc::channel().map_err(|e| e.to_string())?;
+    sender.send(make_request(reply_sender)).map_err(|e| format!("VR proxy server went away: {:?}", e))?;
+    reply_receiver.recv().map_err(|e| format!("VR proxy request failed: {:?}", e))
+}
+
+pub type ProxyVRIdsPtr = Arc<Mutex<ProxyVRIds>>;
+
+// Maps the device ids of the served service to the ids assigned by the VRServiceManager,
+// which the served service doesn't share
+pub struct ProxyVRIds {
+    ids: VRIdAllocatorPtr,
+    displays: HashMap<u32, u32>,
+    gamepads: HashMap<u32, u32>,
+}
+
+impl ProxyVRIds {
+    pub fn new_ptr(ids: VRIdAllocatorPtr) -> ProxyVRIdsPtr {
+        Arc::new(Mutex::new(ProxyVRIds {
+            ids,
+            displays: HashMap::new(),
+            gamepads: HashMap::new(),
+        }))
+    }
+
+    // Devices without serial get a new id
+    fn local_id(ids: &VRIdAllocatorPtr, map: &mut HashMap<u32, u32>, served_id: u32, identity: &VRDeviceIdentity) -> u32 {
+        *map.entry(served_id).or_insert_with(|| {
+            if identity.serial.is_empty() {
+                ids.new_id()
+            } else {
+                ids.id_for(identity)
+            }
+        })
+    }
+
+    pub fn map_display_data(&mut self, data: &mut VRDisplayData) {
+        data.display_id = ProxyVRIds::local_id(&self.ids, &mut self.displays, data.display_id, &data.identity);
+    }
+
+    pub fn gamepad_id(&mut self, served_id: u32, data: &VRGamepadData) -> u32 {
+        ProxyVRIds::local_id(&self.ids, &mut self.gamepads, served_id, &data.identity)
+    }
+
+    // A display first seen through one of its gamepads gets a new id
+    pub fn map_gamepad_data(&mut self, data: &mut VRGamepadData) {
+        let ids = &self.ids;
+        data.display_id = *self.displays.entry(data.display_id).or_insert_with(|| ids.new_id());
+    }
+
+    pub fn display_id(&self, served_id: u32) -> Option<u32> {
+        self.displays.get(&served_id).cloned()
+    }
+
+    pub fn remove_display(&mut self, served_id: u32) -> Option<u32> {
+        self.displays.remove(&served_id)
+    }
+
+    pub fn remove_gamepad(&mut self, served_id: u32) -> Option<u32> {
+        self.gamepads.remove(&served_id)
+    }
+}
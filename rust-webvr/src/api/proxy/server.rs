@@ -0,0 +1,167 @@
+use {VRDisplayPtr, VRFrameData, VRFutureFrameData, VRGamepadData, VRGamepadPtr, VRResolveFrameData, VRService};
+use super::{VRProxyDisplayRequest, VRProxyRequest};
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+
+// Future frame data waiting to be resolved, along with the resolver of the proxy
+type VRProxyPendingFrame = (VRFutureFrameData, VRResolveFrameData);
+
+// Serves a local VRService to the ProxyVRServices of other processes.
+// The service and its devices live on the server thread.
+pub struct VRProxyServer {
+    service: Box<dyn VRService>,
+    displays: HashMap<u32, VRDisplayPtr>,
+    gamepads: HashMap<u32, VRGamepadPtr>,
+    // Threads waiting for the future frame data of each display, in request order,
+    // so that the other requests are still served
+    frame_waiters: HashMap<u32, Sender<VRProxyPendingFrame>>,
+}
+
+// Moved to the server thread before any device is fetched
+unsafe impl Send for VRProxyServer {}
+
+impl VRProxyServer {
+    // Serves the service from a new thread, until every sender went away.
+    // The sender can be passed to other processes, e.g. through an IpcOneShotServer.
+    pub fn spawn(service: Box<dyn VRService>) -> Result<IpcSender<VRProxyRequest>, String> {
+        let (sender, receiver) = ipc::channel().map_err(|e| e.to_string())?;
+        let server = VRProxyServer::new(service);
+        thread::spawn(move || server.run(receiver));
+        Ok(sender)
+    }
+
+    // Serves the service from the current thread, until every sender went away
+    pub fn serve(service: Box<dyn VRService>, receiver: IpcReceiver<VRProxyRequest>) {
+        VRProxyServer::new(service).run(receiver);
+    }
+
+    fn new(service: Box<dyn VRService>) -> VRProxyServer {
+        VRProxyServer {
+            service,
+            displays: HashMap::new(),
+            gamepads: HashMap::new(),
+            frame_waiters: HashMap::new(),
+        }
+    }
+
+    fn run(mut self, receiver: IpcReceiver<VRProxyRequest>) {
+        while let Ok(request) = receiver.recv() {
+            self.handle_request(request);
+        }
+    }
+
+    // Replies fail when the proxy went away in the meantime, which is fine
+    fn handle_request(&mut self, request: VRProxyRequest) {
+        match request {
+            VRProxyRequest::Describe(sender) => {
+                let _ = sender.send((self.service.name().into(), self.service.is_hardware()));
+            }
+            VRProxyRequest::Initialize(sender) => {
+                let _ = sender.send(self.service.initialize());
+            }
+            VRProxyRequest::FetchDisplays(sender) => {
+                let result = self.service.fetch_displays().map(|displays| {
+                    displays.into_iter().map(|display| {
+                        let data = display.borrow().data();
+                        self.displays.insert(data.display_id, display);
+                        data
+                    }).collect()
+                });
+                let _ = sender.send(result);
+            }
+            VRProxyRequest::FetchGamepads(sender) => {
+                let result = self.service.fetch_gamepads().map(|gamepads| self.add_gamepads(gamepads));
+                let _ = sender.send(result);
+            }
+            VRProxyRequest::IsAvailable(sender) => {
+                let _ = sender.send(self.service.is_available());
+            }
+            VRProxyRequest::PollEvents(sender) => {
+                let _ = sender.send(self.service.poll_events());
+            }
+            VRProxyRequest::Display(display_id, request) => {
+                match self.displays.get(&display_id).cloned() {
+                    Some(display) => self.handle_display_request(display_id, display, request),
+                    None => {
+                        warn!("VR proxy request for an unknown display: {}", display_id);
+                        // Dropping the resolver would make the proxy panic while blocking
+                        if let VRProxyDisplayRequest::FutureFrameData(_, _, mut resolver) = request {
+                            let _ = resolver.resolve(VRFrameData::default());
+                        }
+                    }
+                }
+            }
+            VRProxyRequest::GamepadState(gamepad_id, sender) => {
+                if let Some(gamepad) = self.gamepads.get(&gamepad_id) {
+                    let _ = sender.send(gamepad.borrow().state());
+                }
+            }
+        }
+    }
+
+    #[allow(deprecated)]
+    fn handle_display_request(&mut self, display_id: u32, display: VRDisplayPtr, request: VRProxyDisplayRequest) {
+        match request {
+            VRProxyDisplayRequest::Data(sender) => {
+                let _ = sender.send(display.borrow().data());
+            }
+            VRProxyDisplayRequest::FetchGamepads(sender) => {
+                let result = display.borrow_mut().fetch_gamepads().map(|gamepads| self.add_gamepads(gamepads));
+                let _ = sender.send(result);
+            }
+            VRProxyDisplayRequest::ImmediateFrameData(near_z, far_z, sender) => {
+                let _ = sender.send(display.borrow().immediate_frame_data(near_z, far_z));
+            }
+            VRProxyDisplayRequest::FutureFrameData(near_z, far_z, mut resolver) => {
+                let future = display.borrow_mut().future_frame_data(near_z, far_z);
+                if future.is_resolved() {
+                    let _ = resolver.resolve(future.block());
+                } else {
+                    self.wait_for_frame(display_id, future, resolver);
+                }
+            }
+            VRProxyDisplayRequest::SyncPoses(sender) => {
+                display.borrow_mut().sync_poses();
+                let _ = sender.send(());
+            }
+            VRProxyDisplayRequest::SyncedFrameData(near_z, far_z, sender) => {
+                let _ = sender.send(display.borrow().synced_frame_data(near_z, far_z));
+            }
+            VRProxyDisplayRequest::ResetPose => display.borrow_mut().reset_pose(),
+            VRProxyDisplayRequest::BindFramebuffer(index) => display.borrow_mut().bind_framebuffer(index),
+            VRProxyDisplayRequest::GetFramebuffers(sender) => {
+                let _ = sender.send(display.borrow().get_framebuffers());
+            }
+            VRProxyDisplayRequest::RenderLayer(layer) => display.borrow_mut().render_layer(&layer),
+            VRProxyDisplayRequest::SubmitFrame => display.borrow_mut().submit_frame(),
+            VRProxyDisplayRequest::StartPresent(attributes) => display.borrow_mut().start_present(attributes),
+            VRProxyDisplayRequest::StopPresent => display.borrow_mut().stop_present(),
+        }
+    }
+
+    fn wait_for_frame(&mut self, display_id: u32, future: VRFutureFrameData, resolver: VRResolveFrameData) {
+        let waiter = self.frame_waiters.entry(display_id).or_insert_with(|| {
+            let (sender, receiver) = channel::<VRProxyPendingFrame>();
+            thread::spawn(move || {
+                while let Ok((future, mut resolver)) = receiver.recv() {
+                    let _ = resolver.resolve(future.block());
+                }
+            });
+            sender
+        });
+        let _ = waiter.send((future, resolver));
+    }
+
+    fn add_gamepads(&mut self, gamepads: Vec<VRGamepadPtr>) -> Vec<(u32, VRGamepadData)> {
+        gamepads.into_iter().map(|gamepad| {
+            let (gamepad_id, data) = {
+                let gamepad = gamepad.borrow();
+                (gamepad.id(), gamepad.data())
+            };
+            self.gamepads.insert(gamepad_id, gamepad);
+            (gamepad_id, data)
+        }).collect()
+    }
+}
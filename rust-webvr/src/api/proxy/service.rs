@@ -0,0 +1,118 @@
+use {VRDisplayEvent, VRDisplayPtr, VREvent, VRGamepadEvent, VRGamepadPtr, VRIdAllocatorPtr, VRService};
+use super::{ProxyVRIds, ProxyVRIdsPtr, VRProxyRequest, request};
+use super::display::{ProxyVRDisplay, ProxyVRDisplayPtr};
+use super::gamepad::{ProxyVRGamepadPtr, proxy_gamepads};
+use ipc_channel::ipc::IpcSender;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// Exposes the service served by a VRProxyServer, usually in another process
+pub struct ProxyVRService {
+    sender: IpcSender<VRProxyRequest>,
+    name: String,
+    is_hardware: bool,
+    ids: ProxyVRIdsPtr,
+    // The same proxies are returned for a device across fetches, keyed by served id
+    displays: RefCell<HashMap<u32, ProxyVRDisplayPtr>>,
+    gamepads: RefCell<HashMap<u32, ProxyVRGamepadPtr>>,
+}
+
+unsafe impl Send for ProxyVRService {}
+
+impl ProxyVRService {
+    // The device ids of the served service are mapped to ids from the given allocator
+    pub fn connect(sender: IpcSender<VRProxyRequest>, ids: VRIdAllocatorPtr) -> Result<ProxyVRService, String> {
+        let (name, is_hardware) = request(&sender, VRProxyRequest::Describe)?;
+        Ok(ProxyVRService {
+            sender,
+            name,
+            is_hardware,
+            ids: ProxyVRIds::new_ptr(ids),
+            displays: RefCell::new(HashMap::new()),
+            gamepads: RefCell::new(HashMap::new()),
+        })
+    }
+
+    // Replaces the served device ids of an event, None for the devices that were never seen
+    fn map_event(&self, event: VREvent) -> Option<VREvent> {
+        let mut ids = self.ids.lock().unwrap();
+        let event = match event {
+            VREvent::Display(event) => {
+                let event = match event {
+                    VRDisplayEvent::Connect(mut data) => { ids.map_display_data(&mut data); VRDisplayEvent::Connect(data) }
+                    VRDisplayEvent::Activate(mut data, reason) => { ids.map_display_data(&mut data); VRDisplayEvent::Activate(data, reason) }
+                    VRDisplayEvent::Deactivate(mut data, reason) => { ids.map_display_data(&mut data); VRDisplayEvent::Deactivate(data, reason) }
+                    VRDisplayEvent::Change(mut data) => { ids.map_display_data(&mut data); VRDisplayEvent::Change(data) }
+                    VRDisplayEvent::Blur(mut data) => { ids.map_display_data(&mut data); VRDisplayEvent::Blur(data) }
+                    VRDisplayEvent::Focus(mut data) => { ids.map_display_data(&mut data); VRDisplayEvent::Focus(data) }
+                    VRDisplayEvent::PresentChange(mut data, presenting) => { ids.map_display_data(&mut data); VRDisplayEvent::PresentChange(data, presenting) }
+                    VRDisplayEvent::Disconnect(served_id) => {
+                        self.displays.borrow_mut().remove(&served_id);
+                        VRDisplayEvent::Disconnect(ids.remove_display(served_id)?)
+                    }
+                    VRDisplayEvent::Pause(served_id) => VRDisplayEvent::Pause(ids.display_id(served_id)?),
+                    VRDisplayEvent::Resume(served_id) => VRDisplayEvent::Resume(ids.display_id(served_id)?),
+                    VRDisplayEvent::Exit(served_id) => VRDisplayEvent::Exit(ids.display_id(served_id)?),
+                };
+                event.into()
+            }
+            VREvent::Gamepad(VRGamepadEvent::Connect(mut data, mut state)) => {
+                state.gamepad_id = ids.gamepad_id(state.gamepad_id, &data);
+                ids.map_gamepad_data(&mut data);
+                VRGamepadEvent::Connect(data, state).into()
+            }
+            VREvent::Gamepad(VRGamepadEvent::Disconnect(served_id)) => {
+                self.gamepads.borrow_mut().remove(&served_id);
+                VRGamepadEvent::Disconnect(ids.remove_gamepad(served_id)?).into()
+            }
+        };
+        Some(event)
+    }
+}
+
+impl VRService for ProxyVRService {
+    fn initialize(&mut self) -> Result<(), String> {
+        request(&self.sender, VRProxyRequest::Initialize)?
+    }
+
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, String> {
+        let displays = request(&self.sender, VRProxyRequest::FetchDisplays)??;
+        let mut proxies = self.displays.borrow_mut();
+        Ok(displays.into_iter().map(|data| {
+            let served_id = data.display_id;
+            match proxies.get(&served_id) {
+                Some(proxy) => proxy.borrow().set_data(data),
+                None => {
+                    let proxy = ProxyVRDisplay::new(data, self.sender.clone(), self.ids.clone());
+                    proxies.insert(served_id, proxy);
+                }
+            }
+            proxies[&served_id].clone() as VRDisplayPtr
+        }).collect())
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String> {
+        let gamepads = request(&self.sender, VRProxyRequest::FetchGamepads)??;
+        Ok(proxy_gamepads(gamepads, &mut self.gamepads.borrow_mut(), &self.sender, &self.ids))
+    }
+
+    fn is_available(&self) -> bool {
+        request(&self.sender, VRProxyRequest::IsAvailable).unwrap_or(false)
+    }
+
+    fn poll_events(&self) -> Vec<VREvent> {
+        let events = request(&self.sender, VRProxyRequest::PollEvents).unwrap_or_else(|msg| {
+            warn!("Error polling the events of the VR proxy: {}", msg);
+            Vec::new()
+        });
+        events.into_iter().filter_map(|event| self.map_event(event)).collect()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_hardware(&self) -> bool {
+        self.is_hardware
+    }
+}
@@ -0,0 +1,131 @@
+use {VRDisplay, VRDisplayData, VRFramebuffer, VRFramebufferAttributes, VRFrameData, VRFutureFrameData};
+use {VRGamepadPtr, VRLayer, VRDisplayPtr};
+use super::{VRRecord, VRRecorderPtr};
+use super::gamepad::{RecordingVRGamepadPtr, wrap_gamepads};
+use sparkle::gl::Gl;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub type RecordingVRDisplayPtr = Arc<RefCell<RecordingVRDisplay>>;
+
+// Delegates to the display of another service, recording the frame data handed out
+// to the application and the frames it submits
+pub struct RecordingVRDisplay {
+    display: VRDisplayPtr,
+    recorder: VRRecorderPtr,
+    gamepads: HashMap<u32, RecordingVRGamepadPtr>,
+    // Layer of the last render_layer call, recorded by submit_frame
+    layer: Option<VRLayer>,
+}
+
+unsafe impl Send for RecordingVRDisplay {}
+unsafe impl Sync for RecordingVRDisplay {}
+
+impl RecordingVRDisplay {
+    pub fn new(display: VRDisplayPtr, recorder: VRRecorderPtr) -> RecordingVRDisplayPtr {
+        let data = display.borrow().data();
+        recorder.lock().unwrap().record(VRRecord::DisplayData(data));
+        Arc::new(RefCell::new(RecordingVRDisplay {
+            display,
+            recorder,
+            gamepads: HashMap::new(),
+            layer: None,
+        }))
+    }
+
+    fn record_frame_data(&self, data: &VRFrameData) {
+        let display_id = self.id();
+        self.recorder.lock().unwrap().record(VRRecord::FrameData(display_id, data.clone()));
+    }
+
+    fn record_submit(&mut self, layer: Option<VRLayer>) {
+        let display_id = self.id();
+        self.recorder.lock().unwrap().record(VRRecord::SubmitFrame(display_id, layer));
+    }
+}
+
+impl VRDisplay for RecordingVRDisplay {
+    fn id(&self) -> u32 {
+        self.display.borrow().id()
+    }
+
+    fn data(&self) -> VRDisplayData {
+        self.display.borrow().data()
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String> {
+        let gamepads = self.display.borrow_mut().fetch_gamepads()?;
+        Ok(wrap_gamepads(gamepads, &mut self.gamepads, &self.recorder))
+    }
+
+    fn immediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let data = self.display.borrow().immediate_frame_data(near_z, far_z);
+        self.record_frame_data(&data);
+        data
+    }
+
+    fn future_frame_data(&mut self, near_z: f64, far_z: f64) -> VRFutureFrameData {
+        // The frame data is recorded when the application gets it, so that a stalled
+        // frame doesn't hold back the next ones
+        let display_id = self.id();
+        let recorder = self.recorder.clone();
+        self.display.borrow_mut().future_frame_data(near_z, far_z).on_resolve(move |data| {
+            recorder.lock().unwrap().record(VRRecord::FrameData(display_id, data.clone()));
+        })
+    }
+
+    #[allow(deprecated)]
+    fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let data = self.display.borrow().synced_frame_data(near_z, far_z);
+        self.record_frame_data(&data);
+        data
+    }
+
+    fn reset_pose(&mut self) {
+        self.display.borrow_mut().reset_pose();
+    }
+
+    #[allow(deprecated)]
+    fn sync_poses(&mut self) {
+        self.display.borrow_mut().sync_poses();
+    }
+
+    fn bind_framebuffer(&mut self, index: u32) {
+        self.display.borrow_mut().bind_framebuffer(index);
+    }
+
+    fn get_framebuffers(&self) -> Vec<VRFramebuffer> {
+        self.display.borrow().get_framebuffers()
+    }
+
+    #[allow(deprecated)]
+    fn render_layer(&mut self, layer: &VRLayer) {
+        self.layer = Some(layer.clone());
+        self.display.borrow_mut().render_layer(layer);
+    }
+
+    #[allow(deprecated)]
+    fn submit_frame(&mut self) {
+        self.display.borrow_mut().submit_frame();
+        let layer = self.layer.take();
+        self.record_submit(layer);
+    }
+
+    fn submit_layer(&mut self, gl: &Gl, layer: &VRLayer) {
+        self.display.borrow_mut().submit_layer(gl, layer);
+        self.record_submit(Some(layer.clone()));
+    }
+
+    fn start_present(&mut self, attributes: Option<VRFramebufferAttributes>) {
+        self.display.borrow_mut().start_present(attributes);
+        let display_id = self.id();
+        self.recorder.lock().unwrap().record(VRRecord::StartPresent(display_id));
+    }
+
+    fn stop_present(&mut self) {
+        self.display.borrow_mut().stop_present();
+        let display_id = self.id();
+        self.recorder.lock().unwrap().record(VRRecord::StopPresent(display_id));
+    }
+}
@@ -0,0 +1,51 @@
+use {VRGamepad, VRGamepadData, VRGamepadPtr, VRGamepadState};
+use super::{VRRecord, VRRecorderPtr};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub type RecordingVRGamepadPtr = Arc<RefCell<RecordingVRGamepad>>;
+
+// Records the states of a gamepad as the application reads them
+pub struct RecordingVRGamepad {
+    gamepad: VRGamepadPtr,
+    recorder: VRRecorderPtr,
+}
+
+unsafe impl Send for RecordingVRGamepad {}
+unsafe impl Sync for RecordingVRGamepad {}
+
+impl VRGamepad for RecordingVRGamepad {
+    fn id(&self) -> u32 {
+        self.gamepad.borrow().id()
+    }
+
+    fn data(&self) -> VRGamepadData {
+        self.gamepad.borrow().data()
+    }
+
+    fn state(&self) -> VRGamepadState {
+        let state = self.gamepad.borrow().state();
+        self.recorder.lock().unwrap().record(VRRecord::GamepadState(state.clone()));
+        state
+    }
+}
+
+// Wraps the gamepads, keeping the same wrapper for a gamepad across calls
+pub fn wrap_gamepads(gamepads: Vec<VRGamepadPtr>,
+                     wrapped: &mut HashMap<u32, RecordingVRGamepadPtr>,
+                     recorder: &VRRecorderPtr)
+                     -> Vec<VRGamepadPtr> {
+    gamepads.into_iter().map(|gamepad| {
+        let gamepad_id = gamepad.borrow().id();
+        let wrapper = wrapped.entry(gamepad_id).or_insert_with(|| {
+            let data = gamepad.borrow().data();
+            recorder.lock().unwrap().record(VRRecord::GamepadData(gamepad_id, data));
+            Arc::new(RefCell::new(RecordingVRGamepad {
+                gamepad: gamepad.clone(),
+                recorder: recorder.clone(),
+            }))
+        });
+        wrapper.clone() as VRGamepadPtr
+    }).collect()
+}
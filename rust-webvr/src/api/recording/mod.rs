@@ -0,0 +1,12 @@
+mod display;
+mod gamepad;
+mod record;
+#[cfg(feature = "replay")]
+mod replay;
+mod service;
+
+pub use self::record::{VRRecord, VRRecordEntry, VRRecorder, VRRecorderPtr, VRRecording, VRRecordingHeader};
+pub use self::record::{VR_RECORDING_MAGIC, VR_RECORDING_VERSION};
+pub use self::service::RecordingVRService;
+#[cfg(feature = "replay")]
+pub use self::replay::{ReplayVRService, VRReplayMode, VRReplayRemote};
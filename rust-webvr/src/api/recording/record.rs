@@ -0,0 +1,131 @@
+use {VRClockPtr, VRDisplayData, VREvent, VRFrameData, VRGamepadData, VRGamepadState, VRLayer};
+use bincode;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Recording files start with the magic, then the header and the entries, encoded with bincode
+pub const VR_RECORDING_MAGIC: &[u8; 8] = b"VRRECORD";
+pub const VR_RECORDING_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VRRecordingHeader {
+    pub version: u32,
+    // Wall clock time when the recording started, in milliseconds since the UNIX epoch
+    pub wall_time: f64,
+    // Time of the services clock when the recording started
+    pub start_time: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VRRecordEntry {
+    // Time of the services clock, in milliseconds
+    pub time: f64,
+    pub record: VRRecord,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VRRecord {
+    // Data of a display when it is first seen, later changes come with the events
+    DisplayData(VRDisplayData),
+    // Frame data returned to the application, by display id
+    FrameData(u32, VRFrameData),
+    // Data of a gamepad when it is first seen, by gamepad id
+    GamepadData(u32, VRGamepadData),
+    GamepadState(VRGamepadState),
+    Event(VREvent),
+    StartPresent(u32),
+    StopPresent(u32),
+    // A frame submitted to a display, with its layer when known
+    SubmitFrame(u32, Option<VRLayer>),
+}
+
+// A recording file read back
+pub struct VRRecording {
+    pub header: VRRecordingHeader,
+    pub entries: Vec<VRRecordEntry>,
+}
+
+impl VRRecording {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<VRRecording, String> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| format!("Error opening {}: {}", path.display(), e))?;
+        let mut reader = BufReader::new(file);
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic).map_err(|e| e.to_string())?;
+        if &magic != VR_RECORDING_MAGIC {
+            return Err(format!("{} is not a VR recording", path.display()));
+        }
+        let header: VRRecordingHeader = bincode::deserialize_from(&mut reader).map_err(|e| e.to_string())?;
+        if header.version != VR_RECORDING_VERSION {
+            return Err(format!("Unsupported VR recording version: {}", header.version));
+        }
+        let mut entries = Vec::new();
+        loop {
+            match bincode::deserialize_from(&mut reader) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => {
+                    // The end of the recording is lost when the recorded application crashed
+                    match *e {
+                        bincode::ErrorKind::Io(ref e) if e.kind() == ErrorKind::UnexpectedEof => {}
+                        ref e => warn!("VR recording {} is truncated: {}", path.display(), e),
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(VRRecording {
+            header,
+            entries,
+        })
+    }
+}
+
+pub type VRRecorderPtr = Arc<Mutex<VRRecorder>>;
+
+// Appends timestamped records to a recording file
+pub struct VRRecorder {
+    // None once writing failed
+    writer: Option<BufWriter<File>>,
+    clock: VRClockPtr,
+}
+
+impl VRRecorder {
+    pub fn create<P: AsRef<Path>>(path: P, clock: VRClockPtr) -> Result<VRRecorderPtr, String> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| format!("Error creating {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+        let wall_time = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs_f64() * 1000.0).unwrap_or(0.0);
+        let header = VRRecordingHeader {
+            version: VR_RECORDING_VERSION,
+            wall_time,
+            start_time: clock.now(),
+        };
+        writer.write_all(VR_RECORDING_MAGIC).map_err(|e| e.to_string())?;
+        bincode::serialize_into(&mut writer, &header).map_err(|e| e.to_string())?;
+        Ok(Arc::new(Mutex::new(VRRecorder {
+            writer: Some(writer),
+            clock,
+        })))
+    }
+
+    pub fn record(&mut self, record: VRRecord) {
+        let entry = VRRecordEntry {
+            time: self.clock.now(),
+            record,
+        };
+        // Written once per frame, so that a crash loses at most a frame
+        let flush = matches!(entry.record, VRRecord::SubmitFrame(..));
+        let result = match self.writer {
+            Some(ref mut writer) => bincode::serialize_into(&mut *writer, &entry).map_err(|e| e.to_string())
+                                           .and_then(|_| if flush { writer.flush().map_err(|e| e.to_string()) } else { Ok(()) }),
+            None => return,
+        };
+        if let Err(msg) = result {
+            error!("Error writing VR recording, recording stopped: {}", msg);
+            self.writer = None;
+        }
+    }
+}
@@ -0,0 +1,494 @@
+use {VRClockPtr, VRDisplay, VRDisplayData, VRDisplayEvent, VRDisplayPtr, VREvent, VRFramebuffer, VRFramebufferAttributes};
+use {VRFrameData, VRFutureFrameData, VRGamepadData, VRGamepadEvent, VRGamepadPtr, VRIdAllocatorPtr, VRLayer, VRService};
+use {MockVRControlMsg, MockVRGamepadInit, MockVRInit, MockVRTargetRayMode};
+use api::{MockServiceCreator, MockVRDisplayHandle, MockVRServiceRemote};
+use api::mock::MockVRReplayMsg;
+use super::{VRRecord, VRRecordEntry, VRRecording};
+use sparkle::gl::Gl;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VRReplayMode {
+    // The records are played at the time they were recorded, against the services clock
+    RealTime,
+    // Each frame requested by the application gets the next recorded frame, right away
+    AsFastAsPossible,
+    // Same as AsFastAsPossible, but each frame waits for VRReplayRemote::step
+    SingleStep,
+}
+
+type VRReplayPlayerPtr = Arc<Mutex<VRReplayPlayer>>;
+
+// Plays a recording back on the displays of a mock service
+struct VRReplayPlayer {
+    entries: VecDeque<VRRecordEntry>,
+    // Recording time of the last record played
+    time: f64,
+    // Services clock time minus recording time, in real time mode
+    offset: f64,
+    remote: MockVRServiceRemote,
+    // Mock display of each recorded display, by recorded id
+    displays: HashMap<u32, (u32, MockVRDisplayHandle)>,
+    // Recorded display of each recorded gamepad, which is also its mock gamepad index
+    gamepads: HashMap<u32, u32>,
+}
+
+impl VRReplayPlayer {
+    // Plays the records that set the session up, up to the first frame
+    fn start(&mut self, now: f64) {
+        self.play_until_frame();
+        self.offset = now - self.time;
+    }
+
+    fn play_until_frame(&mut self) {
+        while self.entries.front().is_some_and(|entry| !is_frame(&entry.record)) {
+            self.play_next();
+        }
+    }
+
+    fn play_until(&mut self, now: f64) {
+        while self.entries.front().is_some_and(|entry| entry.time + self.offset <= now) {
+            self.play_next();
+        }
+    }
+
+    // Plays the records up to the next frame of the display, and the gamepad states read by
+    // the application right after it. Returns its timestamp, or None at the end of the recording.
+    fn next_frame(&mut self, display_id: u32) -> Option<f64> {
+        let mut timestamp = None;
+        while let Some(entry) = self.entries.pop_front() {
+            if let VRRecord::FrameData(frame_display_id, ref data) = entry.record {
+                if frame_display_id == display_id {
+                    timestamp = Some(data.timestamp);
+                }
+            }
+            self.play(entry);
+            if timestamp.is_some() {
+                break;
+            }
+        }
+        while self.entries.front().is_some_and(|entry| matches!(entry.record, VRRecord::GamepadState(..))) {
+            self.play_next();
+        }
+        timestamp
+    }
+
+    fn play_next(&mut self) {
+        if let Some(entry) = self.entries.pop_front() {
+            self.play(entry);
+        }
+    }
+
+    fn play(&mut self, entry: VRRecordEntry) {
+        self.time = entry.time;
+        match entry.record {
+            VRRecord::DisplayData(data) => {
+                self.add_display(&data);
+                self.send_replay(data.display_id, MockVRReplayMsg::SetDisplayData(data));
+            }
+            VRRecord::FrameData(display_id, data) => {
+                self.send_replay(display_id, MockVRReplayMsg::SetFrameData(Some(data)));
+            }
+            VRRecord::GamepadData(gamepad_id, data) => {
+                self.gamepads.insert(gamepad_id, data.display_id);
+                self.send(data.display_id, MockVRControlMsg::ConnectGamepad(gamepad_id, gamepad_init(data)));
+            }
+            VRRecord::GamepadState(state) => {
+                self.send_gamepad(state.gamepad_id, MockVRReplayMsg::SetGamepadState(state.gamepad_id, state));
+            }
+            VRRecord::Event(event) => self.play_event(event),
+            // Nothing to reproduce, the application presents again by itself
+            VRRecord::StartPresent(_) | VRRecord::StopPresent(_) | VRRecord::SubmitFrame(..) => {}
+        }
+    }
+
+    // Connections go through the mock messages, so that the mock devices follow them
+    fn play_event(&mut self, event: VREvent) {
+        match event {
+            VREvent::Display(VRDisplayEvent::Connect(data)) => {
+                // Displays connected during the recording have no DisplayData record before
+                self.add_display(&data);
+                let display_id = data.display_id;
+                self.send_replay(display_id, MockVRReplayMsg::SetDisplayData(data));
+                self.send(display_id, MockVRControlMsg::Connect);
+            }
+            VREvent::Display(VRDisplayEvent::Disconnect(display_id)) => {
+                self.send(display_id, MockVRControlMsg::Disconnect);
+            }
+            VREvent::Display(event) => {
+                let display_id = display_event_id(&event);
+                let mock_id = match self.displays.get(&display_id) {
+                    Some(&(mock_id, _)) => mock_id,
+                    None => return warn!("Replayed event of an unknown display: {:?}", event),
+                };
+                if let Some(data) = display_event_data(&event) {
+                    self.send_replay(display_id, MockVRReplayMsg::SetDisplayData(data.clone()));
+                }
+                let event = map_display_event(event, mock_id);
+                self.send_replay(display_id, MockVRReplayMsg::EmitEvent(event.into()));
+            }
+            VREvent::Gamepad(VRGamepadEvent::Connect(data, state)) => {
+                let gamepad_id = state.gamepad_id;
+                self.gamepads.insert(gamepad_id, data.display_id);
+                self.send(data.display_id, MockVRControlMsg::ConnectGamepad(gamepad_id, gamepad_init(data)));
+                self.send_gamepad(gamepad_id, MockVRReplayMsg::SetGamepadState(gamepad_id, state));
+            }
+            VREvent::Gamepad(VRGamepadEvent::Disconnect(gamepad_id)) => {
+                match self.gamepads.get(&gamepad_id) {
+                    Some(&display_id) => self.send(display_id, MockVRControlMsg::DisconnectGamepad(gamepad_id)),
+                    None => warn!("Replayed event of an unknown gamepad: {}", gamepad_id),
+                }
+            }
+        }
+    }
+
+    // Creates the mock display of a recorded display the first time it is seen
+    fn add_display(&mut self, data: &VRDisplayData) {
+        if !self.displays.contains_key(&data.display_id) {
+            let init = MockVRInit {
+                serial: Some(data.identity.serial.clone()),
+                ..Default::default()
+            };
+            let (mock_id, _, handle) = self.remote.add_display_with_handle(init);
+            self.displays.insert(data.display_id, (mock_id, handle));
+        }
+    }
+
+    fn send(&self, display_id: u32, msg: MockVRControlMsg) {
+        match self.displays.get(&display_id) {
+            Some((_, handle)) => handle.handle_msg(msg),
+            None => warn!("Replayed record of an unknown display: {:?}", msg),
+        }
+    }
+
+    fn send_replay(&self, display_id: u32, msg: MockVRReplayMsg) {
+        match self.displays.get(&display_id) {
+            Some((_, handle)) => handle.handle_replay_msg(msg),
+            None => warn!("Replayed record of an unknown display: {:?}", msg),
+        }
+    }
+
+    fn send_gamepad(&self, gamepad_id: u32, msg: MockVRReplayMsg) {
+        match self.gamepads.get(&gamepad_id) {
+            Some(&display_id) => self.send_replay(display_id, msg),
+            None => warn!("Replayed record of an unknown gamepad: {:?}", msg),
+        }
+    }
+
+    fn recorded_id(&self, mock_id: u32) -> Option<u32> {
+        self.displays.iter().find(|&(_, (id, _))| *id == mock_id).map(|(display_id, _)| *display_id)
+    }
+}
+
+fn is_frame(record: &VRRecord) -> bool {
+    matches!(*record, VRRecord::FrameData(..))
+}
+
+// The number of buttons and axes comes with the state
+fn gamepad_init(data: VRGamepadData) -> MockVRGamepadInit {
+    MockVRGamepadInit {
+        name: data.name,
+        hand: data.hand,
+        buttons: 0,
+        axes: 0,
+        serial: Some(data.identity.serial),
+        target_ray_mode: MockVRTargetRayMode::default(),
+        profiles: Vec::new(),
+        pointer_origin: None,
+    }
+}
+
+fn display_event_id(event: &VRDisplayEvent) -> u32 {
+    match *event {
+        VRDisplayEvent::Connect(ref data) |
+        VRDisplayEvent::Activate(ref data, _) |
+        VRDisplayEvent::Deactivate(ref data, _) |
+        VRDisplayEvent::Change(ref data) |
+        VRDisplayEvent::Blur(ref data) |
+        VRDisplayEvent::Focus(ref data) |
+        VRDisplayEvent::PresentChange(ref data, _) => data.display_id,
+        VRDisplayEvent::Disconnect(display_id) |
+        VRDisplayEvent::Pause(display_id) |
+        VRDisplayEvent::Resume(display_id) |
+        VRDisplayEvent::Exit(display_id) => display_id,
+    }
+}
+
+fn display_event_data(event: &VRDisplayEvent) -> Option<&VRDisplayData> {
+    match *event {
+        VRDisplayEvent::Connect(ref data) |
+        VRDisplayEvent::Activate(ref data, _) |
+        VRDisplayEvent::Deactivate(ref data, _) |
+        VRDisplayEvent::Change(ref data) |
+        VRDisplayEvent::Blur(ref data) |
+        VRDisplayEvent::Focus(ref data) |
+        VRDisplayEvent::PresentChange(ref data, _) => Some(data),
+        _ => None,
+    }
+}
+
+// The recorded display ids are replaced with the ids of the mock displays
+fn map_display_event(event: VRDisplayEvent, mock_id: u32) -> VRDisplayEvent {
+    match event {
+        VRDisplayEvent::Connect(mut data) => { data.display_id = mock_id; VRDisplayEvent::Connect(data) }
+        VRDisplayEvent::Activate(mut data, reason) => { data.display_id = mock_id; VRDisplayEvent::Activate(data, reason) }
+        VRDisplayEvent::Deactivate(mut data, reason) => { data.display_id = mock_id; VRDisplayEvent::Deactivate(data, reason) }
+        VRDisplayEvent::Change(mut data) => { data.display_id = mock_id; VRDisplayEvent::Change(data) }
+        VRDisplayEvent::Blur(mut data) => { data.display_id = mock_id; VRDisplayEvent::Blur(data) }
+        VRDisplayEvent::Focus(mut data) => { data.display_id = mock_id; VRDisplayEvent::Focus(data) }
+        VRDisplayEvent::PresentChange(mut data, presenting) => { data.display_id = mock_id; VRDisplayEvent::PresentChange(data, presenting) }
+        VRDisplayEvent::Disconnect(_) => VRDisplayEvent::Disconnect(mock_id),
+        VRDisplayEvent::Pause(_) => VRDisplayEvent::Pause(mock_id),
+        VRDisplayEvent::Resume(_) => VRDisplayEvent::Resume(mock_id),
+        VRDisplayEvent::Exit(_) => VRDisplayEvent::Exit(mock_id),
+    }
+}
+
+// Steps a replay in single step mode, and tells when it is over
+#[derive(Clone)]
+pub struct VRReplayRemote {
+    player: VRReplayPlayerPtr,
+    steps: Sender<()>,
+}
+
+impl VRReplayRemote {
+    // Lets the next frame be played
+    pub fn step(&self) {
+        let _ = self.steps.send(());
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.player.lock().unwrap().entries.is_empty()
+    }
+}
+
+// Exposes the displays and gamepads of a recording, reproducing the recorded data
+// on mock displays
+pub struct ReplayVRService {
+    service: Box<dyn VRService>,
+    player: VRReplayPlayerPtr,
+    mode: VRReplayMode,
+    clock: VRClockPtr,
+    // Once the remote goes away, single step replays play freely
+    steps: Arc<Mutex<Receiver<()>>>,
+    displays: HashMap<u32, ReplayVRDisplayPtr>,
+}
+
+unsafe impl Send for ReplayVRService {}
+
+impl ReplayVRService {
+    pub fn new(recording: VRRecording, mode: VRReplayMode, clock: VRClockPtr, ids: VRIdAllocatorPtr)
+               -> (ReplayVRService, VRReplayRemote) {
+        let (service, remote) = MockServiceCreator::new_service_with_displays(clock.clone(), ids);
+        let player = Arc::new(Mutex::new(VRReplayPlayer {
+            entries: recording.entries.into(),
+            time: recording.header.start_time,
+            offset: 0.0,
+            remote,
+            displays: HashMap::new(),
+            gamepads: HashMap::new(),
+        }));
+        let (sender, receiver) = channel();
+        let service = ReplayVRService {
+            service,
+            player: player.clone(),
+            mode,
+            clock,
+            steps: Arc::new(Mutex::new(receiver)),
+            displays: HashMap::new(),
+        };
+        (service, VRReplayRemote { player, steps: sender })
+    }
+
+    fn play(&self) {
+        if self.mode == VRReplayMode::RealTime {
+            self.player.lock().unwrap().play_until(self.clock.now());
+        }
+    }
+}
+
+impl VRService for ReplayVRService {
+    fn initialize(&mut self) -> Result<(), String> {
+        self.service.initialize()?;
+        self.player.lock().unwrap().start(self.clock.now());
+        Ok(())
+    }
+
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, String> {
+        self.play();
+        let displays = self.service.fetch_displays()?;
+        let mut result = Vec::new();
+        for display in displays {
+            let mock_id = display.borrow().id();
+            if !self.displays.contains_key(&mock_id) {
+                let recorded_id = match self.player.lock().unwrap().recorded_id(mock_id) {
+                    Some(recorded_id) => recorded_id,
+                    None => continue,
+                };
+                self.displays.insert(mock_id, Arc::new(RefCell::new(ReplayVRDisplay {
+                    display,
+                    recorded_id,
+                    player: self.player.clone(),
+                    mode: self.mode,
+                    clock: self.clock.clone(),
+                    steps: self.steps.clone(),
+                    timestamp: None,
+                })));
+            }
+            result.push(self.displays[&mock_id].clone() as VRDisplayPtr);
+        }
+        Ok(result)
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String> {
+        self.play();
+        self.service.fetch_gamepads()
+    }
+
+    fn is_available(&self) -> bool {
+        self.service.is_available()
+    }
+
+    fn poll_events(&self) -> Vec<VREvent> {
+        match self.mode {
+            VRReplayMode::RealTime => self.play(),
+            // The events recorded during the last frame
+            _ => self.player.lock().unwrap().play_until_frame(),
+        }
+        self.service.poll_events()
+    }
+
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn is_hardware(&self) -> bool {
+        false
+    }
+}
+
+type ReplayVRDisplayPtr = Arc<RefCell<ReplayVRDisplay>>;
+
+// Moves the replay forward as the application requests frames from a mock display
+struct ReplayVRDisplay {
+    display: VRDisplayPtr,
+    recorded_id: u32,
+    player: VRReplayPlayerPtr,
+    mode: VRReplayMode,
+    clock: VRClockPtr,
+    steps: Arc<Mutex<Receiver<()>>>,
+    // Recorded timestamp of the current frame
+    timestamp: Option<f64>,
+}
+
+unsafe impl Send for ReplayVRDisplay {}
+unsafe impl Sync for ReplayVRDisplay {}
+
+impl ReplayVRDisplay {
+    fn next_frame(&mut self) {
+        match self.mode {
+            VRReplayMode::RealTime => {
+                self.player.lock().unwrap().play_until(self.clock.now());
+                return;
+            }
+            VRReplayMode::SingleStep => {
+                let _ = self.steps.lock().unwrap().recv();
+            }
+            VRReplayMode::AsFastAsPossible => {}
+        }
+        if let Some(timestamp) = self.player.lock().unwrap().next_frame(self.recorded_id) {
+            self.timestamp = Some(timestamp);
+        }
+    }
+
+    // The frame data set by the player, with its recorded timestamp
+    fn recorded_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let mut data = self.display.borrow().immediate_frame_data(near_z, far_z);
+        if let Some(timestamp) = self.timestamp {
+            data.timestamp = timestamp;
+        }
+        data
+    }
+}
+
+impl VRDisplay for ReplayVRDisplay {
+    fn id(&self) -> u32 {
+        self.display.borrow().id()
+    }
+
+    fn data(&self) -> VRDisplayData {
+        self.display.borrow().data()
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String> {
+        self.display.borrow_mut().fetch_gamepads()
+    }
+
+    fn immediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        if self.mode == VRReplayMode::RealTime {
+            self.player.lock().unwrap().play_until(self.clock.now());
+        }
+        self.display.borrow().immediate_frame_data(near_z, far_z)
+    }
+
+    fn future_frame_data(&mut self, near_z: f64, far_z: f64) -> VRFutureFrameData {
+        self.next_frame();
+        match self.mode {
+            VRReplayMode::RealTime => self.display.borrow_mut().future_frame_data(near_z, far_z),
+            _ => VRFutureFrameData::resolved(self.recorded_frame_data(near_z, far_z)),
+        }
+    }
+
+    #[allow(deprecated)]
+    fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        match self.mode {
+            VRReplayMode::RealTime => self.display.borrow().synced_frame_data(near_z, far_z),
+            _ => self.recorded_frame_data(near_z, far_z),
+        }
+    }
+
+    fn reset_pose(&mut self) {
+        self.display.borrow_mut().reset_pose();
+    }
+
+    #[allow(deprecated)]
+    fn sync_poses(&mut self) {
+        self.next_frame();
+        if self.mode == VRReplayMode::RealTime {
+            self.display.borrow_mut().sync_poses();
+        }
+    }
+
+    fn bind_framebuffer(&mut self, index: u32) {
+        self.display.borrow_mut().bind_framebuffer(index);
+    }
+
+    fn get_framebuffers(&self) -> Vec<VRFramebuffer> {
+        self.display.borrow().get_framebuffers()
+    }
+
+    #[allow(deprecated)]
+    fn render_layer(&mut self, layer: &VRLayer) {
+        self.display.borrow_mut().render_layer(layer);
+    }
+
+    #[allow(deprecated)]
+    fn submit_frame(&mut self) {
+        self.display.borrow_mut().submit_frame();
+    }
+
+    fn submit_layer(&mut self, gl: &Gl, layer: &VRLayer) {
+        self.display.borrow_mut().submit_layer(gl, layer);
+    }
+
+    fn start_present(&mut self, attributes: Option<VRFramebufferAttributes>) {
+        self.display.borrow_mut().start_present(attributes);
+    }
+
+    fn stop_present(&mut self) {
+        self.display.borrow_mut().stop_present();
+    }
+}
@@ -0,0 +1,71 @@
+use {VRDisplayPtr, VREvent, VRGamepadPtr, VRService};
+use super::{VRRecord, VRRecorderPtr};
+use super::display::{RecordingVRDisplay, RecordingVRDisplayPtr};
+use super::gamepad::{RecordingVRGamepadPtr, wrap_gamepads};
+use std::collections::HashMap;
+
+// Records a session of any VRService to a file, see VRServiceManager::record_to
+pub struct RecordingVRService {
+    service: Box<dyn VRService>,
+    recorder: VRRecorderPtr,
+    // The same wrappers are returned for a device across fetches
+    displays: HashMap<u32, RecordingVRDisplayPtr>,
+    gamepads: HashMap<u32, RecordingVRGamepadPtr>,
+}
+
+unsafe impl Send for RecordingVRService {}
+
+impl RecordingVRService {
+    pub fn new(service: Box<dyn VRService>, recorder: VRRecorderPtr) -> RecordingVRService {
+        RecordingVRService {
+            service,
+            recorder,
+            displays: HashMap::new(),
+            gamepads: HashMap::new(),
+        }
+    }
+}
+
+impl VRService for RecordingVRService {
+    fn initialize(&mut self) -> Result<(), String> {
+        self.service.initialize()
+    }
+
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, String> {
+        let displays = self.service.fetch_displays()?;
+        let recorder = &self.recorder;
+        let wrapped = &mut self.displays;
+        Ok(displays.into_iter().map(|display| {
+            let display_id = display.borrow().id();
+            wrapped.entry(display_id)
+                   .or_insert_with(|| RecordingVRDisplay::new(display, recorder.clone()))
+                   .clone() as VRDisplayPtr
+        }).collect())
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String> {
+        let gamepads = self.service.fetch_gamepads()?;
+        Ok(wrap_gamepads(gamepads, &mut self.gamepads, &self.recorder))
+    }
+
+    fn is_available(&self) -> bool {
+        self.service.is_available()
+    }
+
+    fn poll_events(&self) -> Vec<VREvent> {
+        let events = self.service.poll_events();
+        let mut recorder = self.recorder.lock().unwrap();
+        for event in &events {
+            recorder.record(VRRecord::Event(event.clone()));
+        }
+        events
+    }
+
+    fn name(&self) -> &str {
+        self.service.name()
+    }
+
+    fn is_hardware(&self) -> bool {
+        self.service.is_hardware()
+    }
+}
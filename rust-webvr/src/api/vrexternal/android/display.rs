@@ -1,11 +1,10 @@
 use super::{mozgfx, VRExternalShmemPtr};
-use rust_webvr_api::utils;
 use std::cell::RefCell;
 use std::mem;
 use std::sync::Arc;
 use {
-    VRDisplay, VRDisplayData, VRDisplayEvent, VRFrameData, VRFramebuffer,
-    VRFramebufferAttributes, VRGamepadPtr, VRLayer, VRViewport,
+    VRDeviceIdentity, VRDisplay, VRDisplayData, VRDisplayEvent, VRFrameData, VRFramebuffer,
+    VRFramebufferAttributes, VRGamepadPtr, VRIdAllocatorPtr, VRLayer, VRViewport,
 };
 
 pub type VRExternalDisplayPtr = Arc<RefCell<VRExternalDisplay>>;
@@ -16,21 +15,25 @@ pub struct VRExternalDisplay {
     rendered_layer: Option<VRLayer>,
     shmem: VRExternalShmemPtr,
     display_id: u32,
+    identity: VRDeviceIdentity,
     attributes: VRFramebufferAttributes,
     presenting: bool,
     events: Vec<VRDisplayEvent>,
 }
 
 impl VRExternalDisplay {
-    pub fn new(shmem: VRExternalShmemPtr) -> VRExternalDisplayPtr {
+    pub fn new(shmem: VRExternalShmemPtr, ids: &VRIdAllocatorPtr) -> VRExternalDisplayPtr {
         let system_state = shmem.as_mut().pull_system(&|_| true);
         let browser_state = shmem.as_mut().pull_browser();
+        // The external shmem doesn't carry a serial number, the display name is used instead.
+        let identity = VRDeviceIdentity::new("vrexternal", display_name(&system_state.displayState));
         Arc::new(RefCell::new(VRExternalDisplay {
             system_state,
             browser_state,
             rendered_layer: None,
             shmem,
-            display_id: utils::new_id(),
+            display_id: ids.id_for(&identity),
+            identity,
             attributes: Default::default(),
             presenting: false,
             events: Vec::new(),
@@ -57,8 +60,9 @@ impl VRDisplay for VRExternalDisplay {
         let mut data = VRDisplayData::default();
 
         let state: &mozgfx::VRDisplayState = &self.system_state.displayState;
-        data.display_name = state.displayName.iter().map(|x| *x as u8 as char).collect();
+        data.display_name = display_name(state);
         data.display_id = self.display_id;
+        data.identity = self.identity.clone();
         data.connected = state.isConnected;
 
         let flags = state.capabilityFlags;
@@ -270,3 +274,7 @@ impl VRDisplay for VRExternalDisplay {
         self.push_browser();
     }
 }
+
+fn display_name(state: &mozgfx::VRDisplayState) -> String {
+    state.displayName.iter().map(|x| *x as u8 as char).collect()
+}
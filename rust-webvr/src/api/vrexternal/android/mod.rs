@@ -3,7 +3,7 @@ mod mozgfx;
 mod service;
 
 use std::os::raw::c_void;
-use {VRService, VRServiceCreator};
+use {VRClockPtr, VRIdAllocatorPtr, VRService, VRServiceCreator};
 
 #[derive(Clone)]
 pub struct VRExternalShmemPtr(*mut mozgfx::VRExternalShmem);
@@ -30,7 +30,7 @@ impl VRExternalServiceCreator {
 }
 
 impl VRServiceCreator for VRExternalServiceCreator {
-    fn new_service(&self) -> Box<VRService> {
-        Box::new(service::VRExternalService::new(self.0.clone()))
+    fn new_service(&self, _clock: VRClockPtr, ids: VRIdAllocatorPtr) -> Box<VRService> {
+        Box::new(service::VRExternalService::new(self.0.clone(), ids))
     }
 }
@@ -1,10 +1,11 @@
 use super::display::{VRExternalDisplay, VRExternalDisplayPtr};
 use super::VRExternalShmemPtr;
-use {VRDisplayPtr, VREvent, VRGamepadPtr, VRService};
+use {VRDisplayPtr, VREvent, VRGamepadPtr, VRIdAllocatorPtr, VRService};
 
 pub struct VRExternalService {
     shmem: VRExternalShmemPtr,
     display: Option<VRExternalDisplayPtr>,
+    ids: VRIdAllocatorPtr,
 }
 
 unsafe impl Send for VRExternalService {}
@@ -18,7 +19,7 @@ impl VRService for VRExternalService {
         if self.display.is_none() {
             // Block until enumerationCompleted is true.
             self.shmem.as_mut().pull_system(&|state| state.enumerationCompleted);
-            let display = VRExternalDisplay::new(self.shmem.clone());
+            let display = VRExternalDisplay::new(self.shmem.clone(), &self.ids);
             self.display = Some(display);
         }
         Ok(vec![self.display.as_ref().unwrap().clone()])
@@ -44,13 +45,18 @@ impl VRService for VRExternalService {
             }
         }
     }
+
+    fn name(&self) -> &str {
+        "vrexternal"
+    }
 }
 
 impl VRExternalService {
-    pub fn new(ptr: VRExternalShmemPtr) -> VRExternalService {
+    pub fn new(ptr: VRExternalShmemPtr, ids: VRIdAllocatorPtr) -> VRExternalService {
         VRExternalService {
             shmem: ptr.clone(),
             display: None,
+            ids: ids,
         }
     }
 }
@@ -1,10 +1,16 @@
-#[cfg(all(feature = "vrexternal", target_os= "android"))]
+// The devices are shared as Arc<RefCell<..>> pointers, made Send and Sync by the unsafe
+// impls next to each device type
+#![allow(clippy::arc_with_non_send_sync)]
+
+#[cfg(any(all(feature = "vrexternal", target_os= "android"), all(feature = "pose-ring", unix)))]
 extern crate libc;
 extern crate rust_webvr_api;
 #[cfg(all(feature = "googlevr", target_os= "android"))]
 extern crate gvr_sys;
 #[cfg(all(target_os="windows", feature = "openvr"))]
 extern crate libloading;
+#[cfg(feature = "plugin")]
+extern crate plugin_libloading;
 #[macro_use]
 extern crate log;
 #[cfg(all(feature = "oculusvr", target_os= "android"))]
@@ -16,9 +22,21 @@ extern crate gleam;
 #[cfg(feature = "glwindow")]
 extern crate glutin;
 #[cfg(feature = "serde-serialization")]
+extern crate serde;
+#[cfg(feature = "serde-serialization")]
 #[macro_use] extern crate serde_derive;
-#[cfg(any(feature = "magicleap", feature = "glwindow"))]
+#[cfg(any(feature = "config", feature = "mock-server"))]
+extern crate serde_json;
+#[cfg(feature = "config")]
+extern crate ron;
+#[cfg(feature = "config")]
+extern crate toml;
+#[cfg(any(feature = "magicleap", feature = "glwindow", feature = "mock", feature = "recording", feature = "ipc"))]
 extern crate sparkle;
+#[cfg(feature = "ipc")]
+extern crate ipc_channel;
+#[cfg(feature = "recording")]
+extern crate bincode;
 
 #[cfg(any(feature = "googlevr", feature= "oculusvr"))]
 mod gl {
@@ -46,7 +64,17 @@ mod egl {
 pub use api::GlWindowVRService;
 
 pub mod api;
+#[cfg(feature = "mock")]
+mod vr_frame_harness;
 mod vr_manager;
+mod vr_service_config;
+mod vr_service_policy;
+mod vr_service_status;
 
 pub use rust_webvr_api::*;
+#[cfg(feature = "mock")]
+pub use vr_frame_harness::{VRFrameHarness, VRHarnessFrame};
 pub use vr_manager::VRServiceManager;
+pub use vr_service_config::{VRServiceConfig, VRServiceConfigEntry};
+pub use vr_service_policy::VRServicePolicy;
+pub use vr_service_status::VRServiceStatus;
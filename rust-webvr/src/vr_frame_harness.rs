@@ -0,0 +1,149 @@
+use std::sync::Arc;
+use std::sync::mpsc::Receiver;
+use api::MockVRDisplayHandle;
+use MockVRControlMsg;
+use MockVRFrame;
+use MockVRInit;
+use VRDisplay;
+use VRDisplayPtr;
+use VREvent;
+use VRFrameData;
+use VRLayer;
+use VRManualClock;
+use VRServiceManager;
+
+const DEFAULT_NEAR: f64 = 0.1;
+const DEFAULT_FAR: f64 = 1000.0;
+
+// What happened during a step of a VRFrameHarness
+#[derive(Debug, Clone)]
+pub struct VRHarnessFrame {
+    // Starts at 0
+    pub index: u64,
+    pub data: VRFrameData,
+    // Frames submitted during the step, usually one
+    pub submitted: Vec<MockVRFrame>,
+    // Events returned by the manager at the end of the step
+    pub events: Vec<VREvent>,
+}
+
+// Drives a VRServiceManager exposing a single mock display, one frame at a time.
+// The manual clock moves forward by exactly one vsync per step and the frame data is
+// resolved synchronously, so that render loops can be tested deterministically.
+pub struct VRFrameHarness {
+    manager: VRServiceManager,
+    clock: Arc<VRManualClock>,
+    display: VRDisplayPtr,
+    handle: MockVRDisplayHandle,
+    submitted: Receiver<MockVRFrame>,
+    near_z: f64,
+    far_z: f64,
+    frames: Vec<VRHarnessFrame>,
+}
+
+impl VRFrameHarness {
+    // The display starts presenting right away. The mock compositor is not used,
+    // since it resolves frames from its own thread.
+    pub fn new(mut init: MockVRInit) -> VRFrameHarness {
+        init.compositor = None;
+        let clock = Arc::new(VRManualClock::new(0.0));
+        let mut manager = VRServiceManager::new_with_clock(clock.clone());
+        let remote = manager.register_mock_with_displays();
+        // The faults are planned for the whole service
+        if let Some(plan) = init.faults.take() {
+            remote.set_faults(plan);
+        }
+        let (display_id, _, handle) = remote.add_display_with_handle(init);
+        let submitted = remote.capture_frames(display_id, false);
+        manager.initialize_services();
+        let display = manager.get_displays().into_iter()
+                             .find(|display| display.borrow().id() == display_id)
+                             .expect("Failed to create the mock display");
+        display.borrow_mut().start_present(None);
+
+        VRFrameHarness {
+            manager,
+            clock,
+            display,
+            handle,
+            submitted,
+            near_z: DEFAULT_NEAR,
+            far_z: DEFAULT_FAR,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn manager(&mut self) -> &mut VRServiceManager {
+        &mut self.manager
+    }
+
+    pub fn clock(&self) -> &VRManualClock {
+        &self.clock
+    }
+
+    pub fn display(&self) -> VRDisplayPtr {
+        self.display.clone()
+    }
+
+    // Reads the state of the display that the VRDisplay API doesn't expose
+    pub fn handle(&self) -> &MockVRDisplayHandle {
+        &self.handle
+    }
+
+    // Clip planes of the requested frame data
+    pub fn set_depth(&mut self, near_z: f64, far_z: f64) {
+        self.near_z = near_z;
+        self.far_z = far_z;
+    }
+
+    // Applies a control message right away, it shows in the frame data of the next step
+    pub fn send(&self, msg: MockVRControlMsg) {
+        self.handle.handle_msg(msg);
+    }
+
+    // Renders a frame that submits the default layer
+    #[allow(deprecated)]
+    pub fn step(&mut self) -> &VRHarnessFrame {
+        self.step_with(|display, _| {
+            display.render_layer(&VRLayer::default());
+            display.submit_frame();
+        })
+    }
+
+    // Requests the frame data of the next vsync and hands it to the render function,
+    // which is expected to submit the frame.
+    // Panics when the frame data isn't resolved, e.g. because of a StallFrame fault.
+    pub fn step_with<F>(&mut self, render: F) -> &VRHarnessFrame
+        where F: FnOnce(&mut dyn VRDisplay, &VRFrameData) {
+        let future = self.display.borrow_mut().future_frame_data(self.near_z, self.far_z);
+        // Without the compositor, the mock display resolves the frames it doesn't stall right away
+        if !future.is_resolved() {
+            panic!("The frame data of frame {} was not resolved, blocking on it would hang", self.frames.len());
+        }
+        let data = future.block();
+        render(&mut *self.display.borrow_mut(), &data);
+
+        let frame = VRHarnessFrame {
+            index: self.frames.len() as u64,
+            data,
+            submitted: self.submitted.try_iter().collect(),
+            events: self.manager.poll_events(),
+        };
+        self.frames.push(frame);
+        self.frames.last().unwrap()
+    }
+
+    // Renders the given number of frames with step
+    pub fn step_frames(&mut self, count: usize) -> &[VRHarnessFrame] {
+        let start = self.frames.len();
+        for _ in 0..count {
+            self.step();
+        }
+        &self.frames[start..]
+    }
+
+    // Every frame rendered so far
+    pub fn frames(&self) -> &[VRHarnessFrame] {
+        &self.frames
+    }
+}
@@ -1,9 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::mem;
+#[cfg(feature = "recording")]
+use std::env;
+use std::sync::Arc;
+use VRClockPtr;
+use VRDisplayEvent;
 use VRDisplayPtr;
 use VREvent;
+use VRGamepadEvent;
 use VRGamepadPtr;
+use VRIdAllocator;
+use VRIdAllocatorPtr;
+use VRMonotonicClock;
 use VRService;
+use VRServiceConfig;
 use VRServiceCreator;
+use VRServicePolicy;
+use VRServiceStatus;
+#[cfg(feature = "recording")]
+use vr_service_config::RECORD_ENV_VAR;
 
 #[cfg(target_os = "android")]
 #[cfg(feature = "googlevr")]
@@ -18,40 +34,141 @@ use api::OpenVRServiceCreator;
 use api::OculusVRServiceCreator;
 
 #[cfg(feature = "mock")]
-use api::{MockServiceCreator, MockVRControlMsg, MockVRInit};
+use api::{MockServiceCreator, MockVRControlMsg, MockVRInit, MockVRServiceRemote, find_mock_profile};
+
+#[cfg(feature = "mock-server")]
+use api::MockVRControlServer;
+
+#[cfg(feature = "plugin")]
+use api::PluginServiceCreator;
+#[cfg(feature = "ipc")]
+use api::{ProxyVRService, VRProxyRequest};
+#[cfg(feature = "recording")]
+use api::{RecordingVRService, VRRecorder, VRRecorderPtr};
+#[cfg(feature = "replay")]
+use api::{ReplayVRService, VRRecording, VRReplayMode, VRReplayRemote};
+#[cfg(any(feature = "plugin", feature = "recording"))]
+use std::path::Path;
 
 #[cfg(feature = "vrexternal")]
 use api::VRExternalShmemPtr;
 
+#[cfg(feature = "glwindow")]
+use api::{EventsLoopFactory, GlWindowVRMainThreadHeartbeat, GlWindowVRService};
+#[cfg(feature = "glwindow")]
+use gleam::gl::Gl;
+#[cfg(feature = "glwindow")]
+use glutin::{NotCurrent, WindowedContext};
+#[cfg(feature = "glwindow")]
+use std::rc::Rc;
+
 #[cfg(target_os = "android")]
 #[cfg(feature = "vrexternal")]
 use api::VRExternalServiceCreator;
 
+// Delay before retrying a service that failed to initialize, doubled after each failure
+const RETRY_INITIAL_DELAY: f64 = 1000.0;
+const RETRY_MAX_DELAY: f64 = 30000.0;
+
 // Single entry point all the VRServices and displays
 pub struct VRServiceManager {
     initialized: bool,
-    services: Vec<Box<dyn VRService>>,
-    displays: HashMap<u32, VRDisplayPtr>,
-    gamepads: HashMap<u32, VRGamepadPtr>
+    clock: VRClockPtr,
+    ids: VRIdAllocatorPtr,
+    services: Vec<VRServiceEntry>,
+    policy: VRServicePolicy,
+    // Services whose devices are exposed, in priority order
+    selected: Vec<usize>,
+    displays: HashMap<u32, VRDeviceEntry<VRDisplayPtr>>,
+    gamepads: HashMap<u32, VRDeviceEntry<VRGamepadPtr>>,
+    // Connect/Disconnect events generated by the manager, waiting for poll_events
+    events: Vec<VREvent>,
+    // Records the sessions of the services registered after record_to
+    #[cfg(feature = "recording")]
+    recorder: Option<VRRecorderPtr>,
+    // Window size of the glwindow service registered by register_glwindow, from the configuration
+    #[cfg(feature = "glwindow")]
+    window_size: Option<(u32, u32)>,
+}
+
+struct VRServiceEntry {
+    service: Box<dyn VRService>,
+    initialized: bool,
+    // Last error of initialize, cleared when it succeeds
+    last_error: Option<String>,
+    // Last error of fetch_displays or fetch_gamepads, cleared when they succeed.
+    // Unlike initialization errors, they don't trigger retries.
+    last_fetch_error: Option<String>,
+    failed_attempts: u32,
+    // Clock time after which a failed service can be initialized again
+    next_retry: f64,
+}
+
+impl VRServiceEntry {
+    fn initialize(&mut self, now: f64) -> bool {
+        match self.service.initialize() {
+            Ok(()) => {
+                self.initialized = true;
+                self.last_error = None;
+                self.failed_attempts = 0;
+            }
+            Err(msg) => {
+                error!("Error initializing VRService {}: {:?}", self.service.name(), msg);
+                let delay = RETRY_INITIAL_DELAY * 2f64.powi(self.failed_attempts.min(16) as i32);
+                self.initialized = false;
+                self.last_error = Some(msg);
+                self.failed_attempts += 1;
+                self.next_retry = now + delay.min(RETRY_MAX_DELAY);
+            }
+        }
+        self.initialized
+    }
+}
+
+// A display or gamepad along with the index of the service that exposes it
+struct VRDeviceEntry<T> {
+    service: usize,
+    device: T,
 }
 
 unsafe impl Send for VRServiceManager {}
 
 impl Drop for VRServiceManager {
      fn drop(&mut self) {
+         self.events.clear();
          self.gamepads.clear();
          self.displays.clear();
          self.services.clear();
      }
 }
 
+impl Default for VRServiceManager {
+    fn default() -> VRServiceManager {
+        VRServiceManager::new()
+    }
+}
+
 impl VRServiceManager {
     pub fn new() -> VRServiceManager {
+        VRServiceManager::new_with_clock(Arc::new(VRMonotonicClock::new()))
+    }
+
+    // Creates a manager whose services stamp frames and gamepads using the provided clock
+    pub fn new_with_clock(clock: VRClockPtr) -> VRServiceManager {
         VRServiceManager {
             initialized: false,
+            clock,
+            ids: Arc::new(VRIdAllocator::new()),
             services: Vec::new(),
+            policy: VRServicePolicy::default(),
+            selected: Vec::new(),
             displays: HashMap::new(),
-            gamepads: HashMap::new()
+            gamepads: HashMap::new(),
+            events: Vec::new(),
+            #[cfg(feature = "recording")]
+            recorder: None,
+            #[cfg(feature = "glwindow")]
+            window_size: None,
         }
     }
 
@@ -70,7 +187,95 @@ impl VRServiceManager {
         );
         
         for creator in &creators {
-            self.register(creator.new_service());
+            self.register(creator.new_service(self.clock.clone(), self.ids.clone()));
+        }
+    }
+
+    // Registers the services listed in the configuration, in order, and applies its policy.
+    // The default services are registered when the configuration doesn't list any.
+    // glwindow, magicleap and vrexternal need resources owned by the embedder, so they
+    // must still be registered explicitly, e.g. with register_glwindow.
+    // Options that the service doesn't support are reported as errors and ignored.
+    pub fn register_config(&mut self, mut config: VRServiceConfig) {
+        if config.services.is_empty() {
+            self.register_defaults();
+        }
+
+        for entry in &mut config.services {
+            for option in entry.unsupported_options() {
+                error!("VRService {} doesn't support the {} option", entry.name, option);
+            }
+            match entry.name.as_str() {
+                #[cfg(target_os = "windows")]
+                #[cfg(feature = "openvr")]
+                "openvr" => {
+                    let service = OpenVRServiceCreator::new().new_service(self.clock.clone(), self.ids.clone());
+                    self.register(service);
+                }
+                #[cfg(target_os = "android")]
+                #[cfg(feature = "googlevr")]
+                "googlevr" => {
+                    let service = GoogleVRServiceCreator::new().new_service(self.clock.clone(), self.ids.clone());
+                    self.register(service);
+                }
+                #[cfg(target_os = "android")]
+                #[cfg(feature = "oculusvr")]
+                "oculusvr" => {
+                    let service = OculusVRServiceCreator::new().new_service(self.clock.clone(), self.ids.clone());
+                    self.register(service);
+                }
+                #[cfg(feature = "mock")]
+                "mock" => {
+                    let mut init = entry.mock.take().unwrap_or_default();
+                    if let Some(ref name) = entry.mock_profile {
+                        match find_mock_profile(name) {
+                            Ok(profile) => init.profile = Some(profile),
+                            Err(msg) => error!("Error loading mock profile {}: {}", name, msg),
+                        }
+                    }
+                    #[cfg(feature = "mock-server")]
+                    {
+                        if let Some(ref address) = entry.mock_server {
+                            match self.register_mock_with_control_server(init, address) {
+                                Ok(address) => info!("Mock control server listening on {}", address),
+                                Err(msg) => error!("Error starting the mock control server on {}: {}", address, msg),
+                            }
+                            continue;
+                        }
+                    }
+                    let service = MockServiceCreator::new_service_with_init(init, self.clock.clone(), self.ids.clone());
+                    self.register(service);
+                }
+                #[cfg(feature = "glwindow")]
+                "glwindow" => {
+                    self.window_size = entry.window_size;
+                }
+                "magicleap" | "vrexternal" => {}
+                name => warn!("VRService {} is not available in this build", name),
+            }
+        }
+
+        self.set_policy(config.policy());
+    }
+
+    // Registers the services configured through the RUST_WEBVR_CONFIG or RUST_WEBVR_SERVICES
+    // environment variables, or the default ones when none of them is set.
+    pub fn register_from_env(&mut self) {
+        #[cfg(feature = "recording")]
+        {
+            if let Ok(path) = env::var(RECORD_ENV_VAR) {
+                if let Err(msg) = self.record_to(&path) {
+                    error!("Error recording VR session to {}: {}", path, msg);
+                }
+            }
+        }
+        match VRServiceConfig::from_env() {
+            Ok(Some(config)) => self.register_config(config),
+            Ok(None) => self.register_defaults(),
+            Err(msg) => {
+                error!("Error loading VRService config: {}", msg);
+                self.register_defaults();
+            }
         }
     }
 
@@ -79,7 +284,7 @@ impl VRServiceManager {
     #[cfg(feature = "vrexternal")]
     pub fn register_vrexternal(&mut self, ptr: VRExternalShmemPtr) {
         let creator = VRExternalServiceCreator::new(ptr);
-        self.register(creator.new_service());
+        self.register(creator.new_service(self.clock.clone(), self.ids.clone()));
     }
 
     // Register VRExternal service.
@@ -89,111 +294,423 @@ impl VRServiceManager {
         unimplemented!();
     }
 
+    // Register the glwindow service, resizing the window to the size of the configuration if any.
+    // This function should be called from the main thread.
+    #[cfg(feature = "glwindow")]
+    pub fn register_glwindow(&mut self,
+                             name: String,
+                             gl_context: WindowedContext<NotCurrent>,
+                             events_loop_factory: EventsLoopFactory,
+                             gl: Rc<dyn Gl>)
+                             -> GlWindowVRMainThreadHeartbeat {
+        let ids = self.ids.clone();
+        let (service, heartbeat) = match self.window_size {
+            Some(size) => GlWindowVRService::new_with_window_size(name, gl_context, events_loop_factory, gl, ids, size),
+            None => GlWindowVRService::new(name, gl_context, events_loop_factory, gl, ids),
+        };
+        self.register(Box::new(service));
+        heartbeat
+    }
+
     // Register mock VR Service
     // Usefull for testing
     #[cfg(feature = "mock")]
     pub fn register_mock(&mut self) {
         let creator = MockServiceCreator::new();
-        self.register(creator.new_service());
+        self.register(creator.new_service(self.clock.clone(), self.ids.clone()));
     }
 
     // Register mock VR Service
     // Usefull for testing
     #[cfg(feature = "mock")]
     pub fn register_mock_with_remote(&mut self, init: MockVRInit) -> std::sync::mpsc::Sender<MockVRControlMsg> {
-        let (service, remote) = MockServiceCreator::new_service_with_remote(init);
+        let (service, remote) = MockServiceCreator::new_service_with_remote(init, self.clock.clone(), self.ids.clone());
         self.register(service);
         remote
     }
 
+    // Register mock VR Service controlled from another process,
+    // e.g. by a test driver
+    #[cfg(all(feature = "mock", feature = "ipc"))]
+    pub fn register_mock_with_ipc_remote(&mut self, init: MockVRInit)
+                                         -> Result<ipc_channel::ipc::IpcSender<MockVRControlMsg>, String> {
+        let (service, remote) = MockServiceCreator::new_service_with_ipc_remote(init, self.clock.clone(), self.ids.clone())?;
+        self.register(service);
+        Ok(remote)
+    }
+
+    // Register a mock VR Service whose displays are added and removed at runtime
+    // Usefull for testing
+    #[cfg(feature = "mock")]
+    pub fn register_mock_with_displays(&mut self) -> MockVRServiceRemote {
+        let (service, remote) = MockServiceCreator::new_service_with_displays(self.clock.clone(), self.ids.clone());
+        self.register(service);
+        remote
+    }
+
+    // Register a mock VR Service driven by scripts through a MockVRControlServer listening
+    // on "tcp:<port>" or "unix:<path>". Returns the address actually listened on.
+    #[cfg(feature = "mock-server")]
+    pub fn register_mock_with_control_server(&mut self, mut init: MockVRInit, address: &str) -> Result<String, String> {
+        let (service, remote) = MockServiceCreator::new_service_with_displays(self.clock.clone(), self.ids.clone());
+        if let Some(plan) = init.faults.take() {
+            remote.set_faults(plan);
+        }
+        let server = MockVRControlServer::new(remote);
+        server.add_display(init);
+        let address = server.listen(address)?;
+        self.register(service);
+        Ok(address)
+    }
+
+    // Register the service served by a VRProxyServer, usually from another process
+    #[cfg(feature = "ipc")]
+    pub fn register_proxy(&mut self, sender: ipc_channel::ipc::IpcSender<VRProxyRequest>) -> Result<(), String> {
+        let service = ProxyVRService::connect(sender, self.ids.clone())?;
+        self.register(Box::new(service));
+        Ok(())
+    }
+
+    // Loads a VR service from a shared library implementing the plugin ABI
+    #[cfg(feature = "plugin")]
+    pub fn load_plugin<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        let creator = PluginServiceCreator::load(path)?;
+        self.register(creator.new_service(self.clock.clone(), self.ids.clone()));
+        Ok(())
+    }
+
+    // Records the sessions of the services registered from now on to a file,
+    // which can be played back by the replay service
+    #[cfg(feature = "recording")]
+    pub fn record_to<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        self.recorder = Some(VRRecorder::create(path, self.clock.clone())?);
+        Ok(())
+    }
+
+    // Register a service playing back a session recorded with record_to.
+    // The remote steps the replay in single step mode.
+    #[cfg(feature = "replay")]
+    pub fn register_replay<P: AsRef<Path>>(&mut self, path: P, mode: VRReplayMode) -> Result<VRReplayRemote, String> {
+        let recording = VRRecording::open(path)?;
+        let (service, remote) = ReplayVRService::new(recording, mode, self.clock.clone(), self.ids.clone());
+        self.register(Box::new(service));
+        Ok(remote)
+    }
+
     // Register a new VR service
     pub fn register(&mut self, service: Box<dyn VRService>) {
-        self.services.push(service);
+        #[cfg(feature = "recording")]
+        let service = match self.recorder {
+            Some(ref recorder) => Box::new(RecordingVRService::new(service, recorder.clone())),
+            None => service,
+        };
+        self.services.push(VRServiceEntry {
+            service,
+            initialized: false,
+            last_error: None,
+            last_fetch_error: None,
+            failed_attempts: 0,
+            next_retry: 0.0,
+        });
+    }
+
+    // Sets the policy used to choose which services expose their devices.
+    // Takes effect on the next get_displays or refresh_devices call.
+    pub fn set_policy(&mut self, policy: VRServicePolicy) {
+        self.policy = policy;
+    }
+
+    pub fn policy(&self) -> &VRServicePolicy {
+        &self.policy
     }
     
-    // Initializes all the services
+    // Initializes all the services allowed by the policy
     pub fn initialize_services(&mut self) {
-        if self.initialized {
-            return;
+        let now = self.clock.now();
+        for entry in &mut self.services {
+            // Services that failed are only initialized again by retry_failed_services
+            if entry.initialized || entry.failed_attempts > 0 || self.policy.is_denied(entry.service.name()) {
+                continue;
+            }
+            entry.initialize(now);
         }
+        self.initialized = true;
+    }
 
-        for service in &mut self.services {
-            if let Err(msg) = service.initialize() {
-                error!("Error initializing VRService: {:?}", msg);
+    // Initializes again the services that failed, once their backoff delay has elapsed.
+    // This allows to connect to a VR runtime launched after the application.
+    // Returns true if any service got initialized, its devices are then enumerated and
+    // reported by the next poll_events call.
+    pub fn retry_failed_services(&mut self) -> bool {
+        if !self.initialized {
+            self.initialize_services();
+        }
+
+        let now = self.clock.now();
+        let mut recovered = false;
+        for entry in &mut self.services {
+            if entry.initialized || entry.failed_attempts == 0 || now < entry.next_retry ||
+               self.policy.is_denied(entry.service.name()) {
+                continue;
             }
+            recovered |= entry.initialize(now);
         }
-        self.initialized = true;
+
+        if recovered {
+            self.refresh_devices();
+        }
+        recovered
+    }
+
+    // Returns the status of every registered service, in registration order
+    pub fn service_status(&self) -> Vec<VRServiceStatus> {
+        self.services.iter().enumerate().map(|(index, entry)| {
+            VRServiceStatus {
+                name: entry.service.name().into(),
+                available: entry.service.is_available(),
+                initialized: entry.initialized,
+                denied: self.policy.is_denied(entry.service.name()),
+                last_error: entry.last_error.clone(),
+                last_fetch_error: entry.last_fetch_error.clone(),
+                failed_attempts: entry.failed_attempts,
+                display_count: self.displays.values().filter(|d| d.service == index).count(),
+                gamepad_count: self.gamepads.values().filter(|g| g.service == index).count(),
+            }
+        }).collect()
     }
 
     pub fn get_displays(&mut self) -> Vec<VRDisplayPtr> {
         self.fetch_displays();
-        let mut result = Vec::new();
-        for (_, display) in &self.displays {
-            result.push(display.clone());
-        }
-        // Sort by display_id to match service initialization order
-        result.sort_by(|a, b| a.borrow().id().cmp(&b.borrow().id()));
-        result
+        let mut result: Vec<_> = self.displays.iter().map(|(id, entry)| (self.rank(entry.service), *id, entry.device.clone())).collect();
+        // Sort by service priority and then by display_id to match service initialization order
+        result.sort_by_key(|&(rank, id, _)| (rank, id));
+        result.into_iter().map(|(_, _, display)| display).collect()
     }
 
     pub fn get_gamepads(&mut self) -> Vec<VRGamepadPtr> {
         self.fetch_gamepads();
-        let mut result = Vec::new();
-        for (_, gamepad) in &self.gamepads {
-            result.push(gamepad.clone());
-        }
-        // Sort by gamepad_id to match service initialization order
-        result.sort_by(|a, b| a.borrow().id().cmp(&b.borrow().id()));
-        result
+        let mut result: Vec<_> = self.gamepads.iter().map(|(id, entry)| (self.rank(entry.service), *id, entry.device.clone())).collect();
+        // Sort by service priority and then by gamepad_id to match service initialization order
+        result.sort_by_key(|&(rank, id, _)| (rank, id));
+        result.into_iter().map(|(_, _, gamepad)| gamepad).collect()
     }
 
     pub fn get_display(&self, display_id: u32) -> Option<&VRDisplayPtr> {
-        self.displays.get(&display_id)
+        self.displays.get(&display_id).map(|entry| &entry.device)
+    }
+
+    // Re-enumerates the displays and gamepads of every service.
+    // Devices that appeared or went away are reported by the next poll_events call.
+    pub fn refresh_devices(&mut self) {
+        self.fetch_displays();
+        self.fetch_gamepads();
     }
 
+    // Returns the pending events of all the services.
+    // Connect and Disconnect events are emitted by the manager itself, so that each
+    // device is announced exactly once and get_displays/get_gamepads stay in sync with them.
     pub fn poll_events(&mut self) -> Vec<VREvent> {
-        let mut events = Vec::new();
-        for service in &mut self.services {
-            events.append(&mut service.poll_events());
+        let mut service_events = Vec::new();
+        for index in self.service_order() {
+            let selected = self.selected.contains(&index);
+            for event in self.services[index].service.poll_events() {
+                service_events.push((selected, event));
+            }
+        }
+
+        let mut refresh = false;
+        for (selected, event) in service_events {
+            match event {
+                // A device plugged into a service that is not selected may change the selection
+                VREvent::Display(VRDisplayEvent::Connect(_)) if !selected => refresh = true,
+                _ if !selected => {}
+                VREvent::Display(VRDisplayEvent::Connect(data)) => {
+                    // Known displays were already announced when they were enumerated
+                    refresh |= !self.displays.contains_key(&data.display_id);
+                }
+                VREvent::Display(VRDisplayEvent::Disconnect(display_id)) => {
+                    if self.displays.remove(&display_id).is_some() {
+                        self.events.push(VRDisplayEvent::Disconnect(display_id).into());
+                    }
+                }
+                VREvent::Gamepad(VRGamepadEvent::Connect(_, state)) => {
+                    refresh |= !self.gamepads.contains_key(&state.gamepad_id);
+                }
+                VREvent::Gamepad(VRGamepadEvent::Disconnect(gamepad_id)) => {
+                    if self.gamepads.remove(&gamepad_id).is_some() {
+                        self.events.push(VRGamepadEvent::Disconnect(gamepad_id).into());
+                    }
+                }
+                event => self.events.push(event),
+            }
         }
-        events
+
+        if refresh && self.initialized {
+            self.refresh_devices();
+        }
+
+        mem::take(&mut self.events)
     }
 
     pub fn is_initialized(&self) -> bool {
         self.initialized
     }
+
+    // Returns the clock shared with the registered services
+    pub fn clock(&self) -> VRClockPtr {
+        self.clock.clone()
+    }
+
+    // Returns the allocator used to assign display and gamepad ids in this manager.
+    // Services created outside of the manager should use it to get their ids.
+    pub fn id_allocator(&self) -> VRIdAllocatorPtr {
+        self.ids.clone()
+    }
 }
 
 impl VRServiceManager {
+    // Indices of the services allowed by the policy, the preferred ones first
+    fn service_order(&self) -> Vec<usize> {
+        let policy = &self.policy;
+        let services = &self.services;
+        let mut order: Vec<usize> = (0..services.len()).filter(|&i| !policy.is_denied(services[i].service.name()))
+                                                       .collect();
+        order.sort_by_key(|&i| {
+            let service = &services[i].service;
+            (policy.prefer_hardware && !service.is_hardware(), policy.priority(service.name()))
+        });
+        order
+    }
+
+    fn rank(&self, service: usize) -> usize {
+        self.selected.iter().position(|&i| i == service).unwrap_or(self.selected.len())
+    }
+
     fn fetch_displays(&mut self) {
         self.initialize_services();
 
-        for service in &mut self.services {
-            let displays = service.fetch_displays();
-            if let Ok(displays) = displays {
-                for display in displays {
-                    let key = display.borrow().id();
-                    if !self.displays.contains_key(&key) {
-                        self.displays.insert(key, display.clone());
-                    }
+        // Enumerate the services in priority order and select the ones whose displays are exposed
+        let mut selected = Vec::new();
+        let mut found = HashMap::new();
+        let mut hardware_found = false;
+        for index in self.service_order() {
+            let entry = &mut self.services[index];
+            if hardware_found && !entry.service.is_hardware() {
+                // Simulated services are only used as a fallback
+                break;
+            }
+            let has_displays = match entry.service.fetch_displays() {
+                Ok(displays) => {
+                    entry.last_fetch_error = None;
+                    let connected: Vec<_> = displays.into_iter().filter_map(|display| {
+                        let data = display.borrow().data();
+                        if data.connected { Some((data, display)) } else { None }
+                    }).collect();
+                    let has_displays = !connected.is_empty();
+                    found.insert(index, connected);
+                    has_displays
+                }
+                // Keep the known displays when a service fails, the failure may be transient
+                Err(msg) => {
+                    entry.last_fetch_error = Some(msg);
+                    self.displays.values().any(|display| display.service == index)
+                }
+            };
+            selected.push(index);
+            if has_displays {
+                if self.policy.exclusive {
+                    break;
+                }
+                hardware_found |= self.policy.prefer_hardware && entry.service.is_hardware();
+            }
+        }
+
+        // Prune the displays that went away or whose service is not selected anymore
+        let found_ids: HashSet<u32> = found.values().flat_map(|displays| displays.iter().map(|d| d.0.display_id)).collect();
+        let stale: Vec<u32> = self.displays.iter()
+                                           .filter(|&(id, entry)| if found.contains_key(&entry.service) {
+                                               !found_ids.contains(id)
+                                           } else {
+                                               !selected.contains(&entry.service)
+                                           })
+                                           .map(|(id, _)| *id)
+                                           .collect();
+        for display_id in stale {
+            self.displays.remove(&display_id);
+            self.events.push(VRDisplayEvent::Disconnect(display_id).into());
+        }
+
+        for &index in &selected {
+            for (data, display) in found.remove(&index).unwrap_or_default() {
+                if let Entry::Vacant(entry) = self.displays.entry(data.display_id) {
+                    entry.insert(VRDeviceEntry {
+                        service: index,
+                        device: display,
+                    });
+                    self.events.push(VRDisplayEvent::Connect(data).into());
                 }
             }
         }
+
+        self.selected = selected;
     }
 
     fn fetch_gamepads(&mut self) {
-        self.initialize_services();
+        if !self.initialized {
+            self.fetch_displays();
+        }
 
-        for service in &mut self.services {
-            let gamepads = service.fetch_gamepads();
-            if let Ok(gamepads) = gamepads {
-                for gamepad in gamepads {
-                    let key = gamepad.borrow().id();
-                    if !self.gamepads.contains_key(&key) {
-                        self.gamepads.insert(key, gamepad.clone());
-                    }
+        for &index in &self.selected {
+            // Keep the known gamepads when a service fails, the failure may be transient
+            let entry = &mut self.services[index];
+            let gamepads = match entry.service.fetch_gamepads() {
+                Ok(gamepads) => {
+                    entry.last_fetch_error = None;
+                    gamepads
+                }
+                Err(msg) => {
+                    entry.last_fetch_error = Some(msg);
+                    continue;
+                }
+            };
+
+            let mut found = HashSet::new();
+            for gamepad in gamepads {
+                let state = gamepad.borrow().state();
+                if !state.connected {
+                    continue;
+                }
+                found.insert(state.gamepad_id);
+                if let Entry::Vacant(entry) = self.gamepads.entry(state.gamepad_id) {
+                    let data = gamepad.borrow().data();
+                    entry.insert(VRDeviceEntry {
+                        service: index,
+                        device: gamepad.clone(),
+                    });
+                    self.events.push(VRGamepadEvent::Connect(data, state).into());
                 }
             }
+
+            // Prune the gamepads that the service doesn't report anymore
+            let stale: Vec<u32> = self.gamepads.iter()
+                                               .filter(|&(id, entry)| entry.service == index && !found.contains(id))
+                                               .map(|(id, _)| *id)
+                                               .collect();
+            for gamepad_id in stale {
+                self.gamepads.remove(&gamepad_id);
+                self.events.push(VRGamepadEvent::Disconnect(gamepad_id).into());
+            }
+        }
+
+        // Prune the gamepads of the services that are not selected anymore
+        let selected = &self.selected;
+        let stale: Vec<u32> = self.gamepads.iter()
+                                           .filter(|&(_, entry)| !selected.contains(&entry.service))
+                                           .map(|(id, _)| *id)
+                                           .collect();
+        for gamepad_id in stale {
+            self.gamepads.remove(&gamepad_id);
+            self.events.push(VRGamepadEvent::Disconnect(gamepad_id).into());
         }
     }
 }
@@ -0,0 +1,172 @@
+use std::env;
+use VRServicePolicy;
+use rust_webvr_api::MockVRInit;
+
+#[cfg(feature = "config")]
+use std::fs::File;
+#[cfg(feature = "config")]
+use std::io::Read;
+#[cfg(feature = "config")]
+use std::path::Path;
+
+// Comma separated list of the services to register, in priority order (e.g. "mock,openvr")
+const SERVICES_ENV_VAR: &str = "RUST_WEBVR_SERVICES";
+// Address of the mock control server, e.g. "tcp:4000", overrides the configuration.
+// Requires the "mock-server" feature.
+const MOCK_SERVER_ENV_VAR: &str = "RUST_WEBVR_MOCK_SERVER";
+// Path to a TOML or JSON configuration file. Requires the "config" feature.
+#[cfg(feature = "config")]
+const CONFIG_ENV_VAR: &str = "RUST_WEBVR_CONFIG";
+// Path of the file the sessions are recorded to, read by VRServiceManager::register_from_env.
+// Requires the "recording" feature.
+#[cfg(feature = "recording")]
+pub(crate) const RECORD_ENV_VAR: &str = "RUST_WEBVR_RECORD";
+
+// Describes which services the VRServiceManager registers, in which order and with which parameters.
+//
+// Example TOML file:
+//
+//     prefer_hardware = true
+//
+//     [[services]]
+//     name = "openvr"
+//
+//     [[services]]
+//     name = "mock"
+//     mock = { eye_level = 1.6 }
+//     mock_profile = "quest"
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "config", derive(Deserialize))]
+#[cfg_attr(feature = "config", serde(default))]
+pub struct VRServiceConfig {
+    // Services to register, the first ones have the highest priority
+    pub services: Vec<VRServiceConfigEntry>,
+    pub prefer_hardware: bool,
+    pub exclusive: bool,
+    pub deny: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "config", derive(Deserialize))]
+#[cfg_attr(feature = "config", serde(default))]
+pub struct VRServiceConfigEntry {
+    // Name of the service, as returned by VRService::name
+    pub name: String,
+    // Initial state of the mock display
+    pub mock: Option<MockVRInit>,
+    // Device profile of the mock display: a built-in profile name or the path of a profile file
+    pub mock_profile: Option<String>,
+    // Address the mock control server listens on: "tcp:<port>" or "unix:<path>"
+    pub mock_server: Option<String>,
+    // Window size of the glwindow display, in physical pixels
+    pub window_size: Option<(u32, u32)>,
+}
+
+impl VRServiceConfig {
+    // Reads the configuration from the RUST_WEBVR_CONFIG file or the RUST_WEBVR_SERVICES list.
+    // Returns None when none of them is set.
+    pub fn from_env() -> Result<Option<VRServiceConfig>, String> {
+        let mut config = VRServiceConfig::from_env_vars()?;
+        if let (Some(config), Ok(address)) = (config.as_mut(), env::var(MOCK_SERVER_ENV_VAR)) {
+            for entry in config.services.iter_mut().filter(|entry| entry.name == "mock") {
+                entry.mock_server = Some(address.clone());
+            }
+        }
+        Ok(config)
+    }
+
+    fn from_env_vars() -> Result<Option<VRServiceConfig>, String> {
+        #[cfg(feature = "config")]
+        {
+            if let Some(path) = env::var_os(CONFIG_ENV_VAR) {
+                return VRServiceConfig::from_file(path).map(Some);
+            }
+        }
+        match env::var(SERVICES_ENV_VAR) {
+            Ok(list) => Ok(Some(VRServiceConfig::from_list(&list))),
+            Err(env::VarError::NotPresent) => Ok(None),
+            Err(e) => Err(format!("Invalid {}: {}", SERVICES_ENV_VAR, e)),
+        }
+    }
+
+    // Parses a comma separated list of service names, e.g. "mock,openvr"
+    pub fn from_list(list: &str) -> VRServiceConfig {
+        let services = list.split(',')
+                           .map(|name| name.trim())
+                           .filter(|name| !name.is_empty())
+                           .map(|name| VRServiceConfigEntry {
+                               name: name.into(),
+                               .. Default::default()
+                           })
+                           .collect();
+        VRServiceConfig {
+            services,
+            .. Default::default()
+        }
+    }
+
+    // Loads a TOML or JSON file, depending on its extension
+    #[cfg(feature = "config")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<VRServiceConfig, String> {
+        let path = path.as_ref();
+        let mut contents = String::new();
+        File::open(path).and_then(|mut file| file.read_to_string(&mut contents))
+                        .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => VRServiceConfig::from_toml(&contents),
+            Some("json") => VRServiceConfig::from_json(&contents),
+            _ => Err(format!("Unknown config format: {}", path.display())),
+        }
+    }
+
+    #[cfg(feature = "config")]
+    pub fn from_toml(contents: &str) -> Result<VRServiceConfig, String> {
+        toml::from_str(contents).map_err(|e| e.to_string())
+    }
+
+    #[cfg(feature = "config")]
+    pub fn from_json(contents: &str) -> Result<VRServiceConfig, String> {
+        serde_json::from_str(contents).map_err(|e| e.to_string())
+    }
+
+    // Policy that follows the order and the filters of the configuration
+    pub fn policy(&self) -> VRServicePolicy {
+        VRServicePolicy {
+            priorities: self.services.iter().map(|entry| entry.name.clone()).collect(),
+            prefer_hardware: self.prefer_hardware,
+            exclusive: self.exclusive,
+            deny: self.deny.iter().cloned().collect(),
+        }
+    }
+
+    pub fn service(&self, name: &str) -> Option<&VRServiceConfigEntry> {
+        self.services.iter().find(|entry| entry.name == name)
+    }
+
+    // Size requested for the glwindow display, applied by VRServiceManager::register_glwindow
+    pub fn window_size(&self) -> Option<(u32, u32)> {
+        self.service("glwindow").and_then(|entry| entry.window_size)
+    }
+}
+
+impl VRServiceConfigEntry {
+    // Options that are set but don't apply to this service
+    pub fn unsupported_options(&self) -> Vec<&'static str> {
+        let mut options = Vec::new();
+        if self.name != "mock" {
+            if self.mock.is_some() {
+                options.push("mock");
+            }
+            if self.mock_profile.is_some() {
+                options.push("mock_profile");
+            }
+            if self.mock_server.is_some() {
+                options.push("mock_server");
+            }
+        }
+        if self.name != "glwindow" && self.window_size.is_some() {
+            options.push("window_size");
+        }
+        options
+    }
+}
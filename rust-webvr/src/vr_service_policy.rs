@@ -0,0 +1,34 @@
+use std::collections::HashSet;
+
+// Controls which of the registered services expose their devices through the VRServiceManager.
+// Services are identified by the name returned by VRService::name (e.g. "openvr", "mock").
+#[derive(Debug, Clone, Default)]
+pub struct VRServicePolicy {
+    // Services listed first are preferred. Unlisted services come after them in registration order.
+    pub priorities: Vec<String>,
+    // Only use services backed by simulated devices (mock, glwindow) when no hardware display is found.
+    pub prefer_hardware: bool,
+    // Stop after the first service that exposes at least one display.
+    pub exclusive: bool,
+    // Services that are never initialized nor queried.
+    pub deny: HashSet<String>,
+}
+
+impl VRServicePolicy {
+    // Prefer real hardware and fall back to the simulated services.
+    pub fn prefer_hardware() -> VRServicePolicy {
+        VRServicePolicy {
+            prefer_hardware: true,
+            .. Default::default()
+        }
+    }
+
+    pub fn is_denied(&self, name: &str) -> bool {
+        self.deny.contains(name)
+    }
+
+    // Lower values come first
+    pub fn priority(&self, name: &str) -> usize {
+        self.priorities.iter().position(|p| p == name).unwrap_or(self.priorities.len())
+    }
+}
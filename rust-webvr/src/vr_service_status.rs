@@ -0,0 +1,20 @@
+// Snapshot of a service registered in the VRServiceManager
+#[derive(Debug, Clone)]
+pub struct VRServiceStatus {
+    pub name: String,
+    // Result of VRService::is_available
+    pub available: bool,
+    // Whether the last call to VRService::initialize succeeded
+    pub initialized: bool,
+    // Whether the service was skipped because of the VRServicePolicy deny-list
+    pub denied: bool,
+    // Last error returned by VRService::initialize, None once it succeeded
+    pub last_error: Option<String>,
+    // Last error returned when fetching the displays or gamepads of the service.
+    // The service stays initialized and its known devices are kept.
+    pub last_fetch_error: Option<String>,
+    // Number of failed initialization attempts since the last success
+    pub failed_attempts: u32,
+    pub display_count: usize,
+    pub gamepad_count: usize,
+}
@@ -0,0 +1,48 @@
+extern crate rust_webvr;
+
+use rust_webvr::{MockVRInit, VRServiceManager};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+
+fn add_display<S: Read + Write>(stream: S) -> String {
+    let mut reader = BufReader::new(stream);
+    writeln!(reader.get_mut(), "{{\"AddDisplay\": {{\"serial\": \"second\"}}}}").unwrap();
+    let mut reply = String::new();
+    reader.read_line(&mut reply).unwrap();
+    reply
+}
+
+#[test]
+fn tcp_clients_add_displays() {
+    let mut manager = VRServiceManager::new();
+    let address = manager.register_mock_with_control_server(MockVRInit::default(), "tcp:0").unwrap();
+    let port = address.strip_prefix("tcp:").unwrap().parse::<u16>().unwrap();
+
+    let reply = add_display(TcpStream::connect(("127.0.0.1", port)).unwrap());
+    assert!(reply.starts_with("{\"DisplayAdded\":"));
+    assert_eq!(manager.get_displays().len(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn stale_unix_sockets_are_replaced() {
+    use std::env;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = env::temp_dir().join(format!("rust-webvr-control-{}.sock", std::process::id()));
+    // Left behind by a server that went away
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let mut manager = VRServiceManager::new();
+    let address = format!("unix:{}", path.display());
+    assert_eq!(manager.register_mock_with_control_server(MockVRInit::default(), &address).unwrap(), address);
+
+    let reply = add_display(UnixStream::connect(&path).unwrap());
+    assert!(reply.starts_with("{\"DisplayAdded\":"));
+
+    // A live server isn't replaced
+    let mut other = VRServiceManager::new();
+    assert!(other.register_mock_with_control_server(MockVRInit::default(), &address).is_err());
+    let _ = std::fs::remove_file(&path);
+}
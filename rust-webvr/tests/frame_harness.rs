@@ -0,0 +1,37 @@
+extern crate rust_webvr;
+
+use rust_webvr::api::mock_profile;
+use rust_webvr::{MockVRControlMsg, MockVRFaultKind, MockVRFaultPlan, MockVRFaultTrigger, MockVRInit};
+use rust_webvr::{VRClock, VRDisplayEvent, VREvent, VRFrameHarness};
+
+#[test]
+fn step_renders_one_vsync() {
+    let profile = mock_profile("vive").unwrap();
+    let vsync = 1000.0 / profile.refresh_rate;
+    let mut harness = VRFrameHarness::new(MockVRInit { profile: Some(profile), .. Default::default() });
+    harness.step();
+    let start = harness.clock().now();
+
+    harness.send(MockVRControlMsg::SetViewerPose([1.0, 1.6, -2.0], [0.0, 0.0, 0.0, 1.0]));
+    harness.send(MockVRControlMsg::Blur);
+    let now = start + vsync;
+    let frame = harness.step();
+    assert_eq!(frame.index, 1);
+    assert_eq!(frame.data.timestamp, now);
+    assert_eq!(frame.data.pose.position, Some([1.0, 1.6, -2.0]));
+    assert_eq!(frame.submitted.len(), 1);
+    assert_eq!(frame.submitted[0].timestamp, now);
+    assert_eq!(frame.events.len(), 1);
+    assert!(matches!(frame.events[0], VREvent::Display(VRDisplayEvent::Blur(_))));
+    assert_eq!(harness.clock().now(), now);
+}
+
+#[test]
+#[should_panic(expected = "The frame data of frame 0 was not resolved")]
+fn stalled_frames_panic() {
+    let init = MockVRInit {
+        faults: Some(MockVRFaultPlan::single(MockVRFaultKind::StallFrame, MockVRFaultTrigger::Always)),
+        .. Default::default()
+    };
+    VRFrameHarness::new(init).step();
+}
@@ -0,0 +1,38 @@
+extern crate rust_webvr;
+
+use rust_webvr::{MockVRControlMsg, MockVREntityKind, MockVRInit, MockVRRegion, MockVRWorld, VRServiceManager};
+use std::f32;
+
+// Two squares facing the ray, at z = -1 and z = -2
+fn world() -> MockVRWorld {
+    let square = |z: f32| vec![
+        [[-1.0, -1.0, z], [1.0, -1.0, z], [1.0, 1.0, z]],
+        [[-1.0, -1.0, z], [1.0, 1.0, z], [-1.0, 1.0, z]],
+    ];
+    MockVRWorld {
+        regions: vec![
+            MockVRRegion { faces: square(-2.0), kind: MockVREntityKind::Mesh },
+            MockVRRegion { faces: square(-1.0), kind: MockVREntityKind::Plane },
+        ],
+    }
+}
+
+#[test]
+fn hits_are_sorted_and_non_finite_rays_ignored() {
+    let mut manager = VRServiceManager::new();
+    let remote = manager.register_mock_with_displays();
+    let (_, _, handle) = remote.add_display_with_handle(MockVRInit::default());
+    // Creates the display
+    manager.get_displays();
+    handle.handle_msg(MockVRControlMsg::SetWorld(Box::new(world())));
+
+    let hits = handle.hit_test([0.1, 0.2, 0.0], [0.0, 0.0, -1.0]);
+    let distances: Vec<f32> = hits.iter().map(|hit| hit.distance).collect();
+    assert_eq!(distances, vec![1.0, 2.0]);
+    assert_eq!(hits[0].kind, MockVREntityKind::Plane);
+    assert_eq!(hits[0].normal, [0.0, 0.0, 1.0]);
+
+    assert!(handle.hit_test([f32::NAN, 0.0, 0.0], [0.0, 0.0, -1.0]).is_empty());
+    assert!(handle.hit_test([0.0, 0.0, 0.0], [0.0, f32::NAN, -1.0]).is_empty());
+    assert!(handle.hit_test([0.0, 0.0, f32::INFINITY], [0.0, 0.0, -1.0]).is_empty());
+}
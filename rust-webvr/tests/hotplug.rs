@@ -0,0 +1,76 @@
+extern crate rust_webvr;
+
+use rust_webvr::api::{MockVRDisplayHandle, MockVRServiceRemote};
+use rust_webvr::{MockVRControlMsg, MockVRInit, VRDisplayEvent, VREvent, VRServiceManager};
+
+fn manager_with_display() -> (VRServiceManager, MockVRServiceRemote, u32, MockVRDisplayHandle) {
+    let mut manager = VRServiceManager::new();
+    let remote = manager.register_mock_with_displays();
+    let (display_id, _, handle) = remote.add_display_with_handle(MockVRInit::default());
+    (manager, remote, display_id, handle)
+}
+
+fn display_ids(manager: &mut VRServiceManager) -> Vec<u32> {
+    manager.get_displays().iter().map(|display| display.borrow().id()).collect()
+}
+
+fn connects(events: &[VREvent]) -> Vec<u32> {
+    events.iter().filter_map(|event| match *event {
+        VREvent::Display(VRDisplayEvent::Connect(ref data)) => Some(data.display_id),
+        _ => None,
+    }).collect()
+}
+
+fn disconnects(events: &[VREvent]) -> Vec<u32> {
+    events.iter().filter_map(|event| match *event {
+        VREvent::Display(VRDisplayEvent::Disconnect(display_id)) => Some(display_id),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn enumerated_display_is_announced_once() {
+    let (mut manager, _remote, display_id, _handle) = manager_with_display();
+    assert_eq!(display_ids(&mut manager), vec![display_id]);
+
+    // The service also reports the new display, it must not be announced twice
+    let events = manager.poll_events();
+    assert_eq!(connects(&events), vec![display_id]);
+    assert!(disconnects(&events).is_empty());
+    assert!(manager.poll_events().is_empty());
+}
+
+#[test]
+fn unplug_and_replug() {
+    let (mut manager, _remote, display_id, handle) = manager_with_display();
+    assert_eq!(display_ids(&mut manager), vec![display_id]);
+    manager.poll_events();
+
+    handle.handle_msg(MockVRControlMsg::Disconnect);
+    let events = manager.poll_events();
+    assert_eq!(disconnects(&events), vec![display_id]);
+    assert!(connects(&events).is_empty());
+    assert!(display_ids(&mut manager).is_empty());
+    assert!(manager.poll_events().is_empty());
+
+    handle.handle_msg(MockVRControlMsg::Connect);
+    let events = manager.poll_events();
+    assert_eq!(connects(&events), vec![display_id]);
+    assert!(disconnects(&events).is_empty());
+    assert_eq!(display_ids(&mut manager), vec![display_id]);
+    assert!(manager.poll_events().is_empty());
+}
+
+#[test]
+fn removed_display_is_disconnected_once() {
+    let (mut manager, remote, display_id, _handle) = manager_with_display();
+    assert_eq!(display_ids(&mut manager), vec![display_id]);
+    manager.poll_events();
+
+    remote.remove_display(display_id);
+    // Enumerating first must not lose the event
+    assert!(display_ids(&mut manager).is_empty());
+    let events = manager.poll_events();
+    assert_eq!(disconnects(&events), vec![display_id]);
+    assert!(manager.poll_events().is_empty());
+}
@@ -0,0 +1,121 @@
+extern crate ipc_channel;
+extern crate rust_webvr;
+
+use ipc_channel::ipc::{self, IpcOneShotServer, IpcSender};
+use rust_webvr::api::{mock_profile, MockServiceCreator, VRProxyDisplayRequest, VRProxyRequest, VRProxyServer};
+use rust_webvr::{MockVRCompositorInit, MockVRControlMsg, MockVRInit, VRDisplayEvent, VREvent, VRIdAllocator, VRMonotonicClock};
+use rust_webvr::{VRFutureFrameData, VRServiceManager};
+use std::env;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const EYE_POSITION: [f32; 3] = [0.0, 1.6, 0.0];
+// Set when the test binary runs as the server process
+const SERVER_NAME: &str = "RUST_WEBVR_PROXY_SERVER";
+
+type Bootstrap = (IpcSender<VRProxyRequest>, IpcSender<MockVRControlMsg>);
+
+// Server process: serves a mock vive with its controllers and a compositor until the proxy went away.
+// Does nothing when run as a regular test.
+#[test]
+fn serve_proxy() {
+    let server_name = match env::var(SERVER_NAME) {
+        Ok(name) => name,
+        Err(_) => return,
+    };
+    let init = MockVRInit {
+        viewer_origin: Some((EYE_POSITION, [0.0, 0.0, 0.0, 1.0])),
+        profile: Some(mock_profile("vive").unwrap()),
+        // Frames resolved by the compositor thread, the server waits for them
+        compositor: Some(MockVRCompositorInit::default()),
+        .. Default::default()
+    };
+    let (service, control) = MockServiceCreator::new_service_with_ipc_remote(init,
+                                                                             Arc::new(VRMonotonicClock::new()),
+                                                                             Arc::new(VRIdAllocator::new())).unwrap();
+    let (sender, receiver) = ipc::channel().unwrap();
+    let bootstrap: IpcSender<Bootstrap> = IpcSender::connect(server_name).unwrap();
+    bootstrap.send((sender, control)).unwrap();
+    VRProxyServer::serve(service, receiver);
+}
+
+#[test]
+fn devices_are_proxied_across_processes() {
+    let (server, server_name) = IpcOneShotServer::<Bootstrap>::new().unwrap();
+    let mut child = Command::new(env::current_exe().unwrap())
+        .args(["serve_proxy", "--exact", "--test-threads=1"])
+        .env(SERVER_NAME, server_name)
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let (_, (sender, control)) = server.accept().unwrap();
+
+    // The local mock takes the first ids, which the server process allocated to its own devices
+    let mut manager = VRServiceManager::new();
+    manager.register_mock();
+    let local = manager.get_displays().pop().unwrap();
+    manager.register_proxy(sender).unwrap();
+
+    let display = manager.get_displays().into_iter()
+                         .find(|display| display.borrow().id() != local.borrow().id())
+                         .expect("No display proxied");
+    let display_id = display.borrow().id();
+    {
+        let data = display.borrow().data();
+        assert_eq!(data.display_id, display_id);
+        assert_eq!(data.display_name, "Mock HTC Vive");
+        assert!(data.connected);
+    }
+
+    display.borrow_mut().start_present(None);
+    let mut last_timestamp = 0.0;
+    for _ in 0..3 {
+        let frame = display.borrow_mut().future_frame_data(0.1, 1000.0).block();
+        assert_eq!(frame.pose.position, Some(EYE_POSITION));
+        assert!(frame.timestamp > last_timestamp);
+        last_timestamp = frame.timestamp;
+    }
+
+    let gamepads = manager.get_gamepads();
+    assert_eq!(gamepads.len(), 2);
+    for gamepad in &gamepads {
+        let id = gamepad.borrow().id();
+        assert_ne!(id, display_id);
+        assert_eq!(gamepad.borrow().data().display_id, display_id);
+        assert_eq!(gamepad.borrow().state().gamepad_id, id);
+    }
+
+    control.send(MockVRControlMsg::Blur).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let blurred = manager.poll_events().iter().any(|event| match *event {
+            VREvent::Display(VRDisplayEvent::Blur(ref data)) => data.display_id == display_id,
+            _ => false,
+        });
+        if blurred {
+            break;
+        }
+        assert!(Instant::now() < deadline, "The blur event wasn't forwarded");
+        thread::sleep(Duration::from_millis(10));
+    }
+    display.borrow_mut().stop_present();
+
+    // The server process exits once the proxy went away
+    drop(gamepads);
+    drop(display);
+    drop(manager);
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn frames_of_unknown_displays_are_resolved() {
+    let service = MockServiceCreator::new_service_with_init(MockVRInit::default(),
+                                                            Arc::new(VRMonotonicClock::new()),
+                                                            Arc::new(VRIdAllocator::new()));
+    let sender = VRProxyServer::spawn(service).unwrap();
+    let (resolver, future) = VRFutureFrameData::blocked();
+    sender.send(VRProxyRequest::Display(42, VRProxyDisplayRequest::FutureFrameData(0.1, 1000.0, resolver))).unwrap();
+    assert_eq!(future.block().timestamp, 0.0);
+}
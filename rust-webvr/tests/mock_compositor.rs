@@ -0,0 +1,26 @@
+extern crate rust_webvr;
+
+use rust_webvr::{MockVRCompositorInit, MockVRInit, VRManualClock, VRServiceManager};
+use std::sync::Arc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+#[test]
+fn compositor_stops_with_its_display() {
+    // The clock never reaches the next vsync, the compositor must notice the display going away anyway
+    let clock = Arc::new(VRManualClock::new(0.0));
+    let mut manager = VRServiceManager::new_with_clock(clock);
+    let remote = manager.register_mock_with_displays();
+    let init = MockVRInit { compositor: Some(MockVRCompositorInit::default()), .. Default::default() };
+    let (display_id, sender) = remote.add_display(init);
+    drop(sender);
+    let frames = remote.capture_frames(display_id, false);
+    assert_eq!(manager.get_displays().len(), 1);
+
+    remote.remove_display(display_id);
+    manager.poll_events();
+    assert!(manager.get_displays().is_empty());
+
+    // The frame sender goes away with the last reference to the display state
+    assert_eq!(frames.recv_timeout(Duration::from_secs(5)).err(), Some(RecvTimeoutError::Disconnected));
+}
@@ -0,0 +1,96 @@
+extern crate rust_webvr;
+
+use rust_webvr::api::{MOCK_PROFILES, mock_profile};
+use rust_webvr::{MockVRCompositorInit, MockVRInit, VRClock, VRManualClock, VRServiceManager};
+use std::sync::Arc;
+
+#[test]
+fn built_in_profiles_load() {
+    for name in MOCK_PROFILES {
+        let profile = mock_profile(name).unwrap();
+        assert_eq!(profile.name, *name);
+        assert!(profile.refresh_rate > 0.0);
+    }
+    assert!(mock_profile("unknown").is_none());
+}
+
+#[test]
+fn default_eyes_are_unchanged() {
+    let mut manager = VRServiceManager::new();
+    manager.register_mock();
+    let display = manager.get_displays()[0].clone();
+    let data = display.borrow().data();
+    assert_eq!(data.left_eye_parameters.offset, [0.035949998, 0.0, 0.015]);
+    assert_eq!(data.right_eye_parameters.offset, [-0.035949998, 0.0, 0.015]);
+    let frame = display.borrow().immediate_frame_data(0.1, 1000.0);
+    assert_eq!(frame.left_view_matrix[12..15], [-0.035949998, 0.0, 0.015]);
+    assert_eq!(frame.right_view_matrix[12..15], [0.035949998, 0.0, 0.015]);
+
+    // A profile puts the eyes half its IPD away from the center
+    let mut manager = VRServiceManager::new();
+    manager.register_mock_with_remote(MockVRInit { profile: mock_profile("vive"), .. Default::default() });
+    let display = manager.get_displays()[0].clone();
+    let data = display.borrow().data();
+    assert_eq!(data.left_eye_parameters.offset, [-0.03595, 0.0, 0.0]);
+    assert_eq!(data.right_eye_parameters.offset, [0.03595, 0.0, 0.0]);
+    let frame = display.borrow().immediate_frame_data(0.1, 1000.0);
+    assert_eq!(frame.left_view_matrix[12..15], [0.03595, 0.0, 0.0]);
+    assert_eq!(frame.right_view_matrix[12..15], [-0.03595, 0.0, 0.0]);
+}
+
+fn frame_time(mut manager: VRServiceManager, init: MockVRInit) -> f64 {
+    manager.register_mock_with_remote(init);
+    let display = manager.get_displays()[0].clone();
+    let future = display.borrow_mut().future_frame_data(0.1, 100.0);
+    future.block().timestamp
+}
+
+#[test]
+fn invalid_refresh_rates_are_ignored() {
+    let mut profile = mock_profile("quest").unwrap();
+    profile.refresh_rate = 0.0;
+    let clock = Arc::new(VRManualClock::new(0.0));
+    let manager = VRServiceManager::new_with_clock(clock.clone());
+    let time = frame_time(manager, MockVRInit { profile: Some(profile), .. Default::default() });
+    assert!(time.is_finite() && time > 0.0);
+    assert_eq!(time, clock.now());
+
+    // The compositor runs on its own, it needs a clock that moves
+    let compositor = MockVRCompositorInit { refresh_rate: Some(-60.0), .. Default::default() };
+    let time = frame_time(VRServiceManager::new(), MockVRInit { compositor: Some(compositor), .. Default::default() });
+    assert!(time.is_finite() && time > 0.0);
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn loading_rejects_invalid_refresh_rates() {
+    use rust_webvr::api::load_mock_profile;
+    use std::env;
+    use std::fs;
+
+    let contents = r#"(
+        name: "frozen",
+        display_name: "Frozen viewer",
+        render_width: 1280,
+        render_height: 720,
+        left_field_of_view: (up_degrees: 22.5, right_degrees: 40.0, down_degrees: 22.5, left_degrees: 40.0),
+        right_field_of_view: (up_degrees: 22.5, right_degrees: 40.0, down_degrees: 22.5, left_degrees: 40.0),
+        ipd: 0.0,
+        refresh_rate: 0.0,
+        capabilities: (
+            has_position: false,
+            has_orientation: true,
+            has_external_display: true,
+            can_present: true,
+            presented_by_browser: false,
+            max_layers: 0,
+        ),
+        stage_size: None,
+        controllers: [],
+    )"#;
+    let path = env::temp_dir().join(format!("rust-webvr-profile-{}.ron", std::process::id()));
+    fs::write(&path, contents).unwrap();
+    let result = load_mock_profile(&path);
+    let _ = fs::remove_file(&path);
+    assert!(result.unwrap_err().starts_with("Invalid refresh rate 0"));
+}
@@ -0,0 +1,121 @@
+extern crate rust_webvr;
+
+use rust_webvr::api::{MockServiceCreator, MockVRDisplayHandle, MockVRServiceRemote, PluginServiceCreator};
+use rust_webvr::vr_plugin::{VRPluginClock, VRPluginCreator, VRPluginService, VR_PLUGIN_ABI_VERSION};
+use rust_webvr::{MockVRControlMsg, MockVRGamepadInit, MockVRInit, VRClock, VRDisplayEvent, VREvent};
+use rust_webvr::{VRGamepadEvent, VRGamepadHand, VRIdAllocator, VRManualClock, VRServiceManager};
+use std::cell::RefCell;
+use std::os::raw::c_char;
+use std::sync::Arc;
+
+// Same as examples/mock_plugin, but linked into the test so that it can control the mock
+static CREATOR: VRPluginCreator = VRPluginCreator {
+    abi_version: VR_PLUGIN_ABI_VERSION,
+    name: b"mock-plugin\0" as *const u8 as *const c_char,
+    new_service,
+};
+
+thread_local! {
+    static REMOTE: RefCell<Option<MockVRServiceRemote>> = const { RefCell::new(None) };
+}
+
+unsafe extern "C" fn new_service(clock: VRPluginClock) -> VRPluginService {
+    let ids = Arc::new(VRIdAllocator::new());
+    let (service, remote) = MockServiceCreator::new_service_with_displays(clock.into_clock(), ids);
+    REMOTE.with(|r| *r.borrow_mut() = Some(remote));
+    VRPluginService::export(service)
+}
+
+fn manager_with_plugin() -> (VRServiceManager, Arc<VRManualClock>, MockVRServiceRemote) {
+    let clock = Arc::new(VRManualClock::new(0.0));
+    let mut manager = VRServiceManager::new_with_clock(clock.clone());
+    let creator = PluginServiceCreator::from_creator(&CREATOR).unwrap();
+    manager.register(creator.new_service(manager.clock(), manager.id_allocator()));
+    let remote = REMOTE.with(|r| r.borrow_mut().take()).unwrap();
+    (manager, clock, remote)
+}
+
+fn manager_with_display() -> (VRServiceManager, Arc<VRManualClock>, MockVRServiceRemote, MockVRDisplayHandle) {
+    let (manager, clock, remote) = manager_with_plugin();
+    let (_, _, handle) = remote.add_display_with_handle(MockVRInit::default());
+    (manager, clock, remote, handle)
+}
+
+#[test]
+fn displays_get_host_ids() {
+    let (mut manager, _clock, _remote, _handle) = manager_with_display();
+    let displays = manager.get_displays();
+    assert_eq!(displays.len(), 1);
+    let display = displays[0].borrow();
+    let data = display.data();
+    assert_eq!(data.display_id, display.id());
+    assert!(manager.get_display(display.id()).is_some());
+    assert!(data.connected);
+    assert!(!data.display_name.is_empty());
+}
+
+#[test]
+fn frame_data_crosses_the_plugin() {
+    let (mut manager, clock, _remote, handle) = manager_with_display();
+    handle.handle_msg(MockVRControlMsg::SetViewerPose([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0]));
+    let display = manager.get_displays()[0].clone();
+
+    let immediate = display.borrow().immediate_frame_data(0.1, 100.0);
+    assert_eq!(immediate.pose.position, Some([1.0, 2.0, 3.0]));
+
+    let future = display.borrow_mut().future_frame_data(0.1, 100.0).block();
+    assert_eq!(future.pose.position, Some([1.0, 2.0, 3.0]));
+    assert_eq!(future.timestamp, clock.now());
+}
+
+#[test]
+fn gamepads_and_events_cross_the_plugin() {
+    let (mut manager, _clock, _remote, handle) = manager_with_display();
+    let display_id = manager.get_displays()[0].borrow().id();
+    manager.poll_events();
+
+    let init = MockVRGamepadInit {
+        name: "Mock controller".into(),
+        hand: VRGamepadHand::Right,
+        buttons: 2,
+        axes: 2,
+        serial: None,
+        target_ray_mode: Default::default(),
+        profiles: Vec::new(),
+        pointer_origin: None,
+    };
+    handle.handle_msg(MockVRControlMsg::ConnectGamepad(0, init));
+    let gamepads = manager.get_gamepads();
+    assert_eq!(gamepads.len(), 1);
+    let gamepad_id = gamepads[0].borrow().id();
+    assert_eq!(gamepads[0].borrow().data().display_id, display_id);
+    assert!(matches!(gamepads[0].borrow().data().hand, VRGamepadHand::Right));
+    assert_eq!(gamepads[0].borrow().state().gamepad_id, gamepad_id);
+    assert_eq!(gamepads[0].borrow().state().axes.len(), 2);
+
+    let events = manager.poll_events();
+    assert!(events.iter().any(|event| matches!(*event, VREvent::Gamepad(VRGamepadEvent::Connect(_, ref state)) if state.gamepad_id == gamepad_id)));
+
+    handle.handle_msg(MockVRControlMsg::DisconnectGamepad(0));
+    let events = manager.poll_events();
+    assert!(events.iter().any(|event| matches!(*event, VREvent::Gamepad(VRGamepadEvent::Disconnect(id)) if id == gamepad_id)));
+    assert!(manager.get_gamepads().is_empty());
+}
+
+#[test]
+fn removed_displays_are_released() {
+    let (mut manager, _clock, remote) = manager_with_plugin();
+    // The plugin has its own ids, different from the ones assigned by the host
+    let (plugin_id, _, _handle) = remote.add_display_with_handle(MockVRInit::default());
+    let display = manager.get_displays()[0].clone();
+    let display_id = display.borrow().id();
+    manager.poll_events();
+
+    remote.remove_display(plugin_id);
+    let events = manager.poll_events();
+    assert!(events.iter().any(|event| matches!(*event, VREvent::Display(VRDisplayEvent::Disconnect(id)) if id == display_id)));
+    assert!(manager.get_displays().is_empty());
+
+    // The manager and the plugin service don't keep the display alive anymore
+    assert_eq!(Arc::strong_count(&display), 1);
+}
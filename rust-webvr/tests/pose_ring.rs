@@ -0,0 +1,167 @@
+// Shared memory pose rings are only supported on unix platforms
+#![cfg(unix)]
+
+extern crate libc;
+extern crate rust_webvr;
+
+use rust_webvr::api::{VRPoseRingError, VRPoseRingReader, VRPoseRingWriter};
+use rust_webvr::{VRFrameData, VRPose};
+use std::ffi::CString;
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+// Shared memory names are global, tests running concurrently must not share them
+fn ring_name(test: &str) -> String {
+    format!("rust-webvr-{}-{}", test, process::id())
+}
+
+fn frame_data(frame: u64) -> VRFrameData {
+    let value = frame as f32;
+    VRFrameData {
+        timestamp: frame as f64,
+        left_projection_matrix: [value; 16],
+        right_view_matrix: [value; 16],
+        pose: VRPose {
+            position: Some([value, value, value]),
+            orientation: Some([0.0, 0.0, 0.0, 1.0]),
+            .. Default::default()
+        },
+        .. Default::default()
+    }
+}
+
+#[test]
+fn published_frames_are_read() {
+    let name = ring_name("publish");
+    let mut writer = VRPoseRingWriter::create(&name, 4).unwrap();
+    let reader = VRPoseRingReader::open(&name).unwrap();
+    assert_eq!(reader.capacity(), 4);
+    assert!(reader.latest().is_none());
+
+    assert_eq!(writer.publish(7, &frame_data(0)), 0);
+    assert_eq!(writer.publish(7, &frame_data(1)), 1);
+    assert_eq!(reader.written(), 2);
+    let frame = reader.read(0).unwrap();
+    assert_eq!(frame.frame, 0);
+    assert_eq!(frame.display_id, 7);
+    let latest = reader.latest().unwrap();
+    let expected = frame_data(1);
+    assert_eq!(latest.frame, 1);
+    assert_eq!(latest.data.timestamp, expected.timestamp);
+    assert_eq!(latest.data.left_projection_matrix, expected.left_projection_matrix);
+    assert_eq!(latest.data.right_view_matrix, expected.right_view_matrix);
+    assert_eq!(latest.data.pose.position, expected.pose.position);
+    assert_eq!(latest.data.pose.orientation, expected.pose.orientation);
+    assert_eq!(latest.data.pose.linear_velocity, None);
+
+    // A ring can't be created twice, and goes away with its writer
+    assert!(VRPoseRingWriter::create(&name, 4).is_err());
+    drop(writer);
+    assert!(VRPoseRingReader::open(&name).is_err());
+    assert_eq!(reader.latest().unwrap().frame, 1);
+}
+
+#[test]
+fn unpublished_frames_are_not_written() {
+    let name = ring_name("not-written");
+    let mut writer = VRPoseRingWriter::create(&name, 4).unwrap();
+    let reader = VRPoseRingReader::open(&name).unwrap();
+    assert_eq!(reader.read(0).unwrap_err(), VRPoseRingError::NotWritten);
+
+    writer.publish(0, &frame_data(0));
+    assert!(reader.read(0).is_ok());
+    assert_eq!(reader.read(1).unwrap_err(), VRPoseRingError::NotWritten);
+    // Frame 4 goes to the slot of frame 0
+    assert_eq!(reader.read(4).unwrap_err(), VRPoseRingError::NotWritten);
+}
+
+#[test]
+fn lapped_frames_are_overwritten() {
+    let name = ring_name("overwritten");
+    let mut writer = VRPoseRingWriter::create(&name, 4).unwrap();
+    let reader = VRPoseRingReader::open(&name).unwrap();
+    for frame in 0..6 {
+        writer.publish(0, &frame_data(frame));
+    }
+
+    assert_eq!(reader.read(0).unwrap_err(), VRPoseRingError::Overwritten);
+    assert_eq!(reader.read(1).unwrap_err(), VRPoseRingError::Overwritten);
+    assert_eq!(reader.read(2).unwrap().data.timestamp, 2.0);
+    assert_eq!(reader.read(6).unwrap_err(), VRPoseRingError::NotWritten);
+    let frames: Vec<u64> = reader.frames_since(0).iter().map(|frame| frame.frame).collect();
+    assert_eq!(frames, vec![2, 3, 4, 5]);
+    let frames: Vec<u64> = reader.frames_since(4).iter().map(|frame| frame.frame).collect();
+    assert_eq!(frames, vec![5]);
+}
+
+#[test]
+fn concurrent_reads_are_never_torn() {
+    const FRAMES: u64 = 200_000;
+    let name = ring_name("concurrent");
+    let mut writer = VRPoseRingWriter::create(&name, 2).unwrap();
+    let done = Arc::new(AtomicBool::new(false));
+    let readers: Vec<_> = (0..4).map(|_| {
+        let reader = VRPoseRingReader::open(&name).unwrap();
+        let done = done.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::Relaxed) {
+                let written = reader.written();
+                if written == 0 {
+                    continue;
+                }
+                match reader.read(written - 1) {
+                    Ok(frame) => {
+                        // Every field was written with the same frame
+                        let expected = frame_data(frame.frame);
+                        assert_eq!(frame.display_id, frame.frame as u32);
+                        assert_eq!(frame.data.timestamp, expected.timestamp);
+                        assert_eq!(frame.data.left_projection_matrix, expected.left_projection_matrix);
+                        assert_eq!(frame.data.right_view_matrix, expected.right_view_matrix);
+                        assert_eq!(frame.data.pose.position, expected.pose.position);
+                    }
+                    Err(VRPoseRingError::Torn) | Err(VRPoseRingError::Overwritten) => {}
+                    Err(error) => panic!("Unexpected error: {:?}", error),
+                }
+            }
+        })
+    }).collect();
+
+    for frame in 0..FRAMES {
+        writer.publish(frame as u32, &frame_data(frame));
+    }
+    done.store(true, Ordering::Relaxed);
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    let reader = VRPoseRingReader::open(&name).unwrap();
+    assert_eq!(reader.latest().unwrap().frame, FRAMES - 1);
+}
+
+#[test]
+fn only_rings_are_opened() {
+    assert!(VRPoseRingReader::open(&ring_name("missing")).is_err());
+
+    let error = open_shared_memory("empty", 0);
+    assert!(error.ends_with("is still being created"), "{}", error);
+    // A zeroed shared memory without the header of a ring
+    let error = open_shared_memory("not-a-ring", 4096);
+    assert!(error.ends_with("is not a pose ring"), "{}", error);
+}
+
+// Opens a ring in a shared memory of the given size, that isn't created by a writer
+fn open_shared_memory(test: &str, len: libc::off_t) -> String {
+    let name = CString::new(format!("/{}", ring_name(test))).unwrap();
+    unsafe {
+        let fd = libc::shm_open(name.as_ptr(), libc::O_CREAT | libc::O_EXCL | libc::O_RDWR, 0o600);
+        assert!(fd >= 0);
+        assert_eq!(libc::ftruncate(fd, len), 0);
+        libc::close(fd);
+    }
+    let error = VRPoseRingReader::open(name.to_str().unwrap()).err().unwrap();
+    unsafe {
+        libc::shm_unlink(name.as_ptr());
+    }
+    error
+}
@@ -0,0 +1,71 @@
+extern crate rust_webvr;
+
+use rust_webvr::api::{MockVRDisplayHandle, MockVRServiceRemote};
+use rust_webvr::{MockVRControlMsg, MockVRInit, MockVRView, VRDisplayPtr, VRServiceManager};
+
+fn manager_with_display() -> (VRServiceManager, MockVRServiceRemote, VRDisplayPtr, MockVRDisplayHandle) {
+    let mut manager = VRServiceManager::new();
+    let remote = manager.register_mock_with_displays();
+    let (_, _, handle) = remote.add_display_with_handle(MockVRInit::default());
+    let display = manager.get_displays().pop().unwrap();
+    (manager, remote, display, handle)
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-4, "{} != {}", actual, expected);
+}
+
+// Asymmetric projection with the given clip planes
+fn projection(near: f32, far: f32) -> [f32; 16] {
+    [1.2, 0.0, 0.0, 0.0,
+     0.0, 1.5, 0.0, 0.0,
+     0.1, -0.2, (near + far) / (near - far), -1.0,
+     0.0, 0.0, 2.0 * far * near / (near - far), 0.0]
+}
+
+#[test]
+fn clip_planes_reach_the_projection() {
+    let (_manager, _remote, display, _handle) = manager_with_display();
+    for &(near, far) in &[(0.1, 1000.0), (0.5, 50.0)] {
+        let frame = display.borrow().immediate_frame_data(near, far);
+        for matrix in &[frame.left_projection_matrix, frame.right_projection_matrix] {
+            assert_close(matrix[10], ((near + far) / (near - far)) as f32);
+            assert_close(matrix[14], (2.0 * far * near / (near - far)) as f32);
+        }
+    }
+}
+
+#[test]
+fn views_round_trip_through_the_field_of_view() {
+    let (_manager, _remote, display, handle) = manager_with_display();
+    handle.handle_msg(MockVRControlMsg::SetViews(MockVRView { projection: projection(0.1, 1000.0), offset: [-0.03, 0.0, 0.0] },
+                                                 MockVRView { projection: projection(0.1, 1000.0), offset: [0.03, 0.0, 0.0] }));
+
+    // The field of view is consistent with the projection, whatever the clip planes
+    let fov = display.borrow().data().left_eye_parameters.field_of_view;
+    assert!(fov.right_degrees > fov.left_degrees);
+    assert!(fov.down_degrees > fov.up_degrees);
+    let frame = display.borrow().immediate_frame_data(0.5, 50.0);
+    let expected = projection(0.5, 50.0);
+    for matrix in &[frame.left_projection_matrix, frame.right_projection_matrix] {
+        for (actual, expected) in matrix.iter().zip(expected.iter()) {
+            assert_close(*actual, *expected);
+        }
+    }
+}
+
+#[test]
+fn projection_overrides_ignore_clip_planes_until_cleared() {
+    let (_manager, _remote, display, handle) = manager_with_display();
+    let default = display.borrow().immediate_frame_data(0.1, 1000.0);
+
+    handle.handle_msg(MockVRControlMsg::SetProjectionOverride(Some(([1.0; 16], [2.0; 16]))));
+    let frame = display.borrow().immediate_frame_data(0.5, 50.0);
+    assert_eq!(frame.left_projection_matrix, [1.0; 16]);
+    assert_eq!(frame.right_projection_matrix, [2.0; 16]);
+
+    handle.handle_msg(MockVRControlMsg::SetProjectionOverride(None));
+    let frame = display.borrow().immediate_frame_data(0.1, 1000.0);
+    assert_eq!(frame.left_projection_matrix, default.left_projection_matrix);
+    assert_eq!(frame.right_projection_matrix, default.right_projection_matrix);
+}
@@ -0,0 +1,38 @@
+extern crate rust_webvr;
+
+use rust_webvr::api::{VRRecord, VRRecording};
+use rust_webvr::{MockVRFaultKind, MockVRFaultPlan, MockVRFaultTrigger, MockVRInit};
+use rust_webvr::{VRManualClock, VRServiceManager};
+use std::env;
+use std::fs;
+use std::sync::Arc;
+
+#[test]
+fn frame_data_is_recorded_when_handed_out() {
+    let path = env::temp_dir().join(format!("rust-webvr-recording-{}.vrrec", std::process::id()));
+    let mut manager = VRServiceManager::new_with_clock(Arc::new(VRManualClock::new(0.0)));
+    manager.record_to(&path).unwrap();
+    let init = MockVRInit {
+        faults: Some(MockVRFaultPlan::single(MockVRFaultKind::StallFrame, MockVRFaultTrigger::Frame(1))),
+        .. Default::default()
+    };
+    manager.register_mock_with_remote(init);
+    let display = manager.get_displays()[0].clone();
+
+    let first = display.borrow_mut().future_frame_data(0.1, 100.0);
+    // Never resolved, doesn't hold back the next frame
+    let stalled = display.borrow_mut().future_frame_data(0.1, 100.0);
+    let third = display.borrow_mut().future_frame_data(0.1, 100.0);
+    let timestamps = vec![first.block().timestamp, third.block().timestamp];
+    drop(stalled);
+    drop(display);
+    drop(manager);
+
+    let recording = VRRecording::open(&path);
+    let _ = fs::remove_file(&path);
+    let recorded: Vec<f64> = recording.unwrap().entries.iter().filter_map(|entry| match entry.record {
+        VRRecord::FrameData(_, ref data) => Some(data.timestamp),
+        _ => None,
+    }).collect();
+    assert_eq!(recorded, timestamps);
+}
@@ -0,0 +1,106 @@
+extern crate rust_webvr;
+
+use rust_webvr::api::VRReplayMode;
+use rust_webvr::{MockVRControlMsg, MockVRGamepadInit, MockVRInit, VREvent, VRFrameData, VRGamepadHand};
+use rust_webvr::{VRGamepadState, VRManualClock, VRServiceManager};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+const FRAMES: usize = 4;
+
+// What the application saw, with the ids left out of the gamepad states
+struct Session {
+    frames: Vec<String>,
+    states: Vec<String>,
+    events: Vec<String>,
+}
+
+fn frame(data: VRFrameData) -> String {
+    format!("{:?}", data)
+}
+
+fn state(mut state: VRGamepadState) -> String {
+    state.gamepad_id = 0;
+    format!("{:?}", state)
+}
+
+// The kind of event, e.g. "Display(Blur"
+fn event(event: &VREvent) -> String {
+    let event = format!("{:?}", event);
+    event.split('(').take(2).collect::<Vec<_>>().join("(")
+}
+
+fn record(path: &Path) -> Session {
+    let mut manager = VRServiceManager::new_with_clock(Arc::new(VRManualClock::new(0.0)));
+    manager.record_to(path).unwrap();
+    let remote = manager.register_mock_with_displays();
+    let (_, _, handle) = remote.add_display_with_handle(MockVRInit::default());
+    // The connection is recorded before the data of the display
+    let mut events: Vec<String> = manager.poll_events().iter().map(event).collect();
+    let display = manager.get_displays()[0].clone();
+
+    let init = MockVRGamepadInit {
+        name: "Mock controller".into(),
+        hand: VRGamepadHand::Left,
+        buttons: 1,
+        axes: 2,
+        serial: None,
+        target_ray_mode: Default::default(),
+        profiles: Vec::new(),
+        pointer_origin: None,
+    };
+    handle.handle_msg(MockVRControlMsg::ConnectGamepad(0, init));
+    manager.get_gamepads();
+    events.extend(manager.poll_events().iter().map(event));
+
+    let mut frames = Vec::new();
+    let mut states = Vec::new();
+    for i in 0..FRAMES {
+        handle.handle_msg(MockVRControlMsg::SetViewerPose([i as f32, 1.6, 0.0], [0.0, 0.0, 0.0, 1.0]));
+        handle.handle_msg(MockVRControlMsg::SetGamepadAxes(0, vec![i as f64 / 4.0, 0.5]));
+        if i == 2 {
+            handle.handle_msg(MockVRControlMsg::Blur);
+        }
+        frames.push(frame(display.borrow_mut().future_frame_data(0.1, 100.0).block()));
+        states.push(state(manager.get_gamepads()[0].borrow().state()));
+        events.extend(manager.poll_events().iter().map(event));
+    }
+    handle.handle_msg(MockVRControlMsg::DisconnectGamepad(0));
+    events.extend(manager.poll_events().iter().map(event));
+    Session { frames, states, events }
+}
+
+fn replay(path: &Path) -> Session {
+    let mut manager = VRServiceManager::new_with_clock(Arc::new(VRManualClock::new(0.0)));
+    manager.register_replay(path, VRReplayMode::AsFastAsPossible).unwrap();
+    let mut events: Vec<String> = manager.poll_events().iter().map(event).collect();
+    let display = manager.get_displays()[0].clone();
+    manager.get_gamepads();
+    events.extend(manager.poll_events().iter().map(event));
+
+    let mut frames = Vec::new();
+    let mut states = Vec::new();
+    for _ in 0..FRAMES {
+        frames.push(frame(display.borrow_mut().future_frame_data(0.1, 100.0).block()));
+        states.push(state(manager.get_gamepads()[0].borrow().state()));
+        events.extend(manager.poll_events().iter().map(event));
+    }
+    events.extend(manager.poll_events().iter().map(event));
+    Session { frames, states, events }
+}
+
+#[test]
+fn replays_match_the_recorded_session() {
+    let path = env::temp_dir().join(format!("rust-webvr-replay-{}.vrrec", std::process::id()));
+    let recorded = record(&path);
+    let replayed = replay(&path);
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(replayed.frames, recorded.frames);
+    assert_eq!(replayed.states, recorded.states);
+    assert_eq!(replayed.events, recorded.events);
+    assert!(recorded.events.iter().any(|event| event == "Display(Blur"));
+}
+
@@ -0,0 +1,97 @@
+extern crate rust_webvr;
+
+use rust_webvr::{VRServiceConfig, VRServiceConfigEntry, VRServiceManager};
+
+fn names(config: &VRServiceConfig) -> Vec<&str> {
+    config.services.iter().map(|entry| entry.name.as_str()).collect()
+}
+
+#[test]
+fn lists_skip_empty_names() {
+    let config = VRServiceConfig::from_list("mock, ,openvr");
+    assert_eq!(names(&config), ["mock", "openvr"]);
+
+    let policy = config.policy();
+    assert_eq!(policy.priorities, ["mock", "openvr"]);
+    assert!(!policy.prefer_hardware);
+    assert!(!policy.exclusive);
+    assert!(policy.deny.is_empty());
+}
+
+#[test]
+fn listed_services_are_registered_in_order() {
+    let mut manager = VRServiceManager::new();
+    manager.register_config(VRServiceConfig::from_list("mock, ,openvr"));
+    assert_eq!(manager.policy().priorities, ["mock", "openvr"]);
+
+    // openvr is only built on windows
+    let status = manager.service_status();
+    assert_eq!(status[0].name, "mock");
+    assert_eq!(status.len(), if cfg!(target_os = "windows") { 2 } else { 1 });
+    assert_eq!(manager.get_displays().len(), 1);
+}
+
+#[test]
+fn options_of_other_services_are_unsupported() {
+    let entry = VRServiceConfigEntry {
+        name: "openvr".into(),
+        mock_profile: Some("quest".into()),
+        window_size: Some((800, 600)),
+        .. Default::default()
+    };
+    assert_eq!(entry.unsupported_options(), ["mock_profile", "window_size"]);
+
+    let entry = VRServiceConfigEntry {
+        name: "mock".into(),
+        mock_profile: Some("quest".into()),
+        .. Default::default()
+    };
+    assert!(entry.unsupported_options().is_empty());
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn toml_files_configure_the_mock() {
+    let config = VRServiceConfig::from_toml(r#"
+        prefer_hardware = true
+        deny = ["openvr"]
+
+        [[services]]
+        name = "mock"
+        mock = { eye_level = 1.2 }
+        mock_profile = "quest"
+
+        [[services]]
+        name = "openvr"
+        mock_profile = "vive"
+    "#).unwrap();
+    assert_eq!(names(&config), ["mock", "openvr"]);
+    assert_eq!(config.services[0].mock_profile, Some("quest".into()));
+    assert_eq!(config.services[1].unsupported_options(), ["mock_profile"]);
+
+    let policy = config.policy();
+    assert_eq!(policy.priorities, ["mock", "openvr"]);
+    assert!(policy.prefer_hardware);
+    assert!(policy.is_denied("openvr"));
+
+    let mut manager = VRServiceManager::new();
+    manager.register_config(config);
+    assert!(manager.policy().prefer_hardware);
+    let displays = manager.get_displays();
+    assert_eq!(displays.len(), 1);
+    let data = displays[0].borrow().data();
+    assert_eq!(data.display_name, "Mock Oculus Quest");
+    assert_eq!(data.stage_parameters.unwrap().sitting_to_standing_transform[13], 1.2);
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn json_files_match_toml_files() {
+    let config = VRServiceConfig::from_json(r#"{
+        "exclusive": true,
+        "services": [{ "name": "mock", "mock_profile": "desktop" }]
+    }"#).unwrap();
+    assert_eq!(names(&config), ["mock"]);
+    assert!(config.policy().exclusive);
+    assert!(VRServiceConfig::from_json("{ \"services\": 1 }").is_err());
+}
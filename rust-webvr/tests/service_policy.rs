@@ -0,0 +1,121 @@
+extern crate rust_webvr;
+
+use rust_webvr::api::{mock_profile, MockServiceCreator};
+use rust_webvr::{MockVRControlMsg, MockVRInit, VRDisplayPtr, VREvent, VRGamepadPtr, VRService};
+use rust_webvr::{VRServiceManager, VRServicePolicy};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A mock service that pretends to drive a real headset
+struct HeadsetVRService(Box<dyn VRService>);
+
+impl VRService for HeadsetVRService {
+    fn initialize(&mut self) -> Result<(), String> {
+        self.0.initialize()
+    }
+
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, String> {
+        self.0.fetch_displays()
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String> {
+        self.0.fetch_gamepads()
+    }
+
+    fn is_available(&self) -> bool {
+        self.0.is_available()
+    }
+
+    fn poll_events(&self) -> Vec<VREvent> {
+        self.0.poll_events()
+    }
+
+    fn name(&self) -> &str {
+        "headset"
+    }
+}
+
+// Registers the default mock display first, then a vive that counts as hardware
+fn manager_with_services() -> (VRServiceManager, Sender<MockVRControlMsg>) {
+    let mut manager = VRServiceManager::new();
+    manager.register_mock();
+    let init = MockVRInit {
+        profile: Some(mock_profile("vive").unwrap()),
+        .. Default::default()
+    };
+    let (service, control) = MockServiceCreator::new_service_with_remote(init,
+                                                                         manager.clock(),
+                                                                         manager.id_allocator());
+    manager.register(Box::new(HeadsetVRService(service)));
+    (manager, control)
+}
+
+fn display_names(manager: &mut VRServiceManager) -> Vec<String> {
+    manager.get_displays().iter().map(|display| display.borrow().data().display_name).collect()
+}
+
+// The control messages are handled on another thread
+fn wait_for_displays(manager: &mut VRServiceManager, expected: &[&str]) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while display_names(manager) != expected {
+        assert!(Instant::now() < deadline, "Expected displays {:?}", expected);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn services_are_enumerated_in_priority_order() {
+    let (mut manager, _control) = manager_with_services();
+    assert_eq!(display_names(&mut manager), ["Mock VRDisplay", "Mock HTC Vive"]);
+
+    manager.set_policy(VRServicePolicy {
+        priorities: vec!["headset".into()],
+        .. Default::default()
+    });
+    assert_eq!(display_names(&mut manager), ["Mock HTC Vive", "Mock VRDisplay"]);
+}
+
+#[test]
+fn exclusive_policies_stop_at_the_first_display() {
+    let (mut manager, _control) = manager_with_services();
+    manager.set_policy(VRServicePolicy {
+        exclusive: true,
+        .. Default::default()
+    });
+    assert_eq!(display_names(&mut manager), ["Mock VRDisplay"]);
+
+    manager.set_policy(VRServicePolicy {
+        priorities: vec!["headset".into()],
+        exclusive: true,
+        .. Default::default()
+    });
+    assert_eq!(display_names(&mut manager), ["Mock HTC Vive"]);
+}
+
+#[test]
+fn denied_services_are_not_queried() {
+    let (mut manager, _control) = manager_with_services();
+    manager.set_policy(VRServicePolicy {
+        deny: vec!["mock".to_owned()].into_iter().collect(),
+        .. Default::default()
+    });
+    assert_eq!(display_names(&mut manager), ["Mock HTC Vive"]);
+    let status = manager.service_status();
+    assert!(status[0].denied);
+    assert!(!status[1].denied);
+}
+
+#[test]
+fn simulated_services_are_a_fallback() {
+    let (mut manager, control) = manager_with_services();
+    manager.set_policy(VRServicePolicy::prefer_hardware());
+    assert_eq!(display_names(&mut manager), ["Mock HTC Vive"]);
+
+    // The mock is used once the headset went away
+    control.send(MockVRControlMsg::Disconnect).unwrap();
+    wait_for_displays(&mut manager, &["Mock VRDisplay"]);
+
+    control.send(MockVRControlMsg::Connect).unwrap();
+    wait_for_displays(&mut manager, &["Mock HTC Vive"]);
+}
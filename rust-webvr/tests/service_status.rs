@@ -0,0 +1,64 @@
+extern crate rust_webvr;
+
+use rust_webvr::{MockVRFaultKind, MockVRFaultPlan, MockVRFaultTrigger, MockVRInit, VRServiceManager};
+use std::thread;
+
+fn manager_with_fault(kind: MockVRFaultKind, trigger: MockVRFaultTrigger) -> VRServiceManager {
+    let mut manager = VRServiceManager::new();
+    let init = MockVRInit {
+        faults: Some(MockVRFaultPlan::single(kind, trigger)),
+        .. Default::default()
+    };
+    manager.register_mock_with_remote(init);
+    manager
+}
+
+#[test]
+fn fetch_errors_dont_fail_the_service() {
+    let mut manager = manager_with_fault(MockVRFaultKind::FailFetchDisplays, MockVRFaultTrigger::Always);
+    assert!(manager.get_displays().is_empty());
+
+    let status = &manager.service_status()[0];
+    assert!(status.initialized);
+    assert_eq!(status.last_error, None);
+    assert_eq!(status.failed_attempts, 0);
+    assert!(status.last_fetch_error.is_some());
+    assert!(!manager.retry_failed_services());
+}
+
+#[test]
+fn init_errors_are_retried() {
+    let mut manager = manager_with_fault(MockVRFaultKind::FailInitialize, MockVRFaultTrigger::Always);
+    manager.initialize_services();
+
+    let status = &manager.service_status()[0];
+    assert!(!status.initialized);
+    assert!(status.last_error.is_some());
+    assert_eq!(status.failed_attempts, 1);
+    assert_eq!(status.last_fetch_error, None);
+}
+
+#[test]
+fn availability_is_rolled_once_per_frame() {
+    let mut manager = manager_with_fault(MockVRFaultKind::Unavailable, MockVRFaultTrigger::Frame(1));
+    let display = manager.get_displays()[0].clone();
+    let available = |manager: &VRServiceManager| manager.service_status()[0].available;
+
+    // Querying the availability doesn't consume the trigger
+    assert!(available(&manager) && available(&manager));
+    display.borrow_mut().future_frame_data(0.1, 100.0).block();
+    assert!(!available(&manager) && !available(&manager));
+    display.borrow_mut().future_frame_data(0.1, 100.0).block();
+    assert!(available(&manager));
+}
+
+#[test]
+fn stalled_frames_are_dropped_when_presentation_stops() {
+    let mut manager = manager_with_fault(MockVRFaultKind::StallFrame, MockVRFaultTrigger::Always);
+    let display = manager.get_displays()[0].clone();
+    display.borrow_mut().start_present(None);
+    let future = display.borrow_mut().future_frame_data(0.1, 100.0);
+    let waiter = thread::spawn(move || future.block());
+    display.borrow_mut().stop_present();
+    assert!(waiter.join().is_err());
+}
@@ -0,0 +1,51 @@
+extern crate rust_webvr;
+
+use rust_webvr::{MockVRControlMsg, MockVRInit, MockVRTimeline, MockVRTimelineEvent, VRManualClock, VRServiceManager};
+use std::f64;
+use std::sync::Arc;
+
+fn set_position(time: f64, position: [f32; 3]) -> MockVRTimelineEvent {
+    MockVRTimelineEvent {
+        time,
+        msg: MockVRControlMsg::SetViewerPose(position, [0.0, 0.0, 0.0, 1.0]),
+    }
+}
+
+#[test]
+fn non_finite_times_are_ignored() {
+    let clock = Arc::new(VRManualClock::new(0.0));
+    let mut manager = VRServiceManager::new_with_clock(clock.clone());
+    let init = MockVRInit {
+        timeline: Some(MockVRTimeline {
+            events: vec![
+                set_position(f64::NAN, [9.0, 9.0, 9.0]),
+                set_position(20.0, [2.0, 0.0, 0.0]),
+                set_position(f64::INFINITY, [9.0, 9.0, 9.0]),
+                set_position(10.0, [1.0, 0.0, 0.0]),
+            ],
+        }),
+        .. Default::default()
+    };
+    manager.register_mock_with_remote(init);
+    let display = manager.get_displays()[0].clone();
+
+    clock.advance(15.0);
+    assert_eq!(display.borrow().immediate_frame_data(0.1, 100.0).pose.position, Some([1.0, 0.0, 0.0]));
+    clock.advance(1000.0);
+    assert_eq!(display.borrow().immediate_frame_data(0.1, 100.0).pose.position, Some([2.0, 0.0, 0.0]));
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn loading_rejects_non_finite_times() {
+    use rust_webvr::api::load_mock_timeline;
+    use std::env;
+    use std::fs;
+
+    let path = env::temp_dir().join(format!("rust-webvr-timeline-{}.ron", std::process::id()));
+    // Overflows to infinity
+    fs::write(&path, "(events: [(time: 1e999, msg: Focus)])").unwrap();
+    let result = load_mock_timeline(&path);
+    let _ = fs::remove_file(&path);
+    assert!(result.unwrap_err().starts_with("Invalid time inf"));
+}
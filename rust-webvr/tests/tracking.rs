@@ -0,0 +1,69 @@
+extern crate rust_webvr;
+
+use rust_webvr::api::MockVRDisplayHandle;
+use rust_webvr::{MockVRControlMsg, MockVRInit, MockVRTracking, VRDisplayEvent, VRDisplayPtr, VREvent, VRPose};
+use rust_webvr::VRServiceManager;
+
+const POSITION: [f32; 3] = [0.0, 1.6, 0.0];
+const ORIENTATION: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+fn set_tracking(manager: &mut VRServiceManager, handle: &MockVRDisplayHandle, tracking: MockVRTracking) -> Vec<VREvent> {
+    handle.handle_msg(MockVRControlMsg::SetTracking(tracking));
+    manager.poll_events()
+}
+
+fn changes(events: &[VREvent]) -> Vec<bool> {
+    events.iter().filter_map(|event| match *event {
+        VREvent::Display(VRDisplayEvent::Change(ref data)) => Some(data.capabilities.has_position),
+        _ => None,
+    }).collect()
+}
+
+fn viewer_pose(display: &VRDisplayPtr) -> VRPose {
+    display.borrow().immediate_frame_data(0.1, 1000.0).pose
+}
+
+#[test]
+fn degraded_tracking_nulls_the_pose() {
+    let mut manager = VRServiceManager::new();
+    let remote = manager.register_mock_with_displays();
+    let (_, _, handle) = remote.add_display_with_handle(MockVRInit {
+        viewer_origin: Some((POSITION, ORIENTATION)),
+        .. Default::default()
+    });
+    let display = manager.get_displays().pop().unwrap();
+    manager.poll_events();
+    assert!(display.borrow().data().capabilities.has_position);
+
+    let events = set_tracking(&mut manager, &handle, MockVRTracking::OrientationOnly);
+    assert_eq!(changes(&events), [false]);
+    assert!(!display.borrow().data().capabilities.has_position);
+    let pose = viewer_pose(&display);
+    assert_eq!(pose.position, None);
+    assert_eq!(pose.linear_velocity, None);
+    assert_eq!(pose.orientation, Some(ORIENTATION));
+
+    // Setting the same tracking again changes nothing
+    assert!(set_tracking(&mut manager, &handle, MockVRTracking::OrientationOnly).is_empty());
+
+    let events = set_tracking(&mut manager, &handle, MockVRTracking::Emulated);
+    assert_eq!(changes(&events), [false]);
+    let pose = viewer_pose(&display);
+    assert_eq!(pose.position, Some(POSITION));
+    assert!(pose.emulated_position);
+
+    let events = set_tracking(&mut manager, &handle, MockVRTracking::Lost);
+    assert_eq!(changes(&events), [false]);
+    let pose = viewer_pose(&display);
+    assert_eq!(pose.position, None);
+    assert_eq!(pose.orientation, None);
+    assert!(!pose.emulated_position);
+
+    let events = set_tracking(&mut manager, &handle, MockVRTracking::Full);
+    assert_eq!(changes(&events), [true]);
+    assert!(display.borrow().data().capabilities.has_position);
+    let pose = viewer_pose(&display);
+    assert_eq!(pose.position, Some(POSITION));
+    assert_eq!(pose.orientation, Some(ORIENTATION));
+    assert!(!pose.emulated_position);
+}